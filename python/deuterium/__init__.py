@@ -0,0 +1,2 @@
+from ._deuterium import *
+from ._deuterium import __doc__
@@ -0,0 +1,215 @@
+"""Assertion helpers for testing code which uses deuterium.
+
+Hypothesis strategies live in `deuterium.testing.strategies` and the pytest
+plugin, which explains failed comparisons against `approx`, in
+`deuterium.testing.pytest_plugin`.
+"""
+
+from .. import (
+    FrozenIsometry3,
+    FrozenUnitQuaternion,
+    FrozenVector3,
+    Isometry3,
+    Matrix3,
+    Matrix4,
+    UnitDualQuaternion,
+    UnitQuaternion,
+    Vector3,
+    Vector4,
+    tolerance,
+)
+
+__all__ = ["DEUTERIUM_TYPES", "Approx", "approx", "assert_close", "components"]
+
+# Every deuterium value type, which `components` and so the other helpers
+# understand
+DEUTERIUM_TYPES = (
+    Vector3,
+    Vector4,
+    UnitQuaternion,
+    Isometry3,
+    UnitDualQuaternion,
+    Matrix3,
+    Matrix4,
+    FrozenVector3,
+    FrozenUnitQuaternion,
+    FrozenIsometry3,
+)
+
+_QUAT_LABELS = ("i", "j", "k", "w")
+
+
+def components(value):
+    """Returns the labelled scalar components of a deuterium value as a list of
+    (label, float) pairs, in a stable order."""
+    if isinstance(value, (Vector3, FrozenVector3)):
+        return list(zip(("x", "y", "z"), value.tuple()))
+    if isinstance(value, Vector4):
+        return list(zip(("x", "y", "z", "w"), value.tuple()))
+    if isinstance(value, (UnitQuaternion, FrozenUnitQuaternion)):
+        return list(zip(_QUAT_LABELS, value.tuple()))
+    if isinstance(value, (Isometry3, FrozenIsometry3)):
+        return [("translation." + l, c) for l, c in components(value.translation)] + [
+            ("rotation." + l, c) for l, c in components(value.rotation)
+        ]
+    if isinstance(value, UnitDualQuaternion):
+        real, dual = value.tuple()
+        return [("real." + l, c) for l, c in zip(_QUAT_LABELS, real)] + [
+            ("dual." + l, c) for l, c in zip(_QUAT_LABELS, dual)
+        ]
+    if isinstance(value, (Matrix3, Matrix4)):
+        return [
+            ("[{}, {}]".format(r, c), v)
+            for r, row in enumerate(value.tuple())
+            for c, v in enumerate(row)
+        ]
+    raise TypeError("{} is not a deuterium value".format(type(value).__name__))
+
+
+def _is_close(a, b, abs_tol, rel_tol):
+    if a == b:
+        return True
+    diff = abs(a - b)
+    return diff <= abs_tol or diff <= rel_tol * max(abs(a), abs(b))
+
+
+def _negated(value):
+    """The components of `value` with its quaternion's sign flipped, which is
+    the same rotation."""
+    if isinstance(value, (UnitQuaternion, Isometry3, FrozenUnitQuaternion, FrozenIsometry3)):
+        return [(l, c if l.startswith("translation.") else -c) for l, c in components(value)]
+    return None
+
+
+def _join(label, component):
+    return label + component if not label or component.startswith("[") else label + "." + component
+
+
+def _compare(actual, expected, abs_tol, rel_tol, as_rotation, label=""):
+    """Returns a list of (label, actual, expected, difference) for every component
+    which isn't close, recursing into lists and tuples of values."""
+    if isinstance(expected, (list, tuple)):
+        if not isinstance(actual, (list, tuple)):
+            return [(label or "value", actual, expected, "type mismatch")]
+        if len(actual) != len(expected):
+            return [(label or "value", len(actual), len(expected), "length mismatch")]
+        found = []
+        for i, (a, e) in enumerate(zip(actual, expected)):
+            found += _compare(a, e, abs_tol, rel_tol, as_rotation, "{}[{}]".format(label, i))
+        return found
+
+    if isinstance(expected, (int, float)) and not isinstance(expected, bool):
+        if not isinstance(actual, (int, float)):
+            return [(label or "value", actual, expected, "type mismatch")]
+        if _is_close(actual, expected, abs_tol, rel_tol):
+            return []
+        return [(label or "value", actual, expected, actual - expected)]
+
+    if type(actual) is not type(expected):
+        return [(label or "value", actual, expected, "type mismatch")]
+
+    def differing(expected_components):
+        return [
+            (_join(label, la), a, e, a - e)
+            for (la, a), (_, e) in zip(components(actual), expected_components)
+            if not _is_close(a, e, abs_tol, rel_tol)
+        ]
+
+    found = differing(components(expected))
+    negated = _negated(expected) if as_rotation else None
+    if found and negated is not None:
+        flipped = differing(negated)
+        if len(flipped) < len(found):
+            found = flipped
+    return found
+
+
+def _format(v):
+    return repr(v) if isinstance(v, float) else str(v)
+
+
+def _tolerances(abs_tol, rel_tol):
+    default_abs, default_rel, _ = tolerance.current()
+    abs_tol = default_abs if abs_tol is None else abs_tol
+    rel_tol = default_rel if rel_tol is None else rel_tol
+    return abs_tol, rel_tol
+
+
+def _table(found):
+    rows = [("component", "actual", "expected", "difference")]
+    rows += [tuple(_format(v) for v in row) for row in found]
+    widths = [max(len(row[i]) for row in rows) for i in range(4)]
+    return ["  " + "  ".join(cell.ljust(w) for cell, w in zip(row, widths)).rstrip() for row in rows]
+
+
+class Approx:
+    """An expected value which compares equal to values close to it, see
+    `approx`."""
+
+    def __init__(self, expected, abs_tol, rel_tol, as_rotation):
+        self.expected = expected
+        self.abs_tol = abs_tol
+        self.rel_tol = rel_tol
+        self.as_rotation = as_rotation
+
+    def mismatches(self, actual):
+        """Returns a (component, actual, expected, difference) tuple for
+        every component of `actual` which isn't close to the expected value."""
+        return _compare(actual, self.expected, self.abs_tol, self.rel_tol, self.as_rotation)
+
+    def explain(self, actual):
+        """Returns lines describing why `actual` isn't close, as in
+        `assert_close`'s message."""
+        found = self.mismatches(actual)
+        if not found:
+            return []
+        header = "{!r} is not close to {!r} (abs_tol={}, rel_tol={})".format(
+            actual, self.expected, self.abs_tol, self.rel_tol
+        )
+        return [header] + _table(found)
+
+    def __eq__(self, actual):
+        return not self.mismatches(actual)
+
+    def __ne__(self, actual):
+        return not self == actual
+
+    __hash__ = None
+
+    def __repr__(self):
+        return "approx({!r})".format(self.expected)
+
+
+def approx(expected, *, abs_tol=None, rel_tol=None, as_rotation=False):
+    """Wraps `expected` so that `actual == approx(expected)` compares them
+    the way `assert_close` does, for use in plain `assert` statements.
+
+    Tolerances which aren't given are taken from the thread's current
+    defaults when `approx` is called. With the pytest plugin installed a
+    failed comparison lists the components which differ.
+
+    Use this in place of `pytest.approx`, which the plugin leaves unchanged
+    and which doesn't understand deuterium values.
+    """
+    abs_tol, rel_tol = _tolerances(abs_tol, rel_tol)
+    return Approx(expected, abs_tol, rel_tol, as_rotation)
+
+
+def assert_close(actual, expected, *, abs_tol=None, rel_tol=None, as_rotation=False, msg=None):
+    """Asserts that `actual` is approximately equal to `expected`.
+
+    Works on every deuterium type, on plain numbers and on (nested) lists and
+    tuples of them. On failure the AssertionError lists every component which
+    differs, along with both values and their difference.
+
+    Tolerances which aren't given are taken from the thread's current defaults,
+    see `deuterium.tolerance`. If `as_rotation` is true quaternions which only
+    differ in sign, and so represent the same rotation, are considered equal.
+    """
+    expected = approx(expected, abs_tol=abs_tol, rel_tol=rel_tol, as_rotation=as_rotation)
+    lines = expected.explain(actual)
+    if not lines:
+        return
+    if msg:
+        lines.insert(0, str(msg))
+    raise AssertionError("\n".join(lines))
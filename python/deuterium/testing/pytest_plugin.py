@@ -0,0 +1,25 @@
+"""A pytest plugin which explains failed comparisons against
+`deuterium.testing.approx`.
+
+It is registered automatically through the `pytest11` entry point when
+deuterium is installed, after which a failing `assert v == approx(expected)`
+lists every component of `v` which differs from `expected`. It doesn't
+change the behaviour of any comparison.
+
+The plugin doesn't extend `pytest.approx` itself, which pytest offers no
+supported way to do, so `pytest.approx` still doesn't understand deuterium
+values. Compare them with `deuterium.testing.approx` instead, which takes
+`abs_tol` and `rel_tol` in place of `pytest.approx`'s `abs` and `rel`.
+"""
+
+from . import Approx
+
+
+def pytest_assertrepr_compare(config, op, left, right):
+    if op != "==":
+        return None
+    if isinstance(right, Approx):
+        return right.explain(left) or None
+    if isinstance(left, Approx):
+        return left.explain(right) or None
+    return None
@@ -0,0 +1,85 @@
+"""Hypothesis strategies which generate deuterium values.
+
+Requires the `hypothesis` package, which is installed by the `testing` extra.
+"""
+
+import math
+
+from hypothesis import strategies as st
+
+from .. import Isometry3, Matrix4, UnitQuaternion, Vector3
+
+__all__ = ["vectors", "unit_quaternions", "isometries", "invertible_matrices"]
+
+
+def _floats(min_value, max_value):
+    return st.floats(
+        min_value=min_value, max_value=max_value, allow_nan=False, allow_infinity=False
+    )
+
+
+def vectors(min_value=-1e6, max_value=1e6):
+    """Generates finite `Vector3` values with components in [min_value, max_value]."""
+    c = _floats(min_value, max_value)
+    return st.builds(Vector3, c, c, c)
+
+
+def _quaternion_from_components(i, j, k, w):
+    # Keep w positive so that the angle lies in [0, pi] and acos is well conditioned
+    if w < 0:
+        i, j, k, w = -i, -j, -k, -w
+    s = math.sqrt(i * i + j * j + k * k)
+    if s < 1e-12:
+        return UnitQuaternion.identity()
+    return UnitQuaternion.from_axis_angle(Vector3(i, j, k), 2.0 * math.atan2(s, w))
+
+
+@st.composite
+def unit_quaternions(draw):
+    """Generates `UnitQuaternion` rotations uniformly distributed over SO(3).
+
+    Uses Shoemake's subgroup algorithm, which maps three uniform samples in
+    [0, 1] onto the unit 3-sphere.
+    """
+    u1, u2, u3 = (draw(_floats(0.0, 1.0)) for _ in range(3))
+    a, b = math.sqrt(1.0 - u1), math.sqrt(u1)
+    return _quaternion_from_components(
+        a * math.sin(2.0 * math.pi * u2),
+        a * math.cos(2.0 * math.pi * u2),
+        b * math.sin(2.0 * math.pi * u3),
+        b * math.cos(2.0 * math.pi * u3),
+    )
+
+
+@st.composite
+def isometries(draw, translations=None, rotations=None):
+    """Generates `Isometry3` values from the given translation and rotation
+    strategies, which default to `vectors()` and `unit_quaternions()`."""
+    iso = Isometry3.from_translation(draw(translations or vectors()))
+    iso.rotation = draw(rotations or unit_quaternions())
+    return iso
+
+
+@st.composite
+def invertible_matrices(draw, max_value=10.0, min_pivot=0.1):
+    """Generates invertible `Matrix4` values.
+
+    Each matrix is built as the product of a unit lower triangular matrix and an
+    upper triangular matrix whose diagonal entries have a magnitude of at least
+    `min_pivot`, so it is invertible by construction without any filtering.
+    """
+    entry = _floats(-max_value, max_value)
+    pivot = _floats(min_pivot, max_value)
+    lower = [[0.0] * 4 for _ in range(4)]
+    upper = [[0.0] * 4 for _ in range(4)]
+    for r in range(4):
+        lower[r][r] = 1.0
+        upper[r][r] = draw(pivot) * draw(st.sampled_from((-1.0, 1.0)))
+        for c in range(r):
+            lower[r][c] = draw(entry)
+        for c in range(r + 1, 4):
+            upper[r][c] = draw(entry)
+
+    return Matrix4.from_rows(
+        [[sum(lower[r][k] * upper[k][c] for k in range(4)) for c in range(4)] for r in range(4)]
+    )
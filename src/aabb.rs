@@ -0,0 +1,156 @@
+use crate::points::extract_finite_points;
+use crate::shape::{self, Transform};
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
+
+/// An axis-aligned bounding box, the points between `min` and `max` inclusive.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct Aabb {
+    pub min: na::Vector3<f64>,
+    pub max: na::Vector3<f64>,
+}
+
+#[pymethods]
+impl Aabb {
+    /// Creates a box from its minimum and maximum corners, raising a
+    /// ValueError if any component of `min` is greater than that of `max`.
+    #[new]
+    fn new(min: &Vector3, max: &Vector3) -> PyResult<Aabb> {
+        if !min.0.iter().zip(max.0.iter()).all(|(lo, hi)| lo <= hi) {
+            return Err(PyValueError::new_err(format!(
+                "Aabb min {} must not be greater than max {}",
+                shape::repr(&min.0),
+                shape::repr(&max.0)
+            )));
+        }
+        Ok(Aabb {
+            min: min.0,
+            max: max.0,
+        })
+    }
+
+    /// Returns the smallest box containing every point, which may be given as
+    /// a list of Vector3s, a list of 3 element sequences or an N x 3 array.
+    /// Raises a ValueError if any point isn't finite.
+    #[staticmethod]
+    fn from_points(py: Python, points: &PyAny) -> PyResult<Aabb> {
+        let points = extract_finite_points(py, points)?;
+        let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| {
+            (min.inf(p), max.sup(p))
+        });
+        Ok(Aabb { min, max })
+    }
+
+    /// Creates a box from its center and the (non-negative) distances from
+    /// the center to its faces.
+    #[staticmethod]
+    fn from_center_half_extents(center: &Vector3, half_extents: &Vector3) -> PyResult<Aabb> {
+        Aabb::new(
+            &Vector3(center.0 - half_extents.0),
+            &Vector3(center.0 + half_extents.0),
+        )
+    }
+
+    #[getter]
+    fn get_min(&self) -> Vector3 {
+        Vector3(self.min)
+    }
+
+    #[getter]
+    fn get_max(&self) -> Vector3 {
+        Vector3(self.max)
+    }
+
+    fn center(&self) -> Vector3 {
+        Vector3(self.min.lerp(&self.max, 0.5))
+    }
+
+    /// Returns the distances from the center of the box to its faces.
+    fn half_extents(&self) -> Vector3 {
+        Vector3((self.max - self.min) * 0.5)
+    }
+
+    fn size(&self) -> Vector3 {
+        Vector3(self.max - self.min)
+    }
+
+    fn volume(&self) -> f64 {
+        (self.max - self.min).product()
+    }
+
+    fn contains_point(&self, point: &Vector3) -> bool {
+        (0..3).all(|i| self.min[i] <= point.0[i] && point.0[i] <= self.max[i])
+    }
+
+    /// Returns whether this box and `other` share any points.
+    fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// Returns the smallest box containing both this box and `other`.
+    fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    /// Returns the smallest axis-aligned box containing the transformed box,
+    /// which is usually larger than the box itself when rotated.
+    fn transformed(&self, arg: &PyAny) -> PyResult<Aabb> {
+        let t = Transform::extract(arg, "Aabb")?;
+        let center = t.point(&self.min.lerp(&self.max, 0.5));
+        let half_extents = t.linear().abs() * ((self.max - self.min) * 0.5);
+        Ok(Aabb {
+            min: center - half_extents,
+            max: center + half_extents,
+        })
+    }
+
+    fn transform(&mut self, arg: &PyAny) -> PyResult<()> {
+        *self = self.transformed(arg)?;
+        Ok(())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Aabb, op: CompareOp) -> Py<PyAny> {
+        let eq = self.min == other.min && self.max == other.max;
+        match op {
+            CompareOp::Eq => eq.into_py(py),
+            CompareOp::Ne => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &Aabb,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        Ok(tol.eq(&self.min, &other.min) && tol.eq(&self.max, &other.max))
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (Vector3, Vector3)) {
+        (
+            py.get_type::<Aabb>().into(),
+            (self.get_min(), self.get_max()),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Aabb(min={}, max={})",
+            shape::repr(&self.min),
+            shape::repr(&self.max)
+        )
+    }
+}
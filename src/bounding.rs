@@ -0,0 +1,171 @@
+use crate::iso::Isometry3;
+use crate::points::{self, extract_finite_points};
+use crate::results::NamedTuple;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::prelude::*;
+
+type V3 = na::Vector3<f64>;
+
+static ORIENTED_BOX: NamedTuple = NamedTuple::new("OrientedBox", &["pose", "half_extents"]);
+
+/// Points this close to a ball's surface, relative to its radius, are
+/// treated as inside it so rounding can't make Welzl's algorithm loop.
+const CONTAINS_EPSILON: f64 = 1e-12;
+
+#[derive(Clone, Copy)]
+pub struct Ball {
+    pub center: V3,
+    pub radius: f64,
+}
+
+impl Ball {
+    fn contains(&self, p: &V3) -> bool {
+        (p - self.center).magnitude() <= self.radius * (1.0 + CONTAINS_EPSILON)
+    }
+
+    fn contains_all(&self, points: &[V3]) -> bool {
+        points.iter().all(|p| self.contains(p))
+    }
+
+    /// The smallest ball with every point of `boundary` on its surface, or
+    /// None if the points are degenerate (collinear or coplanar).
+    fn circumscribing(boundary: &[V3]) -> Option<Ball> {
+        match *boundary {
+            [] => Some(Ball {
+                center: V3::zeros(),
+                radius: -1.0,
+            }),
+            [a] => Some(Ball {
+                center: a,
+                radius: 0.0,
+            }),
+            [a, b] => Some(Ball {
+                center: (a + b) * 0.5,
+                radius: (b - a).magnitude() * 0.5,
+            }),
+            [a, b, c] => {
+                let (ab, ac) = (b - a, c - a);
+                let n = ab.cross(&ac);
+                let n2 = n.magnitude_squared();
+                if n2 <= f64::EPSILON * ab.magnitude_squared() * ac.magnitude_squared() {
+                    return None;
+                }
+                let offset = (n.cross(&ab) * ac.magnitude_squared()
+                    + ac.cross(&n) * ab.magnitude_squared())
+                    / (2.0 * n2);
+                Some(Ball {
+                    center: a + offset,
+                    radius: offset.magnitude(),
+                })
+            }
+            [a, b, c, d] => {
+                let (ab, ac, ad) = (b - a, c - a, d - a);
+                let m = na::Matrix3::from_rows(&[ab.transpose(), ac.transpose(), ad.transpose()]);
+                let scale = ab.magnitude() * ac.magnitude() * ad.magnitude();
+                if m.determinant().abs() <= f64::EPSILON * scale {
+                    return None;
+                }
+                let rhs = V3::new(
+                    ab.magnitude_squared(),
+                    ac.magnitude_squared(),
+                    ad.magnitude_squared(),
+                ) * 0.5;
+                let offset = m.lu().solve(&rhs)?;
+                Some(Ball {
+                    center: a + offset,
+                    radius: offset.magnitude(),
+                })
+            }
+            _ => unreachable!("a ball is defined by at most 4 boundary points"),
+        }
+    }
+
+    /// The smallest ball containing every point of `boundary` with at least
+    /// some of them on its surface. This is the circumscribing ball unless
+    /// the points are degenerate, in which case a subset of them defines it.
+    fn from_boundary(boundary: &[V3]) -> Ball {
+        if let Some(ball) = Ball::circumscribing(boundary) {
+            return ball;
+        }
+        let n = boundary.len();
+        (1..(1u32 << n) - 1)
+            .filter_map(|mask| {
+                let subset: Vec<V3> = (0..n)
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| boundary[i])
+                    .collect();
+                Ball::circumscribing(&subset)
+            })
+            .filter(|ball| ball.contains_all(boundary))
+            .min_by(|a, b| a.radius.total_cmp(&b.radius))
+            .expect("the two furthest apart points always contain the rest")
+    }
+}
+
+/// Welzl's algorithm with the move-to-front heuristic: the smallest ball
+/// containing the first `end` points with `boundary` on its surface.
+fn move_to_front(points: &mut [V3], end: usize, boundary: &mut Vec<V3>) -> Ball {
+    let mut ball = Ball::from_boundary(boundary);
+    if boundary.len() == 4 {
+        return ball;
+    }
+    for i in 0..end {
+        if !ball.contains(&points[i]) {
+            boundary.push(points[i]);
+            ball = move_to_front(points, i, boundary);
+            boundary.pop();
+            points[..=i].rotate_right(1);
+        }
+    }
+    ball
+}
+
+/// The smallest ball containing every point, which must not be empty and
+/// must all be finite.
+pub fn minimal_ball(points: &[V3]) -> Ball {
+    let mut points = points.to_vec();
+    // Welzl's algorithm runs in expected linear time for points in a random
+    // order, so shuffle them (deterministically) to avoid sorted inputs
+    // hitting the worst case.
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    for i in (1..points.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        points.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+    let len = points.len();
+    move_to_front(&mut points, len, &mut Vec::with_capacity(4))
+}
+
+/// Returns a box which contains every point, oriented along the principal
+/// axes of the points, as an `OrientedBox(pose, half_extents)`.
+///
+/// The pose is an Isometry3 from the box's frame, in which it spans from
+/// `-half_extents` to `half_extents`, to the points' frame. Its x axis is the
+/// direction the points vary most along and its z axis the direction they
+/// vary least along, so the box is flat along z for coplanar points and along
+/// y and z for collinear points.
+///
+/// Raises a ValueError if any point isn't finite.
+///
+/// Note: This box is usually small, but isn't the smallest possible.
+#[pyfunction]
+pub fn oriented_bounding_box(py: Python, points: &PyAny) -> PyResult<PyObject> {
+    let points = extract_finite_points(py, points)?;
+    let (mean, covariance) = points::mean_and_covariance(&points);
+    let (axes, _) = points::principal_axes(covariance);
+    let (mut min, mut max) = (V3::repeat(f64::INFINITY), V3::repeat(f64::NEG_INFINITY));
+    for p in &points {
+        let local = axes.inverse_transform_vector(&(p - mean));
+        min = min.inf(&local);
+        max = max.sup(&local);
+    }
+    let center = mean + axes * ((min + max) * 0.5);
+    let pose = na::Isometry3::from_parts(
+        center.into(),
+        na::UnitQuaternion::from_rotation_matrix(&axes),
+    );
+    ORIENTED_BOX.make(py, (Isometry3(pose), Vector3((max - min) * 0.5)))
+}
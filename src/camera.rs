@@ -0,0 +1,393 @@
+use crate::iso::Isometry3;
+use crate::mat3::Matrix3;
+use crate::plane::Plane;
+use crate::points::{extract_pairs, extract_points};
+use crate::ray::Ray;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
+
+type V3 = na::Vector3<f64>;
+
+/// Moves a world point into camera coordinates with a world to camera pose.
+fn to_camera(pose: Option<&Isometry3>, p: &V3) -> V3 {
+    match pose {
+        Some(pose) => pose.0.transform_point(&na::Point3::from(*p)).coords,
+        None => *p,
+    }
+}
+
+/// Moves a point in camera coordinates back into the world.
+fn to_world(pose: Option<&Isometry3>, p: &V3) -> V3 {
+    match pose {
+        Some(pose) => pose.0.inverse_transform_point(&na::Point3::from(*p)).coords,
+        None => *p,
+    }
+}
+
+fn check_depth_range(near: f64, far: f64) -> PyResult<()> {
+    if near.is_nan() || near < 0.0 || far.is_nan() || far <= near {
+        return Err(PyValueError::new_err(
+            "near must be non-negative and far greater than near",
+        ));
+    }
+    Ok(())
+}
+
+/// An ideal pinhole camera with focal lengths `fx` and `fy` and principal
+/// point `(cx, cy)` in pixels, producing `width` x `height` pixel images.
+///
+/// Camera coordinates follow the usual computer vision convention: x to the
+/// right, y down and z forward along the optical axis. Pixel coordinates put
+/// `(0, 0)` at the center of the top left pixel, so the image covers
+/// `-0.5 <= u <= width - 0.5` and `-0.5 <= v <= height - 0.5`.
+///
+/// Methods taking points in the world accept an optional `pose`, an
+/// Isometry3 mapping world coordinates into camera coordinates (the camera's
+/// extrinsics). Without one, points are in camera coordinates.
+#[pyclass(module = "deuterium")]
+#[derive(Clone, PartialEq)]
+pub struct PinholeCamera {
+    pub fx: f64,
+    pub fy: f64,
+    pub cx: f64,
+    pub cy: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PinholeCamera {
+    /// The pixel a point in camera coordinates projects to, or None if it
+    /// isn't in front of the camera.
+    pub fn project_point(&self, p: &V3) -> Option<(f64, f64)> {
+        if p.z > 0.0 {
+            Some(self.normalized_to_pixel(p.x / p.z, p.y / p.z))
+        } else {
+            None
+        }
+    }
+
+    /// The pixel at normalized image coordinates `(x, y)`, i.e. the
+    /// projection of the point `(x, y, 1)` in camera coordinates.
+    pub fn normalized_to_pixel(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.fx * x + self.cx, self.fy * y + self.cy)
+    }
+
+    /// The normalized image coordinates of a pixel.
+    pub fn pixel_to_normalized(&self, u: f64, v: f64) -> (f64, f64) {
+        ((u - self.cx) / self.fx, (v - self.cy) / self.fy)
+    }
+
+    pub fn contains(&self, (u, v): (f64, f64)) -> bool {
+        (-0.5..=self.width as f64 - 0.5).contains(&u)
+            && (-0.5..=self.height as f64 - 0.5).contains(&v)
+    }
+
+    fn visible(&self, p: &V3, near: f64, far: f64) -> bool {
+        (near..=far).contains(&p.z) && self.project_point(p).is_some_and(|uv| self.contains(uv))
+    }
+
+    /// The intrinsic matrix K, which maps a point in camera coordinates to
+    /// homogeneous pixel coordinates.
+    pub fn intrinsics(&self) -> na::Matrix3<f64> {
+        na::Matrix3::new(self.fx, 0.0, self.cx, 0.0, self.fy, self.cy, 0.0, 0.0, 1.0)
+    }
+
+    /// The inverse of the intrinsic matrix.
+    pub fn inverse_intrinsics(&self) -> na::Matrix3<f64> {
+        na::Matrix3::new(
+            1.0 / self.fx,
+            0.0,
+            -self.cx / self.fx,
+            0.0,
+            1.0 / self.fy,
+            -self.cy / self.fy,
+            0.0,
+            0.0,
+            1.0,
+        )
+    }
+
+    fn unproject_pixel(&self, u: f64, v: f64, depth: f64) -> V3 {
+        let (x, y) = self.pixel_to_normalized(u, v);
+        V3::new(x, y, 1.0) * depth
+    }
+}
+
+#[pymethods]
+impl PinholeCamera {
+    /// Creates a camera from its focal lengths and principal point in pixels
+    /// and its image size. The focal lengths must be positive.
+    #[new]
+    fn new(fx: f64, fy: f64, cx: f64, cy: f64, width: u32, height: u32) -> PyResult<PinholeCamera> {
+        if !(fx.is_finite() && fy.is_finite() && fx > 0.0 && fy > 0.0) {
+            return Err(PyValueError::new_err(
+                "Focal lengths must be positive and finite",
+            ));
+        }
+        if !(cx.is_finite() && cy.is_finite()) {
+            return Err(PyValueError::new_err("The principal point must be finite"));
+        }
+        if width == 0 || height == 0 {
+            return Err(PyValueError::new_err(
+                "Image width and height must be positive",
+            ));
+        }
+        Ok(PinholeCamera {
+            fx,
+            fy,
+            cx,
+            cy,
+            width,
+            height,
+        })
+    }
+
+    #[getter]
+    fn get_fx(&self) -> f64 {
+        self.fx
+    }
+
+    #[getter]
+    fn get_fy(&self) -> f64 {
+        self.fy
+    }
+
+    #[getter]
+    fn get_cx(&self) -> f64 {
+        self.cx
+    }
+
+    #[getter]
+    fn get_cy(&self) -> f64 {
+        self.cy
+    }
+
+    #[getter]
+    fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    #[getter]
+    fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the pixel `(u, v)` that `point` projects to. Raises a
+    /// ValueError if the point isn't in front of the camera.
+    ///
+    /// The pixel may be outside the image, use `is_visible` to check.
+    #[pyo3(signature = (point, pose=None))]
+    fn project(&self, point: &Vector3, pose: Option<&Isometry3>) -> PyResult<(f64, f64)> {
+        self.project_point(&to_camera(pose, &point.0))
+            .ok_or_else(|| {
+                PyValueError::new_err("Cannot project a point which is not in front of the camera")
+            })
+    }
+
+    /// Projects many points at once, taken as a list of Vector3s, a list of
+    /// 3 element sequences or an N x 3 array, returning a list of `(u, v)`
+    /// pixels. Points not in front of the camera give `(nan, nan)`.
+    #[pyo3(signature = (points, pose=None))]
+    fn project_batch(
+        &self,
+        py: Python,
+        points: &PyAny,
+        pose: Option<&Isometry3>,
+    ) -> PyResult<Vec<(f64, f64)>> {
+        let points = extract_points(py, points)?;
+        Ok(py.allow_threads(|| {
+            points
+                .iter()
+                .map(|p| {
+                    self.project_point(&to_camera(pose, p))
+                        .unwrap_or((f64::NAN, f64::NAN))
+                })
+                .collect()
+        }))
+    }
+
+    /// Returns the point which projects to pixel `(u, v)` at `depth` along the
+    /// optical axis (its z in camera coordinates, not its distance from the
+    /// camera).
+    #[pyo3(signature = (u, v, depth, pose=None))]
+    fn unproject(&self, u: f64, v: f64, depth: f64, pose: Option<&Isometry3>) -> Vector3 {
+        Vector3(to_world(pose, &self.unproject_pixel(u, v, depth)))
+    }
+
+    /// Unprojects many pixels at once, taken as a list of `(u, v)` sequences
+    /// or an N x 2 array, each at the matching entry of `depths`.
+    #[pyo3(signature = (pixels, depths, pose=None))]
+    fn unproject_batch(
+        &self,
+        py: Python,
+        pixels: &PyAny,
+        depths: Vec<f64>,
+        pose: Option<&Isometry3>,
+    ) -> PyResult<Vec<Vector3>> {
+        let pixels = extract_pairs(py, pixels, "pixel")?;
+        if depths.len() != pixels.len() {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} depths but got {}",
+                pixels.len(),
+                depths.len()
+            )));
+        }
+        let points: Vec<V3> = py.allow_threads(|| {
+            pixels
+                .iter()
+                .zip(&depths)
+                .map(|((u, v), depth)| to_world(pose, &self.unproject_pixel(*u, *v, *depth)))
+                .collect()
+        });
+        Ok(points.into_iter().map(Vector3).collect())
+    }
+
+    /// Returns the Ray from the camera's center through pixel `(u, v)`.
+    #[pyo3(signature = (u, v, pose=None))]
+    fn ray(&self, u: f64, v: f64, pose: Option<&Isometry3>) -> Ray {
+        let direction = self.unproject_pixel(u, v, 1.0);
+        let (origin, direction) = match pose {
+            Some(pose) => (
+                pose.0.inverse().translation.vector,
+                pose.0.inverse_transform_vector(&direction),
+            ),
+            None => (V3::zeros(), direction),
+        };
+        Ray {
+            origin,
+            direction: na::Unit::new_normalize(direction),
+        }
+    }
+
+    /// Returns the intrinsic matrix `K = [[fx, 0, cx], [0, fy, cy], [0, 0, 1]]`
+    /// as a Matrix3.
+    fn matrix(&self) -> Matrix3 {
+        Matrix3(self.intrinsics())
+    }
+
+    /// Returns the normalized image coordinates `(x, y)` of pixel `(u, v)`,
+    /// where the point `(x, y, 1)` in camera coordinates projects to it. Lens
+    /// distortion models work in these coordinates.
+    fn to_normalized(&self, u: f64, v: f64) -> (f64, f64) {
+        self.pixel_to_normalized(u, v)
+    }
+
+    /// Returns the pixel `(u, v)` at normalized image coordinates `(x, y)`.
+    fn to_pixel(&self, x: f64, y: f64) -> (f64, f64) {
+        self.normalized_to_pixel(x, y)
+    }
+
+    /// Returns True if pixel `(u, v)` is within the image.
+    fn contains_pixel(&self, u: f64, v: f64) -> bool {
+        self.contains((u, v))
+    }
+
+    /// Returns True if `point` is in front of the camera, projects inside
+    /// the image and has a depth from `near` to `far`, i.e. is within the
+    /// camera's view frustum.
+    #[pyo3(signature = (point, pose=None, *, near=0.0, far=f64::INFINITY))]
+    fn is_visible(
+        &self,
+        point: &Vector3,
+        pose: Option<&Isometry3>,
+        near: f64,
+        far: f64,
+    ) -> PyResult<bool> {
+        check_depth_range(near, far)?;
+        Ok(self.visible(&to_camera(pose, &point.0), near, far))
+    }
+
+    /// Checks the visibility of many points at once as `is_visible` does,
+    /// returning a list of bools.
+    #[pyo3(signature = (points, pose=None, *, near=0.0, far=f64::INFINITY))]
+    fn is_visible_batch(
+        &self,
+        py: Python,
+        points: &PyAny,
+        pose: Option<&Isometry3>,
+        near: f64,
+        far: f64,
+    ) -> PyResult<Vec<bool>> {
+        check_depth_range(near, far)?;
+        let points = extract_points(py, points)?;
+        Ok(py.allow_threads(|| {
+            points
+                .iter()
+                .map(|p| self.visible(&to_camera(pose, p), near, far))
+                .collect()
+        }))
+    }
+
+    /// Returns the planes bounding the camera's view frustum, with normals
+    /// pointing into it: the left, right, top and bottom planes through the
+    /// image edges, then planes at depths `near` and `far` if given.
+    ///
+    /// A point is within the frustum if its signed distance to every plane is
+    /// non-negative. With `pose` the planes are in world coordinates.
+    #[pyo3(signature = (near=None, far=None, pose=None))]
+    fn frustum_planes(
+        &self,
+        near: Option<f64>,
+        far: Option<f64>,
+        pose: Option<&Isometry3>,
+    ) -> PyResult<Vec<Plane>> {
+        check_depth_range(near.unwrap_or(0.0), far.unwrap_or(f64::INFINITY))?;
+        let (left, top) = self.pixel_to_normalized(-0.5, -0.5);
+        let (right, bottom) =
+            self.pixel_to_normalized(self.width as f64 - 0.5, self.height as f64 - 0.5);
+        // Each side plane passes through the camera's center
+        let mut planes: Vec<(V3, f64)> = vec![
+            (V3::new(1.0, 0.0, -left), 0.0),
+            (V3::new(-1.0, 0.0, right), 0.0),
+            (V3::new(0.0, 1.0, -top), 0.0),
+            (V3::new(0.0, -1.0, bottom), 0.0),
+        ];
+        if let Some(near) = near {
+            planes.push((V3::z(), near));
+        }
+        if let Some(far) = far {
+            planes.push((-V3::z(), -far));
+        }
+        Ok(planes
+            .into_iter()
+            .map(|(normal, offset)| {
+                let normal = na::Unit::new_normalize(normal);
+                let point = to_world(pose, &(*normal * offset));
+                let normal = match pose {
+                    Some(pose) => na::Unit::new_unchecked(pose.0.inverse_transform_vector(&normal)),
+                    None => normal,
+                };
+                Plane {
+                    offset: normal.dot(&point),
+                    normal,
+                }
+            })
+            .collect())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &PinholeCamera, op: CompareOp) -> Py<PyAny> {
+        match op {
+            CompareOp::Eq => (self == other).into_py(py),
+            CompareOp::Ne => (self != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, PyObject) {
+        (
+            py.get_type::<PinholeCamera>().into(),
+            (self.fx, self.fy, self.cx, self.cy, self.width, self.height).into_py(py),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PinholeCamera(fx={}, fy={}, cx={}, cy={}, width={}, height={})",
+            self.fx, self.fy, self.cx, self.cy, self.width, self.height
+        )
+    }
+}
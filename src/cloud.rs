@@ -0,0 +1,447 @@
+use crate::points::extract_points;
+use crate::vec3::Vector3;
+use crate::{pcd, ply, xyz};
+use nalgebra as na;
+use pyo3::exceptions::{PyBufferError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+use pyo3::AsPyPointer;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::os::raw::{c_int, c_void};
+use std::path::{Path, PathBuf};
+
+type V3 = na::Vector3<f64>;
+
+// pyo3 0.18's create_exception! checks a cfg which newer compilers don't know.
+#[allow(unexpected_cfgs)]
+mod errors {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyValueError;
+
+    create_exception!(
+        deuterium,
+        PointCloudFormatError,
+        PyValueError,
+        "Raised when a point cloud file is malformed or uses an unsupported feature of its format."
+    );
+}
+pub use errors::PointCloudFormatError;
+
+/// Points with optional per point normals and colors.
+#[derive(Clone, Default)]
+pub struct Cloud {
+    pub points: Vec<V3>,
+    pub normals: Option<Vec<V3>>,
+    pub colors: Option<Vec<[u8; 3]>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Ascii,
+    Binary { big_endian: bool },
+}
+
+/// A numeric type a point cloud file can store values as.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    pub fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    /// Decodes a value from the start of `bytes`, which must hold at least
+    /// `size()` bytes.
+    pub fn decode(self, bytes: &[u8], big_endian: bool) -> f64 {
+        macro_rules! decode {
+            ($t:ty) => {{
+                let bytes = bytes[..std::mem::size_of::<$t>()].try_into().unwrap();
+                if big_endian {
+                    <$t>::from_be_bytes(bytes) as f64
+                } else {
+                    <$t>::from_le_bytes(bytes) as f64
+                }
+            }};
+        }
+        match self {
+            Scalar::I8 => decode!(i8),
+            Scalar::U8 => decode!(u8),
+            Scalar::I16 => decode!(i16),
+            Scalar::U16 => decode!(u16),
+            Scalar::I32 => decode!(i32),
+            Scalar::U32 => decode!(u32),
+            Scalar::F32 => decode!(f32),
+            Scalar::F64 => decode!(f64),
+        }
+    }
+
+    /// Converts a color component stored as this type to a byte. Floating
+    /// point colors run from 0 to 1 and integer colors from 0 to 255.
+    pub fn color_component(self, value: f64) -> u8 {
+        let value = match self {
+            Scalar::F32 | Scalar::F64 => value * 255.0,
+            _ => value,
+        };
+        value.round().clamp(0.0, 255.0) as u8
+    }
+}
+
+/// Parses a number from a text file, describing what was found instead if
+/// it isn't one.
+pub fn parse_number(token: &str) -> Result<f64, String> {
+    token
+        .parse()
+        .map_err(|_| format!("expected a number but got '{}'", token))
+}
+
+/// Reads a point cloud file a line or a block of bytes at a time, tracking
+/// the line number for error messages.
+pub struct Input<'a> {
+    data: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(data: &'a [u8]) -> Input<'a> {
+        Input {
+            data,
+            pos: 0,
+            line: 0,
+        }
+    }
+
+    /// The next line without its line ending, or None at the end of the data.
+    pub fn line(&mut self) -> Option<Result<&'a str, String>> {
+        if self.pos >= self.data.len() {
+            return None;
+        }
+        let rest = &self.data[self.pos..];
+        let end = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        self.pos += (end + 1).min(rest.len());
+        self.line += 1;
+        let line = &rest[..end];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        Some(std::str::from_utf8(line).map_err(|_| self.error("is not valid text")))
+    }
+
+    /// The next `n` bytes, or None if there aren't that many left.
+    pub fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(bytes)
+    }
+
+    /// Prefixes `message` with the number of the line last read.
+    pub fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Ply,
+    Xyz,
+    Pcd,
+}
+
+impl Format {
+    fn resolve(path: &Path, format: Option<&str>) -> PyResult<Format> {
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+        match format.or(extension.as_deref()) {
+            Some("ply") => Ok(Format::Ply),
+            Some("xyz") => Ok(Format::Xyz),
+            Some("pcd") => Ok(Format::Pcd),
+            _ => match format {
+                Some(format) => Err(PyValueError::new_err(format!(
+                    "format must be 'ply', 'xyz' or 'pcd', not '{}'",
+                    format
+                ))),
+                None => Err(PyValueError::new_err(format!(
+                    "Can't tell the point cloud format of '{}' from its extension, pass format='ply', 'xyz' or 'pcd'",
+                    path.display()
+                ))),
+            },
+        }
+    }
+
+    fn encoding(self, encoding: Option<&str>) -> PyResult<Encoding> {
+        let little_endian = Encoding::Binary { big_endian: false };
+        match (self, encoding) {
+            (Format::Xyz, None) | (_, Some("ascii")) => Ok(Encoding::Ascii),
+            (Format::Ply, None | Some("binary" | "binary_little_endian")) => Ok(little_endian),
+            (Format::Ply, Some("binary_big_endian")) => Ok(Encoding::Binary { big_endian: true }),
+            (Format::Pcd, None | Some("binary")) => Ok(little_endian),
+            (Format::Ply, Some(encoding)) => Err(PyValueError::new_err(format!(
+                "PLY encoding must be 'ascii', 'binary', 'binary_little_endian' or 'binary_big_endian', not '{}'",
+                encoding
+            ))),
+            (Format::Pcd, Some(encoding)) => Err(PyValueError::new_err(format!(
+                "PCD encoding must be 'ascii' or 'binary', not '{}'",
+                encoding
+            ))),
+            (Format::Xyz, Some(encoding)) => Err(PyValueError::new_err(format!(
+                "XYZ files are always ascii, not '{}'",
+                encoding
+            ))),
+        }
+    }
+}
+
+/// A set of points with optional normals and colors, which can be read from
+/// and written to PLY, XYZ and PCD files.
+///
+/// The points are stored contiguously and exposed through the buffer
+/// protocol as a read-only N x 3 float64 array, so a PointCloud can be passed
+/// anywhere points are accepted, or wrapped with `numpy.asarray` without a
+/// copy. Point clouds are immutable.
+#[pyclass(module = "deuterium")]
+pub struct PointCloud {
+    cloud: Cloud,
+    // Pointed to by the buffers exposing the points
+    shape: [isize; 2],
+    strides: [isize; 2],
+}
+
+impl From<Cloud> for PointCloud {
+    fn from(cloud: Cloud) -> PointCloud {
+        PointCloud {
+            shape: [cloud.points.len() as isize, 3],
+            strides: [std::mem::size_of::<V3>() as isize, 8],
+            cloud,
+        }
+    }
+}
+
+/// The buffer protocol format code for the float64 coordinates.
+const FLOAT64_FORMAT: &std::ffi::CStr = c"d";
+
+fn to_vectors(points: &[V3]) -> Vec<Vector3> {
+    points.iter().map(|p| Vector3(*p)).collect()
+}
+
+#[pymethods]
+impl PointCloud {
+    /// Creates a point cloud from a list of Vector3s, a list of 3 element
+    /// sequences or an N x 3 array of points. `normals` are given the same
+    /// way and `colors` as a list of `(red, green, blue)` sequences of
+    /// integers from 0 to 255, each with one entry for every point.
+    #[new]
+    #[pyo3(signature = (points, normals=None, colors=None))]
+    fn new(
+        py: Python,
+        points: &PyAny,
+        normals: Option<&PyAny>,
+        colors: Option<Vec<Vec<u8>>>,
+    ) -> PyResult<PointCloud> {
+        let points = extract_points(py, points)?;
+        let check_len = |name: &str, len: usize| {
+            if len != points.len() {
+                return Err(PyValueError::new_err(format!(
+                    "Expected {} {} but got {}",
+                    points.len(),
+                    name,
+                    len
+                )));
+            }
+            Ok(())
+        };
+        let normals = match normals {
+            Some(normals) => {
+                let normals = extract_points(py, normals)?;
+                check_len("normals", normals.len())?;
+                Some(normals)
+            }
+            None => None,
+        };
+        let colors = match colors {
+            Some(colors) => {
+                check_len("colors", colors.len())?;
+                let colors = colors
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        <[u8; 3]>::try_from(c.as_slice()).map_err(|_| {
+                            PyValueError::new_err(format!(
+                                "Expected colors with 3 components but color {} has {}",
+                                i,
+                                c.len()
+                            ))
+                        })
+                    })
+                    .collect::<PyResult<_>>()?;
+                Some(colors)
+            }
+            None => None,
+        };
+        Ok(PointCloud::from(Cloud {
+            points,
+            normals,
+            colors,
+        }))
+    }
+
+    /// Reads a point cloud from a PLY, XYZ or PCD file. The format is taken
+    /// from the file's extension unless `format` is given as `"ply"`, `"xyz"`
+    /// or `"pcd"`.
+    ///
+    /// PLY files may be ascii or binary of either endianness, and PCD files
+    /// ascii or (uncompressed) binary. Normals and colors are kept when the
+    /// file has them. XYZ files have 3, 6 or 9 numbers per line: the point,
+    /// then optionally its normal and then its color from 0 to 255.
+    ///
+    /// Raises a PointCloudFormatError describing where the file is malformed.
+    #[staticmethod]
+    #[pyo3(signature = (path, format=None))]
+    fn read(py: Python, path: PathBuf, format: Option<&str>) -> PyResult<PointCloud> {
+        let format = Format::resolve(&path, format)?;
+        let data = std::fs::read(&path)?;
+        let cloud = py.allow_threads(|| match format {
+            Format::Ply => ply::read(&data),
+            Format::Xyz => xyz::read(&data),
+            Format::Pcd => pcd::read(&data),
+        });
+        cloud
+            .map(PointCloud::from)
+            .map_err(|e| PointCloudFormatError::new_err(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Writes the point cloud to a PLY, XYZ or PCD file, with the format
+    /// chosen as for `read`. PLY and PCD files are binary (little endian)
+    /// unless `encoding` is `"ascii"`, and PLY files can also be written
+    /// `"binary_big_endian"`. Coordinates are written as float64 and colors
+    /// as bytes.
+    ///
+    /// XYZ files can't store colors without normals, so this raises a
+    /// ValueError for a point cloud with colors but no normals.
+    #[pyo3(signature = (path, format=None, encoding=None))]
+    fn write(
+        &self,
+        py: Python,
+        path: PathBuf,
+        format: Option<&str>,
+        encoding: Option<&str>,
+    ) -> PyResult<()> {
+        let format = Format::resolve(&path, format)?;
+        let encoding = format.encoding(encoding)?;
+        if let Format::Xyz = format {
+            if self.cloud.colors.is_some() && self.cloud.normals.is_none() {
+                return Err(PyValueError::new_err(
+                    "XYZ files can only store colors along with normals",
+                ));
+            }
+        }
+        let file = File::create(&path)?;
+        py.allow_threads(|| {
+            let mut out = BufWriter::new(file);
+            match format {
+                Format::Ply => ply::write(&mut out, &self.cloud, encoding)?,
+                Format::Xyz => xyz::write(&mut out, &self.cloud)?,
+                Format::Pcd => pcd::write(&mut out, &self.cloud, encoding)?,
+            }
+            out.flush()
+        })?;
+        Ok(())
+    }
+
+    #[getter]
+    fn points(&self) -> Vec<Vector3> {
+        to_vectors(&self.cloud.points)
+    }
+
+    /// The normal of each point, or None if the cloud has no normals.
+    #[getter]
+    fn normals(&self) -> Option<Vec<Vector3>> {
+        self.cloud.normals.as_deref().map(to_vectors)
+    }
+
+    /// The `(red, green, blue)` color of each point, or None if the cloud
+    /// has no colors.
+    #[getter]
+    fn colors(&self) -> Option<Vec<(u8, u8, u8)>> {
+        self.cloud
+            .colors
+            .as_ref()
+            .map(|colors| colors.iter().map(|[r, g, b]| (*r, *g, *b)).collect())
+    }
+
+    fn __len__(&self) -> usize {
+        self.cloud.points.len()
+    }
+
+    unsafe fn __getbuffer__(
+        slf: PyRef<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("PointCloud points are read-only"));
+        }
+        let requested = |flag: c_int| flags & flag == flag;
+        (*view).obj = slf.as_ptr();
+        ffi::Py_INCREF((*view).obj);
+        (*view).buf = slf.cloud.points.as_ptr() as *mut c_void;
+        (*view).len = (slf.cloud.points.len() * std::mem::size_of::<V3>()) as isize;
+        (*view).readonly = 1;
+        (*view).itemsize = 8;
+        (*view).format = if requested(ffi::PyBUF_FORMAT) {
+            FLOAT64_FORMAT.as_ptr() as *mut _
+        } else {
+            std::ptr::null_mut()
+        };
+        if requested(ffi::PyBUF_ND) {
+            (*view).ndim = 2;
+            (*view).shape = slf.shape.as_ptr() as *mut _;
+        } else {
+            (*view).ndim = 1;
+            (*view).shape = std::ptr::null_mut();
+        }
+        (*view).strides = if requested(ffi::PyBUF_STRIDES) {
+            slf.strides.as_ptr() as *mut _
+        } else {
+            std::ptr::null_mut()
+        };
+        (*view).suboffsets = std::ptr::null_mut();
+        (*view).internal = std::ptr::null_mut();
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, PyObject) {
+        (
+            py.get_type::<PointCloud>().into(),
+            (self.points(), self.normals(), self.colors()).into_py(py),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        let mut repr = format!("PointCloud({} points", self.cloud.points.len());
+        if self.cloud.normals.is_some() {
+            repr += ", normals";
+        }
+        if self.cloud.colors.is_some() {
+            repr += ", colors";
+        }
+        repr + ")"
+    }
+}
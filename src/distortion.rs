@@ -0,0 +1,581 @@
+use crate::points::extract_pairs;
+use crate::results::NamedTuple;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::{PyTuple, PyType};
+
+type V2 = na::Vector2<f64>;
+
+static UNDISTORTED: NamedTuple =
+    NamedTuple::new("Undistorted", &["x", "y", "converged", "iterations"]);
+static UNDISTORTED_POINTS: NamedTuple =
+    NamedTuple::new("UndistortedPoints", &["points", "converged"]);
+
+/// The result of inverting a distortion model at one point.
+pub struct Undistortion {
+    pub point: V2,
+    pub converged: bool,
+    pub iterations: usize,
+}
+
+/// A lens distortion model mapping ideal normalized image coordinates to
+/// distorted ones and back.
+pub trait Model: Sync {
+    fn distort(&self, p: V2) -> V2;
+    fn undistort(&self, p: V2, max_iterations: usize, tolerance: f64) -> Undistortion;
+}
+
+/// Finds the point `f` maps to `target` with Newton's method, starting from
+/// the target itself. `f` returns its value and Jacobian at a point.
+fn newton(
+    target: V2,
+    max_iterations: usize,
+    tolerance: f64,
+    f: impl Fn(V2) -> (V2, na::Matrix2<f64>),
+) -> Undistortion {
+    let mut p = target;
+    let mut iterations = 0;
+    let converged = loop {
+        let (value, jacobian) = f(p);
+        let residual = value - target;
+        if residual.iter().all(|r| r.abs() <= tolerance) {
+            break true;
+        }
+        match jacobian.try_inverse() {
+            Some(inverse) if iterations < max_iterations => p -= inverse * residual,
+            _ => break false,
+        }
+        iterations += 1;
+    };
+    Undistortion {
+        point: p,
+        converged,
+        iterations,
+    }
+}
+
+fn check_iteration_settings(tolerance: f64) -> PyResult<()> {
+    if tolerance.is_nan() || tolerance <= 0.0 {
+        return Err(PyValueError::new_err("tolerance must be a positive number"));
+    }
+    Ok(())
+}
+
+fn check_finite(coefficients: &[f64]) -> PyResult<()> {
+    if !coefficients.iter().all(|c| c.is_finite()) {
+        return Err(PyValueError::new_err(
+            "Distortion coefficients must be finite",
+        ));
+    }
+    Ok(())
+}
+
+fn undistort(
+    py: Python,
+    model: &impl Model,
+    x: f64,
+    y: f64,
+    max_iterations: usize,
+    tolerance: f64,
+) -> PyResult<PyObject> {
+    check_iteration_settings(tolerance)?;
+    let result = model.undistort(V2::new(x, y), max_iterations, tolerance);
+    UNDISTORTED.make(
+        py,
+        (
+            result.point.x,
+            result.point.y,
+            result.converged,
+            result.iterations,
+        ),
+    )
+}
+
+fn distort_batch(py: Python, model: &impl Model, points: &PyAny) -> PyResult<Vec<(f64, f64)>> {
+    let points = extract_pairs(py, points, "point")?;
+    Ok(py.allow_threads(|| {
+        points
+            .iter()
+            .map(|(x, y)| {
+                let p = model.distort(V2::new(*x, *y));
+                (p.x, p.y)
+            })
+            .collect()
+    }))
+}
+
+fn undistort_batch(
+    py: Python,
+    model: &impl Model,
+    points: &PyAny,
+    max_iterations: usize,
+    tolerance: f64,
+) -> PyResult<PyObject> {
+    check_iteration_settings(tolerance)?;
+    let points = extract_pairs(py, points, "point")?;
+    let (points, converged): (Vec<(f64, f64)>, Vec<bool>) = py.allow_threads(|| {
+        points
+            .iter()
+            .map(|(x, y)| {
+                let result = model.undistort(V2::new(*x, *y), max_iterations, tolerance);
+                ((result.point.x, result.point.y), result.converged)
+            })
+            .unzip()
+    });
+    UNDISTORTED_POINTS.make(py, (points, converged))
+}
+
+const BROWN_CONRADY_NAMES: [&str; 12] = [
+    "k1", "k2", "p1", "p2", "k3", "k4", "k5", "k6", "s1", "s2", "s3", "s4",
+];
+
+/// The Brown–Conrady lens distortion model used by OpenCV, with radial
+/// coefficients `k1` to `k6` (`k4` to `k6` making the radial term
+/// rational), tangential coefficients `p1` and `p2` and thin prism
+/// coefficients `s1` to `s4`.
+///
+/// The parameters are in the same order as OpenCV's `distCoeffs`, so a
+/// calibration's coefficients can be used directly as
+/// `BrownConrady(*dist_coeffs)`. Points are normalized image coordinates,
+/// see `PinholeCamera.to_normalized`.
+#[pyclass(module = "deuterium")]
+#[derive(Clone, PartialEq)]
+pub struct BrownConrady {
+    pub coefficients: [f64; 12],
+}
+
+impl BrownConrady {
+    /// The distorted point and the Jacobian of the distortion there.
+    fn distort_with_jacobian(&self, p: V2) -> (V2, na::Matrix2<f64>) {
+        let [k1, k2, p1, p2, k3, k4, k5, k6, s1, s2, s3, s4] = self.coefficients;
+        let (x, y) = (p.x, p.y);
+        let r2 = x * x + y * y;
+        let numerator = 1.0 + r2 * (k1 + r2 * (k2 + r2 * k3));
+        let denominator = 1.0 + r2 * (k4 + r2 * (k5 + r2 * k6));
+        let radial = numerator / denominator;
+        // The derivatives of the radial and prism terms with respect to r2
+        let d_numerator = k1 + r2 * (2.0 * k2 + 3.0 * k3 * r2);
+        let d_denominator = k4 + r2 * (2.0 * k5 + 3.0 * k6 * r2);
+        let d_radial =
+            (d_numerator * denominator - numerator * d_denominator) / (denominator * denominator);
+        let (d_prism_x, d_prism_y) = (s1 + 2.0 * s2 * r2, s3 + 2.0 * s4 * r2);
+
+        let distorted = V2::new(
+            x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x) + r2 * (s1 + s2 * r2),
+            y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y + r2 * (s3 + s4 * r2),
+        );
+        let cross = 2.0 * x * y * d_radial + 2.0 * p1 * x + 2.0 * p2 * y;
+        let jacobian = na::Matrix2::new(
+            radial + 2.0 * x * x * d_radial + 2.0 * p1 * y + 6.0 * p2 * x + 2.0 * x * d_prism_x,
+            cross + 2.0 * y * d_prism_x,
+            cross + 2.0 * x * d_prism_y,
+            radial + 2.0 * y * y * d_radial + 6.0 * p1 * y + 2.0 * p2 * x + 2.0 * y * d_prism_y,
+        );
+        (distorted, jacobian)
+    }
+
+    /// The coefficients in OpenCV's order, trimmed to the shortest length
+    /// OpenCV accepts (4, 5, 8 or 12) which keeps every non-zero coefficient.
+    fn trimmed(&self) -> &[f64] {
+        let len = [4, 5, 8, 12]
+            .into_iter()
+            .find(|len| self.coefficients[*len..].iter().all(|c| *c == 0.0))
+            .unwrap_or(12);
+        &self.coefficients[..len]
+    }
+}
+
+impl Model for BrownConrady {
+    fn distort(&self, p: V2) -> V2 {
+        self.distort_with_jacobian(p).0
+    }
+
+    fn undistort(&self, p: V2, max_iterations: usize, tolerance: f64) -> Undistortion {
+        newton(p, max_iterations, tolerance, |p| {
+            self.distort_with_jacobian(p)
+        })
+    }
+}
+
+#[pymethods]
+impl BrownConrady {
+    #[new]
+    #[pyo3(signature = (
+        k1=0.0, k2=0.0, p1=0.0, p2=0.0, k3=0.0, k4=0.0, k5=0.0, k6=0.0,
+        s1=0.0, s2=0.0, s3=0.0, s4=0.0,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        k1: f64,
+        k2: f64,
+        p1: f64,
+        p2: f64,
+        k3: f64,
+        k4: f64,
+        k5: f64,
+        k6: f64,
+        s1: f64,
+        s2: f64,
+        s3: f64,
+        s4: f64,
+    ) -> PyResult<BrownConrady> {
+        let coefficients = [k1, k2, p1, p2, k3, k4, k5, k6, s1, s2, s3, s4];
+        check_finite(&coefficients)?;
+        Ok(BrownConrady { coefficients })
+    }
+
+    /// Creates the model from a sequence of 4, 5, 8, 12 or 14 coefficients in
+    /// OpenCV's `distCoeffs` order. The two tilted sensor coefficients which
+    /// make up 14 aren't supported, and must be zero.
+    #[staticmethod]
+    fn from_coefficients(coefficients: Vec<f64>) -> PyResult<BrownConrady> {
+        if ![4, 5, 8, 12, 14].contains(&coefficients.len()) {
+            return Err(PyValueError::new_err(format!(
+                "Expected 4, 5, 8, 12 or 14 distortion coefficients but got {}",
+                coefficients.len()
+            )));
+        }
+        if coefficients.len() == 14 && (coefficients[12] != 0.0 || coefficients[13] != 0.0) {
+            return Err(PyValueError::new_err(
+                "Tilted sensor distortion coefficients are not supported",
+            ));
+        }
+        check_finite(&coefficients)?;
+        let mut all = [0.0; 12];
+        let len = coefficients.len().min(12);
+        all[..len].copy_from_slice(&coefficients[..len]);
+        Ok(BrownConrady { coefficients: all })
+    }
+
+    /// The coefficients in OpenCV's `distCoeffs` order, as the shortest list
+    /// of 4, 5, 8 or 12 which includes all the non-zero ones.
+    #[getter]
+    fn get_coefficients(&self) -> Vec<f64> {
+        self.trimmed().to_vec()
+    }
+
+    /// Applies the distortion to the normalized image coordinates `(x, y)`.
+    fn distort(&self, x: f64, y: f64) -> (f64, f64) {
+        let p = Model::distort(self, V2::new(x, y));
+        (p.x, p.y)
+    }
+
+    /// Finds the undistorted point which `distort` maps to `(x, y)` with
+    /// Newton's method, returning `Undistorted(x, y, converged, iterations)`.
+    ///
+    /// Iteration stops once the point distorts to within `tolerance` of
+    /// `(x, y)` in each coordinate, when `converged` is True, or after
+    /// `max_iterations`.
+    #[pyo3(signature = (x, y, *, max_iterations=20, tolerance=1e-12))]
+    fn undistort(
+        &self,
+        py: Python,
+        x: f64,
+        y: f64,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> PyResult<PyObject> {
+        undistort(py, self, x, y, max_iterations, tolerance)
+    }
+
+    /// Distorts many points at once, taken as a list of `(x, y)` sequences or
+    /// an N x 2 array, returning a list of `(x, y)` tuples.
+    fn distort_batch(&self, py: Python, points: &PyAny) -> PyResult<Vec<(f64, f64)>> {
+        distort_batch(py, self, points)
+    }
+
+    /// Undistorts many points at once as `undistort` does, returning
+    /// `UndistortedPoints(points, converged)` with a list of each.
+    #[pyo3(signature = (points, *, max_iterations=20, tolerance=1e-12))]
+    fn undistort_batch(
+        &self,
+        py: Python,
+        points: &PyAny,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> PyResult<PyObject> {
+        undistort_batch(py, self, points, max_iterations, tolerance)
+    }
+
+    fn __richcmp__(&self, py: Python, other: &BrownConrady, op: CompareOp) -> Py<PyAny> {
+        match op {
+            CompareOp::Eq => (self == other).into_py(py),
+            CompareOp::Ne => (self != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, Py<PyTuple>) {
+        (
+            py.get_type::<BrownConrady>().into(),
+            PyTuple::new(py, self.coefficients).into(),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        let coefficients: Vec<String> = BROWN_CONRADY_NAMES
+            .iter()
+            .zip(self.trimmed())
+            .map(|(name, c)| format!("{}={}", name, c))
+            .collect();
+        format!("BrownConrady({})", coefficients.join(", "))
+    }
+}
+
+/// The Kannala–Brandt fisheye distortion model used by OpenCV's `fisheye`
+/// module, which distorts the angle `theta` of a ray from the optical axis
+/// to `theta * (1 + k1 theta^2 + k2 theta^4 + k3 theta^6 + k4 theta^8)`.
+///
+/// The parameters are in the same order as the `D` coefficients of
+/// `cv.fisheye`. Points are normalized image coordinates, see
+/// `PinholeCamera.to_normalized`, so only rays less than 90 degrees from
+/// the optical axis can be represented.
+#[pyclass(module = "deuterium")]
+#[derive(Clone, PartialEq)]
+pub struct KannalaBrandt {
+    pub coefficients: [f64; 4],
+}
+
+impl KannalaBrandt {
+    /// The distorted angle for `theta` and its derivative.
+    fn distort_angle(&self, theta: f64) -> (f64, f64) {
+        let [k1, k2, k3, k4] = self.coefficients;
+        let t2 = theta * theta;
+        let polynomial = 1.0 + t2 * (k1 + t2 * (k2 + t2 * (k3 + t2 * k4)));
+        let derivative = 1.0 + t2 * (3.0 * k1 + t2 * (5.0 * k2 + t2 * (7.0 * k3 + t2 * 9.0 * k4)));
+        (theta * polynomial, derivative)
+    }
+}
+
+impl Model for KannalaBrandt {
+    fn distort(&self, p: V2) -> V2 {
+        let r = p.magnitude();
+        if r == 0.0 {
+            return p;
+        }
+        p * (self.distort_angle(r.atan()).0 / r)
+    }
+
+    fn undistort(&self, p: V2, max_iterations: usize, tolerance: f64) -> Undistortion {
+        let distorted_theta = p.magnitude();
+        if distorted_theta == 0.0 {
+            return Undistortion {
+                point: p,
+                converged: true,
+                iterations: 0,
+            };
+        }
+        let mut theta = distorted_theta;
+        let mut iterations = 0;
+        let converged = loop {
+            let (value, derivative) = self.distort_angle(theta);
+            if (value - distorted_theta).abs() <= tolerance {
+                break true;
+            }
+            if iterations == max_iterations || derivative == 0.0 {
+                break false;
+            }
+            theta -= (value - distorted_theta) / derivative;
+            iterations += 1;
+        };
+        // Angles from 90 degrees on can't be represented as normalized
+        // coordinates, so such solutions haven't converged on a usable point
+        let in_front = (0.0..std::f64::consts::FRAC_PI_2).contains(&theta);
+        Undistortion {
+            point: p * (theta.tan() / distorted_theta),
+            converged: converged && in_front,
+            iterations,
+        }
+    }
+}
+
+#[pymethods]
+impl KannalaBrandt {
+    #[new]
+    #[pyo3(signature = (k1=0.0, k2=0.0, k3=0.0, k4=0.0))]
+    fn new(k1: f64, k2: f64, k3: f64, k4: f64) -> PyResult<KannalaBrandt> {
+        let coefficients = [k1, k2, k3, k4];
+        check_finite(&coefficients)?;
+        Ok(KannalaBrandt { coefficients })
+    }
+
+    /// The coefficients `[k1, k2, k3, k4]`, in the order of `cv.fisheye`'s `D`.
+    #[getter]
+    fn get_coefficients(&self) -> Vec<f64> {
+        self.coefficients.to_vec()
+    }
+
+    /// Applies the distortion to the normalized image coordinates `(x, y)`.
+    fn distort(&self, x: f64, y: f64) -> (f64, f64) {
+        let p = Model::distort(self, V2::new(x, y));
+        (p.x, p.y)
+    }
+
+    /// Finds the undistorted point which `distort` maps to `(x, y)` with
+    /// Newton's method, returning `Undistorted(x, y, converged, iterations)`.
+    ///
+    /// Iteration stops once the point's distorted angle is within `tolerance`
+    /// of the angle of `(x, y)`, or after `max_iterations`. The result hasn't
+    /// converged if it's 90 degrees or more from the optical axis.
+    #[pyo3(signature = (x, y, *, max_iterations=20, tolerance=1e-12))]
+    fn undistort(
+        &self,
+        py: Python,
+        x: f64,
+        y: f64,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> PyResult<PyObject> {
+        undistort(py, self, x, y, max_iterations, tolerance)
+    }
+
+    /// Distorts many points at once, taken as a list of `(x, y)` sequences or
+    /// an N x 2 array, returning a list of `(x, y)` tuples.
+    fn distort_batch(&self, py: Python, points: &PyAny) -> PyResult<Vec<(f64, f64)>> {
+        distort_batch(py, self, points)
+    }
+
+    /// Undistorts many points at once as `undistort` does, returning
+    /// `UndistortedPoints(points, converged)` with a list of each.
+    #[pyo3(signature = (points, *, max_iterations=20, tolerance=1e-12))]
+    fn undistort_batch(
+        &self,
+        py: Python,
+        points: &PyAny,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> PyResult<PyObject> {
+        undistort_batch(py, self, points, max_iterations, tolerance)
+    }
+
+    fn __richcmp__(&self, py: Python, other: &KannalaBrandt, op: CompareOp) -> Py<PyAny> {
+        match op {
+            CompareOp::Eq => (self == other).into_py(py),
+            CompareOp::Ne => (self != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, Py<PyTuple>) {
+        (
+            py.get_type::<KannalaBrandt>().into(),
+            PyTuple::new(py, self.coefficients).into(),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        let [k1, k2, k3, k4] = self.coefficients;
+        format!("KannalaBrandt(k1={}, k2={}, k3={}, k4={})", k1, k2, k3, k4)
+    }
+}
+
+/// Fitzgibbon's single parameter division model, where a distorted point
+/// `p` undistorts to `p / (1 + k1 |p|^2)`. A negative `k1` models barrel
+/// distortion.
+///
+/// Both directions have closed forms, so `undistort` never iterates. Points
+/// are normalized image coordinates, see `PinholeCamera.to_normalized`.
+#[pyclass(module = "deuterium")]
+#[derive(Clone, PartialEq)]
+pub struct DivisionModel {
+    pub k1: f64,
+}
+
+impl Model for DivisionModel {
+    /// Solves `r = d / (1 + k1 d^2)` for the distorted radius `d`, taking the
+    /// root which tends to `r` as `k1` tends to zero. Points with no solution
+    /// distort to NaN.
+    fn distort(&self, p: V2) -> V2 {
+        let r2 = p.magnitude_squared();
+        let discriminant = 1.0 - 4.0 * self.k1 * r2;
+        if discriminant < 0.0 {
+            return V2::repeat(f64::NAN);
+        }
+        p * (2.0 / (1.0 + discriminant.sqrt()))
+    }
+
+    fn undistort(&self, p: V2, _max_iterations: usize, _tolerance: f64) -> Undistortion {
+        let point = p / (1.0 + self.k1 * p.magnitude_squared());
+        Undistortion {
+            point,
+            converged: point.iter().all(|c| c.is_finite()),
+            iterations: 0,
+        }
+    }
+}
+
+#[pymethods]
+impl DivisionModel {
+    #[new]
+    #[pyo3(signature = (k1=0.0))]
+    fn new(k1: f64) -> PyResult<DivisionModel> {
+        check_finite(&[k1])?;
+        Ok(DivisionModel { k1 })
+    }
+
+    #[getter]
+    fn get_k1(&self) -> f64 {
+        self.k1
+    }
+
+    /// Applies the distortion to the normalized image coordinates `(x, y)`.
+    /// Points further out than the model can distort give `(nan, nan)`.
+    fn distort(&self, x: f64, y: f64) -> (f64, f64) {
+        let p = Model::distort(self, V2::new(x, y));
+        (p.x, p.y)
+    }
+
+    /// Returns the undistorted point which `distort` maps to `(x, y)` as
+    /// `Undistorted(x, y, converged, iterations)`. This model undistorts in
+    /// closed form, so `iterations` is always 0 and `converged` is only False
+    /// if the result isn't finite. The keyword arguments are accepted for
+    /// compatibility with the other models.
+    #[pyo3(signature = (x, y, *, max_iterations=20, tolerance=1e-12))]
+    fn undistort(
+        &self,
+        py: Python,
+        x: f64,
+        y: f64,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> PyResult<PyObject> {
+        undistort(py, self, x, y, max_iterations, tolerance)
+    }
+
+    /// Distorts many points at once, taken as a list of `(x, y)` sequences or
+    /// an N x 2 array, returning a list of `(x, y)` tuples.
+    fn distort_batch(&self, py: Python, points: &PyAny) -> PyResult<Vec<(f64, f64)>> {
+        distort_batch(py, self, points)
+    }
+
+    /// Undistorts many points at once as `undistort` does, returning
+    /// `UndistortedPoints(points, converged)` with a list of each.
+    #[pyo3(signature = (points, *, max_iterations=20, tolerance=1e-12))]
+    fn undistort_batch(
+        &self,
+        py: Python,
+        points: &PyAny,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> PyResult<PyObject> {
+        undistort_batch(py, self, points, max_iterations, tolerance)
+    }
+
+    fn __richcmp__(&self, py: Python, other: &DivisionModel, op: CompareOp) -> Py<PyAny> {
+        match op {
+            CompareOp::Eq => (self == other).into_py(py),
+            CompareOp::Ne => (self != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (f64,)) {
+        (py.get_type::<DivisionModel>().into(), (self.k1,))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DivisionModel(k1={})", self.k1)
+    }
+}
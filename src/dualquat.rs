@@ -0,0 +1,192 @@
+use crate::iso::Isometry3;
+use crate::mat4::Tuple4;
+use crate::quat::UnitQuaternion;
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+
+#[pyclass]
+#[derive(Clone)]
+pub struct UnitDualQuaternion(pub na::UnitDualQuaternion<f64>);
+
+#[pymethods]
+impl UnitDualQuaternion {
+    #[new]
+    fn new() -> Self {
+        UnitDualQuaternion(na::UnitDualQuaternion::identity())
+    }
+
+    #[staticmethod]
+    fn identity() -> Self {
+        UnitDualQuaternion(na::UnitDualQuaternion::identity())
+    }
+
+    #[staticmethod]
+    fn from_isometry(iso: &Isometry3) -> UnitDualQuaternion {
+        UnitDualQuaternion(na::UnitDualQuaternion::from_isometry(&iso.0))
+    }
+
+    #[staticmethod]
+    fn from_parts(translation: &Vector3, rotation: &UnitQuaternion) -> UnitDualQuaternion {
+        UnitDualQuaternion(na::UnitDualQuaternion::from_parts(
+            translation.as_translation(),
+            rotation.0,
+        ))
+    }
+
+    fn to_isometry(&self) -> Isometry3 {
+        Isometry3(self.0.to_isometry())
+    }
+
+    #[getter]
+    fn get_rotation(&self) -> UnitQuaternion {
+        UnitQuaternion(self.0.rotation())
+    }
+
+    #[getter]
+    fn get_translation(&self) -> Vector3 {
+        Vector3::from_translation(&self.0.translation())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &UnitDualQuaternion, op: CompareOp) -> Py<PyAny> {
+        match op {
+            CompareOp::Eq => (self.0 == other.0).into_py(py),
+            CompareOp::Ne => (self.0 != other.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    /// Compares the components of both dual quaternions.
+    ///
+    /// Note: `q` and `-q` represent the same rigid transform but are not
+    /// considered approximately equal here, compare `to_isometry()` for that.
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &UnitDualQuaternion,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &other.0))
+    }
+
+    fn __mul__(&self, py: Python, arg: &PyAny) -> PyResult<PyObject> {
+        let dqr: PyResult<PyRef<UnitDualQuaternion>> = arg.extract();
+        if let Ok(dq) = dqr {
+            return Ok(Py::new(py, UnitDualQuaternion(self.0 * dq.0))?.to_object(py));
+        }
+        let vecr: PyResult<PyRef<Vector3>> = arg.extract();
+        if let Ok(vec) = vecr {
+            return Ok(
+                Py::new(py, Vector3::from_p3(&self.0.transform_point(&vec.as_p3())))?.to_object(py),
+            );
+        }
+        Ok(py.NotImplemented())
+    }
+
+    fn __imul__(&mut self, arg: &UnitDualQuaternion) {
+        self.0 *= arg.0;
+    }
+
+    fn premultiply(&mut self, arg: &UnitDualQuaternion) {
+        self.0 = arg.0 * self.0;
+    }
+
+    fn inverse(&self) -> UnitDualQuaternion {
+        UnitDualQuaternion(self.0.inverse())
+    }
+
+    fn invert(&mut self) {
+        self.0.inverse_mut();
+    }
+
+    fn transform_point(&self, v: &Vector3) -> Vector3 {
+        Vector3::from_p3(&self.0.transform_point(&v.as_p3()))
+    }
+
+    fn transform_vector(&self, v: &Vector3) -> Vector3 {
+        Vector3(self.0.transform_vector(&v.0))
+    }
+
+    /// Performs a screw linear interpolation (ScLERP) between `self` and `other`.
+    ///
+    /// The rotation and translation are interpolated together along a constant
+    /// screw motion, so for a parameter `t` between 0 and 1 the result is a rigid
+    /// transform between `self` (t = 0) and `other` (t = 1).
+    ///
+    /// Raises a ValueError when the two rotations are 180 degrees apart, as the
+    /// interpolation path is not well-defined.
+    fn sclerp(&self, other: PyRef<UnitDualQuaternion>, t: f64) -> PyResult<UnitDualQuaternion> {
+        match self.0.try_sclerp(&other.0, t, f64::EPSILON) {
+            Some(dq) => Ok(UnitDualQuaternion(dq)),
+            None => Err(PyValueError::new_err(
+                "Cannot sclerp between rotations which are 180 degrees apart",
+            )),
+        }
+    }
+
+    /// Blends many dual quaternions with dual quaternion linear blending (DLB).
+    ///
+    /// Each dual quaternion is scaled by its weight and summed, then the result
+    /// is normalized. Dual quaternions are flipped into the same hemisphere as the
+    /// first one before summing so that `q` and `-q` blend as the same transform.
+    /// If `weights` is omitted every dual quaternion is weighted equally.
+    #[staticmethod]
+    fn blend(
+        dqs: Vec<PyRef<UnitDualQuaternion>>,
+        weights: Option<Vec<f64>>,
+    ) -> PyResult<UnitDualQuaternion> {
+        let first = match dqs.first() {
+            Some(dq) => dq.0,
+            None => return Err(PyValueError::new_err("Cannot blend an empty list")),
+        };
+        let weights = weights.unwrap_or_else(|| vec![1.0; dqs.len()]);
+        if weights.len() != dqs.len() {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} weights but got {}",
+                dqs.len(),
+                weights.len()
+            )));
+        }
+
+        let mut sum = na::DualQuaternion::from_real(na::Quaternion::new(0.0, 0.0, 0.0, 0.0));
+        for (dq, w) in dqs.iter().zip(weights) {
+            let aligned = if first.as_ref().real.coords.dot(&dq.0.as_ref().real.coords) < 0.0 {
+                -w
+            } else {
+                w
+            };
+            sum += dq.0.into_inner() * aligned;
+        }
+
+        if sum.real.norm() <= f64::EPSILON {
+            return Err(PyValueError::new_err(
+                "Cannot blend dual quaternions whose weighted sum is zero",
+            ));
+        }
+        Ok(UnitDualQuaternion(na::UnitDualQuaternion::new_normalize(
+            sum,
+        )))
+    }
+
+    /// Returns the (real, dual) parts as two (i, j, k, w) tuples.
+    fn tuple(&self) -> (Tuple4, Tuple4) {
+        let dq = self.0.as_ref();
+        (
+            (dq.real.i, dq.real.j, dq.real.k, dq.real.w),
+            (dq.dual.i, dq.dual.j, dq.dual.k, dq.dual.w),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        let dq = self.0.as_ref();
+        format!(
+            "UnitDualQuaternion(real=({}, {}, {}, {}), dual=({}, {}, {}, {}))",
+            dq.real.i, dq.real.j, dq.real.k, dq.real.w, dq.dual.i, dq.dual.j, dq.dual.k, dq.dual.w
+        )
+    }
+}
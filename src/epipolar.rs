@@ -0,0 +1,453 @@
+use crate::camera::PinholeCamera;
+use crate::iso::Isometry3;
+use crate::mat3::Matrix3;
+use crate::points::extract_pairs;
+use crate::results::NamedTuple;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+type V2 = na::Vector2<f64>;
+type V3 = na::Vector3<f64>;
+type M3 = na::Matrix3<f64>;
+
+static RELATIVE_POSE: NamedTuple = NamedTuple::new("RelativePose", &["pose", "in_front"]);
+
+/// How a point is found from its two projections.
+#[derive(Clone, Copy)]
+enum Method {
+    /// The direct linear transform, which minimises an algebraic error.
+    Linear,
+    /// The midpoint of the closest points on the two rays.
+    Midpoint,
+}
+
+impl Method {
+    fn new(method: &str) -> PyResult<Method> {
+        match method {
+            "linear" => Ok(Method::Linear),
+            "midpoint" => Ok(Method::Midpoint),
+            _ => Err(PyValueError::new_err(format!(
+                "method must be 'linear' or 'midpoint', not '{}'",
+                method
+            ))),
+        }
+    }
+}
+
+/// Finds the point which projects to normalized image coordinates `x1` and
+/// `x2` in two cameras with world to camera poses `pose1` and `pose2`.
+///
+/// Returns None if the rays are parallel, so the point is at infinity.
+fn triangulate_point(
+    method: Method,
+    pose1: &na::Isometry3<f64>,
+    x1: V2,
+    pose2: &na::Isometry3<f64>,
+    x2: V2,
+) -> Option<V3> {
+    match method {
+        Method::Linear => {
+            let mut a = na::Matrix4::<f64>::zeros();
+            for (i, (pose, x)) in [(pose1, x1), (pose2, x2)].into_iter().enumerate() {
+                let p = pose.to_homogeneous();
+                for (j, coordinate) in x.iter().enumerate() {
+                    let row = p.row(2) * *coordinate - p.row(j);
+                    a.set_row(2 * i + j, &(row / row.norm()));
+                }
+            }
+            let svd = na::SVD::try_new(a, false, true, f64::EPSILON, 1000)?;
+            let x = svd.v_t?.row(3).transpose();
+            let point = x.xyz();
+            if x.w.abs() <= 1e-12 * point.norm() {
+                return None;
+            }
+            Some(point / x.w)
+        }
+        Method::Midpoint => {
+            let center = |pose: &na::Isometry3<f64>| {
+                pose.inverse_transform_point(&na::Point3::origin()).coords
+            };
+            let direction = |pose: &na::Isometry3<f64>, x: V2| {
+                pose.inverse_transform_vector(&V3::new(x.x, x.y, 1.0))
+            };
+            let (c1, d1) = (center(pose1), direction(pose1, x1));
+            let (c2, d2) = (center(pose2), direction(pose2, x2));
+            let r = c1 - c2;
+            let (a, b, e) = (d1.dot(&d1), d1.dot(&d2), d2.dot(&d2));
+            let (c, f) = (d1.dot(&r), d2.dot(&r));
+            let denom = a * e - b * b;
+            if denom <= 1e-12 * a * e {
+                return None;
+            }
+            let s = (b * f - c * e) / denom;
+            let t = (a * f - b * c) / denom;
+            Some((c1 + d1 * s + c2 + d2 * t) / 2.0)
+        }
+    }
+}
+
+/// The essential matrix of the relative pose mapping camera 1's coordinates
+/// into camera 2's, `E = [t]x R`.
+fn essential(pose: &na::Isometry3<f64>) -> M3 {
+    pose.translation.vector.cross_matrix() * pose.rotation.to_rotation_matrix().into_inner()
+}
+
+/// The four relative poses an essential matrix can come from. Their
+/// translations have unit length.
+fn decompose(e: &M3) -> PyResult<[na::Isometry3<f64>; 4]> {
+    let svd = Matrix3::try_svd(e)?;
+    if svd.singular_values[1] <= svd.singular_values[0] * 1e-9 {
+        return Err(PyValueError::new_err(
+            "An essential matrix must have two non-zero singular values",
+        ));
+    }
+    let (mut u, mut v_t) = (svd.u.unwrap(), svd.v_t.unwrap());
+    if u.determinant() < 0.0 {
+        u = -u;
+    }
+    if v_t.determinant() < 0.0 {
+        v_t = -v_t;
+    }
+    let w = M3::new(0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+    let t: V3 = u.column(2).into();
+    let pose = |r: M3, t: V3| {
+        na::Isometry3::from_parts(
+            t.into(),
+            na::UnitQuaternion::from_rotation_matrix(&na::Rotation3::from_matrix_unchecked(r)),
+        )
+    };
+    let (r1, r2) = (u * w * v_t, u * w.transpose() * v_t);
+    Ok([pose(r1, t), pose(r1, -t), pose(r2, t), pose(r2, -t)])
+}
+
+fn check_finite(m: &Matrix3) -> PyResult<()> {
+    match m.0.iter().all(|v| v.is_finite()) {
+        true => Ok(()),
+        false => Err(PyValueError::new_err("Matrix3 contains non-finite values")),
+    }
+}
+
+/// Extracts two equally long lists of image points, converting pixels to
+/// normalized image coordinates if the matching camera is given.
+fn extract_correspondences(
+    py: Python,
+    points1: &PyAny,
+    points2: &PyAny,
+    camera1: Option<&PinholeCamera>,
+    camera2: Option<&PinholeCamera>,
+) -> PyResult<(Vec<V2>, Vec<V2>)> {
+    let extract = |points: &PyAny, camera: Option<&PinholeCamera>| -> PyResult<Vec<V2>> {
+        Ok(extract_pairs(py, points, "point")?
+            .into_iter()
+            .map(|(u, v)| match camera {
+                Some(camera) => camera.pixel_to_normalized(u, v),
+                None => (u, v),
+            })
+            .map(|(x, y)| V2::new(x, y))
+            .collect())
+    };
+    let (points1, points2) = (extract(points1, camera1)?, extract(points2, camera2)?);
+    if points1.len() != points2.len() {
+        return Err(PyValueError::new_err(format!(
+            "Got {} points in the first image but {} in the second",
+            points1.len(),
+            points2.len()
+        )));
+    }
+    Ok((points1, points2))
+}
+
+/// The first-order approximation of the squared distance of a pair of
+/// points from satisfying `x2^T F x1 = 0`.
+fn sampson(f: &M3, x1: V2, x2: V2) -> f64 {
+    let (x1, x2) = (x1.push(1.0), x2.push(1.0));
+    let (fx1, ftx2) = (f * x1, f.transpose() * x2);
+    let error = x2.dot(&fx1);
+    let denom = fx1.x * fx1.x + fx1.y * fx1.y + ftx2.x * ftx2.x + ftx2.y * ftx2.y;
+    error * error / denom
+}
+
+/// The similarity transform moving points' centroid to the origin and
+/// scaling their mean distance from it to sqrt(2), as Hartley's normalized
+/// eight-point algorithm needs.
+fn conditioning(points: &[V2]) -> M3 {
+    let mean = points.iter().sum::<V2>() / points.len() as f64;
+    let spread = points.iter().map(|p| (p - mean).norm()).sum::<f64>() / points.len() as f64;
+    let scale = if spread > 0.0 {
+        2f64.sqrt() / spread
+    } else {
+        1.0
+    };
+    M3::new(
+        scale,
+        0.0,
+        -scale * mean.x,
+        0.0,
+        scale,
+        -scale * mean.y,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+/// Returns the essential matrix `E = [t]x R` of `pose`, the relative pose
+/// mapping points from the first camera's coordinates into the second's.
+///
+/// Corresponding normalized image coordinates `x1` and `x2` (as
+/// homogeneous vectors) satisfy `x2^T E x1 = 0`.
+#[pyfunction]
+pub fn essential_from_pose(pose: &Isometry3) -> Matrix3 {
+    Matrix3(essential(&pose.0))
+}
+
+/// Returns the four relative poses that `essential` can be decomposed into,
+/// each with a unit length translation. Only one of them puts observed
+/// points in front of both cameras, see `pose_from_essential`.
+#[pyfunction]
+pub fn poses_from_essential(essential: &Matrix3) -> PyResult<Vec<Isometry3>> {
+    check_finite(essential)?;
+    Ok(decompose(&essential.0)?
+        .into_iter()
+        .map(Isometry3)
+        .collect())
+}
+
+/// Recovers the relative pose from an essential matrix and the
+/// correspondences it was estimated from, returning a
+/// `RelativePose(pose, in_front)`.
+///
+/// `pose` maps points from the first camera's coordinates into the
+/// second's. Of the four decompositions of the essential matrix, it is the
+/// one which puts the most triangulated points in front of both cameras
+/// (the cheirality check), and `in_front` is a list of bools marking those
+/// points. The translation's scale can't be recovered, so it has unit
+/// length.
+///
+/// The points are normalized image coordinates, or pixels if `camera1` and
+/// `camera2` are given.
+#[pyfunction]
+#[pyo3(signature = (essential, points1, points2, camera1=None, camera2=None))]
+pub fn pose_from_essential(
+    py: Python,
+    essential: &Matrix3,
+    points1: &PyAny,
+    points2: &PyAny,
+    camera1: Option<&PinholeCamera>,
+    camera2: Option<&PinholeCamera>,
+) -> PyResult<PyObject> {
+    check_finite(essential)?;
+    let (points1, points2) = extract_correspondences(py, points1, points2, camera1, camera2)?;
+    if points1.is_empty() {
+        return Err(PyValueError::new_err(
+            "Recovering a pose needs at least one correspondence",
+        ));
+    }
+    let identity = na::Isometry3::identity();
+    let (pose, in_front) = decompose(&essential.0)?
+        .into_iter()
+        .map(|pose| {
+            let in_front: Vec<bool> = points1
+                .iter()
+                .zip(&points2)
+                .map(|(x1, x2)| {
+                    match triangulate_point(Method::Linear, &identity, *x1, &pose, *x2) {
+                        Some(p) => p.z > 0.0 && pose.transform_point(&p.into()).z > 0.0,
+                        None => false,
+                    }
+                })
+                .collect();
+            (pose, in_front)
+        })
+        .max_by_key(|(_, in_front)| in_front.iter().filter(|i| **i).count())
+        .unwrap();
+    if !in_front.contains(&true) {
+        return Err(PyValueError::new_err(
+            "No decomposition puts any point in front of both cameras",
+        ));
+    }
+    RELATIVE_POSE.make(py, (Isometry3(pose), in_front))
+}
+
+/// Estimates the fundamental matrix `F` from at least 8 corresponding pixels
+/// with the normalized eight-point algorithm, so that `x2^T F x1 = 0` for
+/// pixels `x1` and `x2` (as homogeneous vectors).
+///
+/// The estimate is made rank 2 and scaled to unit norm, with its largest
+/// element positive. Raises a ValueError if the correspondences don't
+/// determine a unique matrix, e.g. when the points are all on one plane and
+/// the cameras share a center. Outliers should be removed first.
+#[pyfunction]
+pub fn fundamental_matrix(py: Python, pixels1: &PyAny, pixels2: &PyAny) -> PyResult<Matrix3> {
+    let (pixels1, pixels2) = extract_correspondences(py, pixels1, pixels2, None, None)?;
+    if pixels1.len() < 8 {
+        return Err(PyValueError::new_err(format!(
+            "Estimating a fundamental matrix needs at least 8 correspondences but got {}",
+            pixels1.len()
+        )));
+    }
+    if !pixels1
+        .iter()
+        .chain(&pixels2)
+        .all(|p| p.iter().all(|c| c.is_finite()))
+    {
+        return Err(PyValueError::new_err("Pixels must be finite"));
+    }
+    let (t1, t2) = (conditioning(&pixels1), conditioning(&pixels2));
+    // Pad with zero rows, as the SVD of a matrix with fewer rows than
+    // columns doesn't give the null space
+    let mut a = na::DMatrix::<f64>::zeros(pixels1.len().max(9), 9);
+    for (i, (p1, p2)) in pixels1.iter().zip(&pixels2).enumerate() {
+        let x1 = t1 * p1.push(1.0);
+        let x2 = t2 * p2.push(1.0);
+        for r in 0..3 {
+            for c in 0..3 {
+                a[(i, 3 * r + c)] = x2[r] * x1[c];
+            }
+        }
+    }
+    let svd = a.svd(false, true);
+    let mut order: Vec<usize> = (0..9).collect();
+    order.sort_by(|i, j| svd.singular_values[*j].total_cmp(&svd.singular_values[*i]));
+    if svd.singular_values[order[7]] <= svd.singular_values[order[0]] * 1e-10 {
+        return Err(PyValueError::new_err(
+            "The correspondences don't determine a unique fundamental matrix",
+        ));
+    }
+    let v_t = svd.v_t.unwrap();
+    let f = M3::from_fn(|r, c| v_t[(order[8], 3 * r + c)]);
+
+    // The closest rank 2 matrix
+    let svd = Matrix3::try_svd(&f)?;
+    let mut s = svd.singular_values;
+    s[2] = 0.0;
+    let f = svd.u.unwrap() * M3::from_diagonal(&s) * svd.v_t.unwrap();
+
+    let f = t2.transpose() * f * t1;
+    let largest = f
+        .iter()
+        .copied()
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap();
+    Ok(Matrix3(f / (f.norm() * largest.signum())))
+}
+
+/// Returns the essential matrix `K2^T F K1` of a fundamental matrix between
+/// pixels of `camera1` and `camera2`.
+#[pyfunction]
+pub fn essential_from_fundamental(
+    fundamental: &Matrix3,
+    camera1: &PinholeCamera,
+    camera2: &PinholeCamera,
+) -> Matrix3 {
+    Matrix3(camera2.intrinsics().transpose() * fundamental.0 * camera1.intrinsics())
+}
+
+/// Returns the fundamental matrix `K2^-T E K1^-1` relating pixels of
+/// `camera1` and `camera2` with essential matrix `essential`.
+#[pyfunction]
+pub fn fundamental_from_essential(
+    essential: &Matrix3,
+    camera1: &PinholeCamera,
+    camera2: &PinholeCamera,
+) -> Matrix3 {
+    Matrix3(camera2.inverse_intrinsics().transpose() * essential.0 * camera1.inverse_intrinsics())
+}
+
+/// Returns the Sampson error of a correspondence, the first-order
+/// approximation of the squared distance (in squared pixels) the pixels
+/// must move to satisfy `x2^T F x1 = 0`.
+///
+/// An essential matrix can be used with normalized image coordinates
+/// instead of pixels.
+#[pyfunction]
+pub fn sampson_error(fundamental: &Matrix3, pixel1: (f64, f64), pixel2: (f64, f64)) -> f64 {
+    sampson(
+        &fundamental.0,
+        V2::new(pixel1.0, pixel1.1),
+        V2::new(pixel2.0, pixel2.1),
+    )
+}
+
+/// Returns the Sampson error of each correspondence, see `sampson_error`.
+#[pyfunction]
+pub fn sampson_errors(
+    py: Python,
+    fundamental: &Matrix3,
+    pixels1: &PyAny,
+    pixels2: &PyAny,
+) -> PyResult<Vec<f64>> {
+    let (pixels1, pixels2) = extract_correspondences(py, pixels1, pixels2, None, None)?;
+    Ok(pixels1
+        .into_iter()
+        .zip(pixels2)
+        .map(|(x1, x2)| sampson(&fundamental.0, x1, x2))
+        .collect())
+}
+
+/// Returns the point in the world seen at `pixel1` by `camera1` and at
+/// `pixel2` by `camera2`, where `pose1` and `pose2` are the cameras'
+/// world to camera poses.
+///
+/// `method` is `"linear"` for the direct linear transform or `"midpoint"`
+/// for the midpoint of the closest points on the two rays. The rays of
+/// noisy pixels don't meet, and the methods give slightly different points.
+/// Raises a ValueError if the rays are parallel.
+#[pyfunction]
+#[pyo3(signature = (camera1, pose1, pixel1, camera2, pose2, pixel2, *, method="linear"))]
+pub fn triangulate(
+    camera1: &PinholeCamera,
+    pose1: &Isometry3,
+    pixel1: (f64, f64),
+    camera2: &PinholeCamera,
+    pose2: &Isometry3,
+    pixel2: (f64, f64),
+    method: &str,
+) -> PyResult<Vector3> {
+    let x1 = camera1.pixel_to_normalized(pixel1.0, pixel1.1);
+    let x2 = camera2.pixel_to_normalized(pixel2.0, pixel2.1);
+    triangulate_point(
+        Method::new(method)?,
+        &pose1.0,
+        V2::new(x1.0, x1.1),
+        &pose2.0,
+        V2::new(x2.0, x2.1),
+    )
+    .map(Vector3)
+    .ok_or_else(|| PyValueError::new_err("The rays through the pixels are parallel"))
+}
+
+/// Triangulates each pair of pixels as `triangulate` does, returning a list
+/// of Vector3s. Pixels whose rays are parallel give NaN points.
+///
+/// Pixels may be given as a list of `(u, v)` sequences or an N x 2 array.
+#[pyfunction]
+#[pyo3(signature = (camera1, pose1, pixels1, camera2, pose2, pixels2, *, method="linear"))]
+#[allow(clippy::too_many_arguments)]
+pub fn triangulate_batch(
+    py: Python,
+    camera1: &PinholeCamera,
+    pose1: &Isometry3,
+    pixels1: &PyAny,
+    camera2: &PinholeCamera,
+    pose2: &Isometry3,
+    pixels2: &PyAny,
+    method: &str,
+) -> PyResult<Vec<Vector3>> {
+    let method = Method::new(method)?;
+    let (points1, points2) =
+        extract_correspondences(py, pixels1, pixels2, Some(camera1), Some(camera2))?;
+    let points = py.allow_threads(|| {
+        points1
+            .into_iter()
+            .zip(points2)
+            .map(|(x1, x2)| {
+                triangulate_point(method, &pose1.0, x1, &pose2.0, x2)
+                    .unwrap_or_else(|| V3::repeat(f64::NAN))
+            })
+            .collect::<Vec<_>>()
+    });
+    Ok(points.into_iter().map(Vector3).collect())
+}
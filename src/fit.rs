@@ -0,0 +1,216 @@
+use crate::plane::Plane;
+use crate::points::{self, extract_finite_points, extract_nonempty_points};
+use crate::quat::UnitQuaternion;
+use crate::results::NamedTuple;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+type V3 = na::Vector3<f64>;
+
+static PLANE_FIT: NamedTuple = NamedTuple::new("PlaneFit", &["plane", "rms"]);
+static LINE_FIT: NamedTuple = NamedTuple::new("LineFit", &["point", "direction", "rms"]);
+static PRINCIPAL_AXES: NamedTuple = NamedTuple::new("PrincipalAxes", &["rotation", "variances"]);
+static RANSAC_PLANE_FIT: NamedTuple =
+    NamedTuple::new("RansacPlaneFit", &["plane", "rms", "inliers"]);
+
+/// Variances this small relative to the largest are treated as zero, i.e.
+/// the points don't extend in that direction.
+const DEGENERATE_RATIO: f64 = 1e-12;
+
+/// The least-squares plane through the points and its RMS residual, or None
+/// if the points are collinear (or coincident) so no single plane fits.
+///
+/// The points are centered on their mean before fitting, so the fit doesn't
+/// lose precision for points far from the origin.
+fn least_squares_plane(points: &[V3]) -> Option<(Plane, f64)> {
+    if points.len() < 3 {
+        return None;
+    }
+    let (mean, covariance) = points::mean_and_covariance(points);
+    let (axes, variances) = points::principal_axes(covariance);
+    if variances[1] <= variances[0] * DEGENERATE_RATIO {
+        return None;
+    }
+    let normal = na::Unit::new_normalize(axes.matrix().column(2).into_owned());
+    let plane = Plane {
+        offset: normal.dot(&mean),
+        normal,
+    };
+    Some((
+        plane.clone(),
+        rms(points.iter().map(|p| plane.signed_distance_to(p))),
+    ))
+}
+
+fn rms(residuals: impl ExactSizeIterator<Item = f64>) -> f64 {
+    let n = residuals.len() as f64;
+    (residuals.map(|r| r * r).sum::<f64>() / n).sqrt()
+}
+
+fn collinear_error() -> PyErr {
+    PyValueError::new_err("Cannot fit a plane to fewer than 3 points or collinear points")
+}
+
+/// Fits a plane to three or more points, minimising the sum of squared
+/// distances from the points to the plane. Returns a `PlaneFit(plane, rms)`
+/// where `rms` is the root mean square distance of the points from the plane.
+///
+/// Points may be given as a list of Vector3s, a list of 3 element sequences
+/// or an N x 3 array. Raises a ValueError for collinear or non-finite points.
+#[pyfunction]
+pub fn fit_plane(py: Python, points: &PyAny) -> PyResult<PyObject> {
+    let (plane, rms) =
+        least_squares_plane(&extract_finite_points(py, points)?).ok_or_else(collinear_error)?;
+    PLANE_FIT.make(py, (plane, rms))
+}
+
+/// Fits a line to two or more points, minimising the sum of squared distances
+/// from the points to the line. Returns a `LineFit(point, direction, rms)`
+/// where `point` is the centroid of the points, `direction` is a unit vector
+/// and `rms` is the root mean square distance of the points from the line.
+///
+/// Raises a ValueError if all the points are the same, to within rounding
+/// for their distance from the origin, or any of them isn't finite.
+#[pyfunction]
+pub fn fit_line(py: Python, points: &PyAny) -> PyResult<PyObject> {
+    let points = extract_finite_points(py, points)?;
+    let (mean, covariance) = points::mean_and_covariance(&points);
+    let (axes, variances) = points::principal_axes(covariance);
+    // Rounding leaves coincident points a tiny spread, which grows with their
+    // distance from the origin
+    let scale = points.iter().map(|p| p.norm_squared()).fold(0.0, f64::max);
+    if variances[0] <= scale * DEGENERATE_RATIO {
+        return Err(PyValueError::new_err(
+            "Cannot fit a line to fewer than 2 distinct points",
+        ));
+    }
+    let direction: V3 = axes.matrix().column(0).normalize();
+    let residuals = points.iter().map(|p| {
+        let d = p - mean;
+        (d - direction * direction.dot(&d)).magnitude()
+    });
+    LINE_FIT.make(py, (Vector3(mean), Vector3(direction), rms(residuals)))
+}
+
+/// Returns the mean of the points, weighted by `weights` if given.
+///
+/// Raises a ValueError if there are no points, the number of weights doesn't
+/// match the number of points or the weights sum to zero.
+#[pyfunction]
+pub fn centroid(py: Python, points: &PyAny, weights: Option<Vec<f64>>) -> PyResult<Vector3> {
+    let points = extract_nonempty_points(py, points)?;
+    let weights = match weights {
+        None => return Ok(Vector3(points.iter().sum::<V3>() / points.len() as f64)),
+        Some(weights) => weights,
+    };
+    if weights.len() != points.len() {
+        return Err(PyValueError::new_err(format!(
+            "Expected {} weights but got {}",
+            points.len(),
+            weights.len()
+        )));
+    }
+    let total: f64 = weights.iter().sum();
+    if total == 0.0 {
+        return Err(PyValueError::new_err("weights must not sum to zero"));
+    }
+    let sum: V3 = points.iter().zip(&weights).map(|(p, w)| p * *w).sum();
+    Ok(Vector3(sum / total))
+}
+
+/// Returns the principal axes of the points as a `PrincipalAxes(rotation,
+/// variances)`. The rotation's x, y and z axes are the directions in which
+/// the points vary most to least, and `variances` holds the variance of the
+/// points along each of them. Raises a ValueError if any point isn't finite.
+#[pyfunction]
+pub fn principal_axes(py: Python, points: &PyAny) -> PyResult<PyObject> {
+    let points = extract_finite_points(py, points)?;
+    let (_, covariance) = points::mean_and_covariance(&points);
+    let (axes, variances) = points::principal_axes(covariance);
+    let rotation = UnitQuaternion(na::UnitQuaternion::from_rotation_matrix(&axes));
+    PRINCIPAL_AXES.make(py, (rotation, Vector3(variances)))
+}
+
+/// Fits a plane to points which include outliers using RANSAC. Returns a
+/// `RansacPlaneFit(plane, rms, inliers)` where `inliers` lists the indices of
+/// the points within `threshold` of the plane and `rms` is their root mean
+/// square distance from it.
+///
+/// Each of the `iterations` tries a plane through three random points,
+/// keeping the one with the most inliers, which is then refined with a
+/// least-squares fit to its inliers. The random points are chosen by a
+/// generator seeded with `seed`, so the result is deterministic. Raises a
+/// ValueError for fewer than 3 points or any that aren't finite.
+#[pyfunction]
+#[pyo3(signature = (points, threshold, *, iterations=1000, seed=0))]
+pub fn fit_plane_ransac(
+    py: Python,
+    points: &PyAny,
+    threshold: f64,
+    iterations: usize,
+    seed: u64,
+) -> PyResult<PyObject> {
+    if threshold.is_nan() || threshold <= 0.0 {
+        return Err(PyValueError::new_err("threshold must be a positive number"));
+    }
+    let points = extract_finite_points(py, points)?;
+    if points.len() < 3 {
+        return Err(collinear_error());
+    }
+    let inliers_of = |plane: &Plane| -> Vec<usize> {
+        (0..points.len())
+            .filter(|i| plane.signed_distance_to(&points[*i]).abs() <= threshold)
+            .collect()
+    };
+
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let mut best: Option<(Plane, Vec<usize>)> = None;
+    for _ in 0..iterations {
+        let sample = rand::seq::index::sample(&mut rng, points.len(), 3);
+        let (a, b, c) = (
+            points[sample.index(0)],
+            points[sample.index(1)],
+            points[sample.index(2)],
+        );
+        let normal = match na::Unit::try_new((b - a).cross(&(c - a)), 0.0) {
+            Some(normal) => normal,
+            None => continue,
+        };
+        let plane = Plane {
+            offset: normal.dot(&a),
+            normal,
+        };
+        let inliers = inliers_of(&plane);
+        let better = match &best {
+            Some((_, best)) => inliers.len() > best.len(),
+            None => true,
+        };
+        if better {
+            best = Some((plane, inliers));
+        }
+    }
+    let (plane, inliers) = best.ok_or_else(collinear_error)?;
+
+    // Refine the plane with a least-squares fit to its inliers, unless they
+    // happen to be collinear
+    let inlier_points: Vec<V3> = inliers.iter().map(|i| points[*i]).collect();
+    let (plane, inliers) = match least_squares_plane(&inlier_points) {
+        Some((refined, _)) => {
+            let refined_inliers = inliers_of(&refined);
+            if refined_inliers.len() >= inliers.len() {
+                (refined, refined_inliers)
+            } else {
+                (plane, inliers)
+            }
+        }
+        None => (plane, inliers),
+    };
+    let rms = rms(inliers
+        .iter()
+        .map(|i| plane.signed_distance_to(&points[*i])));
+    RANSAC_PLANE_FIT.make(py, (plane, rms, inliers))
+}
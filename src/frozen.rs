@@ -0,0 +1,436 @@
+use crate::iso::Isometry3;
+use crate::quat::UnitQuaternion;
+use crate::seq::{self, ComponentIter};
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Hashes floats consistently with `==`, i.e. `0.0` and `-0.0` hash the same.
+fn hash_components(components: &[f64]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for c in components {
+        let c = if *c == 0.0 { 0.0 } else { *c };
+        c.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Hashes floats after snapping them to a grid with the given cell size, so
+/// values which are close together usually (but not always, if they straddle
+/// a cell boundary) hash the same.
+fn quantized_hash_components(components: &[f64], resolution: f64) -> PyResult<u64> {
+    if resolution.is_nan() || resolution <= 0.0 {
+        return Err(PyValueError::new_err(
+            "resolution must be a positive number",
+        ));
+    }
+    let mut hasher = DefaultHasher::new();
+    for c in components {
+        ((c / resolution).round() as i64).hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+fn extract_vector(arg: &PyAny) -> PyResult<na::Vector3<f64>> {
+    if let Ok(v) = arg.extract::<PyRef<FrozenVector3>>() {
+        Ok(v.0)
+    } else if let Ok(v) = arg.extract::<PyRef<Vector3>>() {
+        Ok(v.0)
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Expected a Vector3 or FrozenVector3, not {}",
+            arg.get_type().name().unwrap_or("?")
+        )))
+    }
+}
+
+fn extract_rotation(arg: &PyAny) -> PyResult<na::UnitQuaternion<f64>> {
+    if let Ok(q) = arg.extract::<PyRef<FrozenUnitQuaternion>>() {
+        Ok(q.0)
+    } else if let Ok(q) = arg.extract::<PyRef<UnitQuaternion>>() {
+        Ok(q.0)
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Expected a UnitQuaternion or FrozenUnitQuaternion, not {}",
+            arg.get_type().name().unwrap_or("?")
+        )))
+    }
+}
+
+/// An immutable, hashable counterpart to Vector3.
+#[pyclass(frozen)]
+#[derive(Clone)]
+pub struct FrozenVector3(pub na::Vector3<f64>);
+
+#[pymethods]
+impl FrozenVector3 {
+    #[new]
+    fn new(x: Option<f64>, y: Option<f64>, z: Option<f64>) -> Self {
+        FrozenVector3(na::Vector3::new(
+            x.unwrap_or(0.0),
+            y.unwrap_or(0.0),
+            z.unwrap_or(0.0),
+        ))
+    }
+
+    #[getter]
+    fn get_x(&self) -> f64 {
+        self.0.x
+    }
+
+    #[getter]
+    fn get_y(&self) -> f64 {
+        self.0.y
+    }
+
+    #[getter]
+    fn get_z(&self) -> f64 {
+        self.0.z
+    }
+
+    fn __getitem__(&self, py: Python, idx: &PyAny) -> PyResult<PyObject> {
+        seq::get_item(py, self.0.as_slice(), idx)
+    }
+
+    fn __iter__(&self) -> ComponentIter {
+        ComponentIter::new(self.0.as_slice())
+    }
+
+    fn __reversed__(&self) -> ComponentIter {
+        ComponentIter::new_reversed(self.0.as_slice())
+    }
+
+    fn __contains__(&self, value: &PyAny) -> bool {
+        seq::contains(self.0.as_slice(), value)
+    }
+
+    fn __bool__(&self) -> bool {
+        self.0.iter().any(|c| *c != 0.0)
+    }
+
+    #[classattr]
+    fn __match_args__() -> (&'static str, &'static str, &'static str) {
+        ("x", "y", "z")
+    }
+
+    #[staticmethod]
+    fn __len__() -> usize {
+        3
+    }
+
+    fn __richcmp__(&self, py: Python, other: &PyAny, op: CompareOp) -> Py<PyAny> {
+        let other = if let Ok(v) = other.extract::<PyRef<FrozenVector3>>() {
+            v.0
+        } else if let Ok(v) = other.extract::<PyRef<Vector3>>() {
+            v.0
+        } else {
+            return py.NotImplemented();
+        };
+        match op {
+            CompareOp::Eq => (self.0 == other).into_py(py),
+            CompareOp::Ne => (self.0 != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash_components(self.0.as_slice())
+    }
+
+    /// Returns a hash of this vector snapped to a grid of `resolution` sized
+    /// cells, for deduplicating points which are approximately equal.
+    ///
+    /// Note: Points which are close together but either side of a cell
+    /// boundary will hash differently.
+    fn quantized_hash(&self, resolution: f64) -> PyResult<u64> {
+        quantized_hash_components(self.0.as_slice(), resolution)
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &FrozenVector3,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &other.0))
+    }
+
+    /// Returns a mutable copy of this vector.
+    fn thaw(&self) -> Vector3 {
+        Vector3(self.0)
+    }
+
+    fn tuple(&self) -> (f64, f64, f64) {
+        (self.0[0], self.0[1], self.0[2])
+    }
+
+    fn list(&self) -> [f64; 3] {
+        [self.0[0], self.0[1], self.0[2]]
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FrozenVector3({}, {}, {})", self.0[0], self.0[1], self.0[2])
+    }
+}
+
+/// An immutable, hashable counterpart to UnitQuaternion.
+///
+/// Like `UnitQuaternion()`, the constructor only gives the identity, so other
+/// rotations are made with UnitQuaternion's constructors and `freeze()`, e.g.
+/// `UnitQuaternion.from_axis_angle(axis, angle).freeze()`.
+#[pyclass(frozen)]
+#[derive(Clone)]
+pub struct FrozenUnitQuaternion(pub na::UnitQuaternion<f64>);
+
+impl FrozenUnitQuaternion {
+    /// The coordinates of one of `q` or `-q`, picked consistently, so both
+    /// representations of a rotation hash the same as they compare equal.
+    fn canonical_coords(&self) -> na::Vector4<f64> {
+        let c = &self.0.coords;
+        let first = [c[3], c[0], c[1], c[2]].into_iter().find(|v| *v != 0.0);
+        if first.unwrap_or(0.0) < 0.0 {
+            -c
+        } else {
+            *c
+        }
+    }
+}
+
+#[pymethods]
+impl FrozenUnitQuaternion {
+    #[new]
+    fn new() -> Self {
+        FrozenUnitQuaternion(na::UnitQuaternion::identity())
+    }
+
+    fn __getitem__(&self, py: Python, idx: &PyAny) -> PyResult<PyObject> {
+        seq::get_item(py, self.0.coords.as_slice(), idx)
+    }
+
+    #[getter]
+    fn get_i(&self) -> f64 {
+        self.0.i
+    }
+
+    #[getter]
+    fn get_j(&self) -> f64 {
+        self.0.j
+    }
+
+    #[getter]
+    fn get_k(&self) -> f64 {
+        self.0.k
+    }
+
+    #[getter]
+    fn get_w(&self) -> f64 {
+        self.0.w
+    }
+
+    fn __iter__(&self) -> ComponentIter {
+        ComponentIter::new(self.0.coords.as_slice())
+    }
+
+    fn __reversed__(&self) -> ComponentIter {
+        ComponentIter::new_reversed(self.0.coords.as_slice())
+    }
+
+    fn __contains__(&self, value: &PyAny) -> bool {
+        seq::contains(self.0.coords.as_slice(), value)
+    }
+
+    #[classattr]
+    fn __match_args__() -> (&'static str, &'static str, &'static str, &'static str) {
+        ("i", "j", "k", "w")
+    }
+
+    #[staticmethod]
+    fn __len__() -> usize {
+        4
+    }
+
+    fn __richcmp__(&self, py: Python, other: &PyAny, op: CompareOp) -> Py<PyAny> {
+        let other = if let Ok(q) = other.extract::<PyRef<FrozenUnitQuaternion>>() {
+            q.0
+        } else if let Ok(q) = other.extract::<PyRef<UnitQuaternion>>() {
+            q.0
+        } else {
+            return py.NotImplemented();
+        };
+        match op {
+            CompareOp::Eq => (self.0 == other).into_py(py),
+            CompareOp::Ne => (self.0 != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash_components(self.canonical_coords().as_slice())
+    }
+
+    /// Returns a hash of this rotation's components snapped to a grid of
+    /// `resolution` sized cells.
+    fn quantized_hash(&self, resolution: f64) -> PyResult<u64> {
+        quantized_hash_components(self.canonical_coords().as_slice(), resolution)
+    }
+
+    #[pyo3(signature = (v, *, abs_tol=None, rel_tol=None, max_ulps=None, as_rotation=false))]
+    fn approx_equals(
+        &self,
+        v: &FrozenUnitQuaternion,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+        as_rotation: bool,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        let (a, b) = (&self.0.coords, &v.0.coords);
+        Ok(tol.eq(a, b) || (as_rotation && tol.eq(a, &-b)))
+    }
+
+    /// Returns a mutable copy of this rotation.
+    fn thaw(&self) -> UnitQuaternion {
+        UnitQuaternion(self.0)
+    }
+
+    fn angle(&self) -> f64 {
+        self.0.angle()
+    }
+
+    fn axis(&self) -> Option<Vector3> {
+        self.0.axis().map(|a| Vector3(*a))
+    }
+
+    fn euler(&self) -> (f64, f64, f64) {
+        self.0.euler_angles()
+    }
+
+    fn tuple(&self) -> (f64, f64, f64, f64) {
+        (self.0[0], self.0[1], self.0[2], self.0[3])
+    }
+
+    fn list(&self) -> [f64; 4] {
+        [self.0[0], self.0[1], self.0[2], self.0[3]]
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FrozenUnitQuaternion({}, {}, {}, {})",
+            self.0[0], self.0[1], self.0[2], self.0[3]
+        )
+    }
+}
+
+/// An immutable, hashable counterpart to Isometry3.
+#[pyclass(frozen)]
+#[derive(Clone)]
+pub struct FrozenIsometry3(pub na::Isometry3<f64>);
+
+impl FrozenIsometry3 {
+    fn components(&self) -> [f64; 7] {
+        let t = &self.0.translation.vector;
+        let r = &self.0.rotation.coords;
+        [t.x, t.y, t.z, r[0], r[1], r[2], r[3]]
+    }
+
+    fn canonical_components(&self) -> [f64; 7] {
+        let t = &self.0.translation.vector;
+        let r = FrozenUnitQuaternion(self.0.rotation).canonical_coords();
+        [t.x, t.y, t.z, r[0], r[1], r[2], r[3]]
+    }
+}
+
+#[pymethods]
+impl FrozenIsometry3 {
+    /// Creates the transform which rotates by `rotation` and then translates
+    /// by `translation`, as `Isometry3()` does. Both may be frozen or not.
+    #[new]
+    #[pyo3(signature = (translation=None, rotation=None))]
+    fn new(translation: Option<&PyAny>, rotation: Option<&PyAny>) -> PyResult<Self> {
+        let translation = match translation {
+            Some(t) => extract_vector(t)?.into(),
+            None => na::Translation3::identity(),
+        };
+        let rotation = match rotation {
+            Some(r) => extract_rotation(r)?,
+            None => na::UnitQuaternion::identity(),
+        };
+        Ok(FrozenIsometry3(na::Isometry3::from_parts(
+            translation,
+            rotation,
+        )))
+    }
+
+    #[getter]
+    fn get_translation(&self) -> FrozenVector3 {
+        FrozenVector3(self.0.translation.vector)
+    }
+
+    #[getter]
+    fn get_rotation(&self) -> FrozenUnitQuaternion {
+        FrozenUnitQuaternion(self.0.rotation)
+    }
+
+    fn __richcmp__(&self, py: Python, other: &PyAny, op: CompareOp) -> Py<PyAny> {
+        let other = if let Ok(iso) = other.extract::<PyRef<FrozenIsometry3>>() {
+            iso.0
+        } else if let Ok(iso) = other.extract::<PyRef<Isometry3>>() {
+            iso.0
+        } else {
+            return py.NotImplemented();
+        };
+        match op {
+            CompareOp::Eq => (self.0 == other).into_py(py),
+            CompareOp::Ne => (self.0 != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash_components(&self.canonical_components())
+    }
+
+    /// Returns a hash of this transform snapped to a grid. The translation is
+    /// quantized to `resolution` sized cells and the rotation's components to
+    /// `rotation_resolution` sized cells, which defaults to `resolution`.
+    fn quantized_hash(&self, resolution: f64, rotation_resolution: Option<f64>) -> PyResult<u64> {
+        let translation =
+            quantized_hash_components(self.0.translation.vector.as_slice(), resolution)?;
+        let rotation = FrozenUnitQuaternion(self.0.rotation)
+            .quantized_hash(rotation_resolution.unwrap_or(resolution))?;
+        let mut hasher = DefaultHasher::new();
+        (translation, rotation).hash(&mut hasher);
+        Ok(hasher.finish())
+    }
+
+    #[pyo3(signature = (arg, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        arg: &FrozenIsometry3,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &arg.0))
+    }
+
+    /// Returns a mutable copy of this transform.
+    fn thaw(&self) -> Isometry3 {
+        Isometry3(self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        let [x, y, z, i, j, k, w] = self.components();
+        format!(
+            "FrozenIsometry3(translation=({}, {}, {}), rotation=({}, {}, {}, {}))",
+            x, y, z, i, j, k, w
+        )
+    }
+}
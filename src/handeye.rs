@@ -0,0 +1,220 @@
+use crate::iso::Isometry3;
+use crate::results::NamedTuple;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+type V3 = na::Vector3<f64>;
+type M3 = na::Matrix3<f64>;
+
+static HAND_EYE_RESULT: NamedTuple = NamedTuple::new(
+    "HandEyeResult",
+    &[
+        "transform",
+        "rotation_rmse",
+        "translation_rmse",
+        "max_rotation_error",
+        "max_translation_error",
+    ],
+);
+
+/// Motions rotating by less than this many radians say nothing about the
+/// rotation axis.
+const MIN_ROTATION: f64 = 1e-6;
+
+/// The robot motions' rotation axes must span at least this angle (in
+/// radians) between them, or the rotation about the common axis is unknown.
+const MIN_AXIS_SPREAD: f64 = 0.05;
+
+#[derive(PartialEq)]
+enum Method {
+    /// Tsai and Lenz, 1989: solves for the rotation's (scaled) modified
+    /// Rodrigues vector linearly.
+    Tsai,
+    /// Park and Martin, 1994: the rotation which best maps the camera
+    /// motions' rotation vectors onto the robot's.
+    Park,
+}
+
+/// Checks that the rotation axes of the motions aren't all (nearly)
+/// parallel, which the rotation needs at least two of.
+fn check_axes(motions: &[na::Isometry3<f64>]) -> PyResult<()> {
+    let scatter: M3 = motions
+        .iter()
+        .filter_map(|m| m.rotation.axis_angle())
+        .filter(|(_, angle)| *angle > MIN_ROTATION)
+        .map(|(axis, _)| axis.into_inner() * axis.transpose())
+        .sum();
+    // For two axes at angle a the eigenvalues are 1 + cos(a) and 1 - cos(a)
+    let mut eigenvalues: Vec<f64> = scatter.symmetric_eigenvalues().iter().copied().collect();
+    eigenvalues.sort_by(|a, b| b.total_cmp(a));
+    let threshold = (1.0 - MIN_AXIS_SPREAD.cos()) / (1.0 + MIN_AXIS_SPREAD.cos());
+    if eigenvalues[1] <= eigenvalues[0] * threshold {
+        return Err(PyValueError::new_err(
+            "The robot motions' rotation axes are not diverse enough, at least two \
+             motions must rotate about clearly different axes",
+        ));
+    }
+    Ok(())
+}
+
+/// The modified Rodrigues vector `2 sin(angle / 2) axis` of a rotation,
+/// which is twice the vector part of its (positive) quaternion.
+fn rodrigues(rotation: &na::UnitQuaternion<f64>) -> V3 {
+    let q = rotation.quaternion();
+    q.imag() * 2.0 * q.w.signum()
+}
+
+fn tsai_rotation(robot: &[na::Isometry3<f64>], camera: &[na::Isometry3<f64>]) -> Option<M3> {
+    // skew(pa + pb) x = pb - pa, where x = tan(angle / 2) axis for the
+    // hand-eye rotation
+    let mut a = na::DMatrix::<f64>::zeros(3 * robot.len(), 3);
+    let mut b = na::DVector::<f64>::zeros(3 * robot.len());
+    for (i, (robot, camera)) in robot.iter().zip(camera).enumerate() {
+        let (pa, pb) = (rodrigues(&robot.rotation), rodrigues(&camera.rotation));
+        a.fixed_view_mut::<3, 3>(3 * i, 0)
+            .copy_from(&(pa + pb).cross_matrix());
+        b.fixed_rows_mut::<3>(3 * i).copy_from(&(pb - pa));
+    }
+    let x = a.svd(true, true).solve(&b, 1e-12).ok()?;
+    let x = V3::new(x[0], x[1], x[2]);
+    let p = x * 2.0 / (1.0 + x.magnitude_squared()).sqrt();
+    let p2 = p.magnitude_squared();
+    Some(
+        M3::identity() * (1.0 - p2 / 2.0)
+            + (p * p.transpose() + p.cross_matrix() * (4.0 - p2).sqrt()) * 0.5,
+    )
+}
+
+fn park_rotation(robot: &[na::Isometry3<f64>], camera: &[na::Isometry3<f64>]) -> Option<M3> {
+    // The robot's rotation vectors are the camera's rotated by the
+    // hand-eye rotation, so this is an orthogonal Procrustes problem
+    let covariance: M3 = robot
+        .iter()
+        .zip(camera)
+        .map(|(a, b)| b.rotation.scaled_axis() * a.rotation.scaled_axis().transpose())
+        .sum();
+    let svd = na::SVD::try_new(covariance, true, true, f64::EPSILON, 1000)?;
+    let (u, v_t) = (svd.u?, svd.v_t?);
+    let d = (v_t.transpose() * u.transpose()).determinant().signum();
+    Some(v_t.transpose() * M3::from_diagonal(&V3::new(1.0, 1.0, d)) * u.transpose())
+}
+
+/// The translation given the rotation, from `(Ra - I) t = R tb - ta`.
+fn translation(
+    robot: &[na::Isometry3<f64>],
+    camera: &[na::Isometry3<f64>],
+    rotation: &M3,
+) -> Option<V3> {
+    let mut a = na::DMatrix::<f64>::zeros(3 * robot.len(), 3);
+    let mut b = na::DVector::<f64>::zeros(3 * robot.len());
+    for (i, (robot, camera)) in robot.iter().zip(camera).enumerate() {
+        a.fixed_view_mut::<3, 3>(3 * i, 0)
+            .copy_from(&(robot.rotation.to_rotation_matrix().into_inner() - M3::identity()));
+        b.fixed_rows_mut::<3>(3 * i)
+            .copy_from(&(rotation * camera.translation.vector - robot.translation.vector));
+    }
+    let t = a.svd(true, true).solve(&b, 1e-12).ok()?;
+    Some(V3::new(t[0], t[1], t[2]))
+}
+
+/// Solves the hand-eye calibration problem `A X = X B` for the rigid
+/// transform `X`, from paired motions `A` of the robot and `B` of the camera,
+/// returning a `HandEyeResult(transform, rotation_rmse, translation_rmse,
+/// max_rotation_error, max_translation_error)`.
+///
+/// For a camera mounted on the robot's hand, each robot motion is the
+/// hand's motion between two stations, `inv(base_to_hand_j) * base_to_hand_i`,
+/// and the camera motion the matching motion of the camera, e.g.
+/// `camera_to_target_j * inv(camera_to_target_i)` when observing a fixed
+/// target. `transform` then maps camera coordinates into hand coordinates.
+///
+/// `method` is `"tsai"` for the Tsai-Lenz solver or `"park"` for the
+/// Park-Martin solver. Both find the rotation first and then the translation
+/// by linear least squares. The residuals compare `A X` with `X B` for each
+/// pair of motions: the rotation errors are angles in radians and the
+/// translation errors distances.
+///
+/// At least two motions are needed, and the robot must rotate about at least
+/// two clearly different axes, otherwise the rotation about a shared axis
+/// can't be found and a ValueError is raised.
+#[pyfunction]
+#[pyo3(signature = (robot_motions, camera_motions, *, method="tsai"))]
+pub fn calibrate_hand_eye(
+    py: Python,
+    robot_motions: Vec<PyRef<Isometry3>>,
+    camera_motions: Vec<PyRef<Isometry3>>,
+    method: &str,
+) -> PyResult<PyObject> {
+    let method = match method {
+        "tsai" => Method::Tsai,
+        "park" => Method::Park,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "method must be 'tsai' or 'park', not '{}'",
+                method
+            )))
+        }
+    };
+    if robot_motions.len() != camera_motions.len() {
+        return Err(PyValueError::new_err(format!(
+            "Got {} robot motions but {} camera motions",
+            robot_motions.len(),
+            camera_motions.len()
+        )));
+    }
+    if robot_motions.len() < 2 {
+        return Err(PyValueError::new_err(
+            "Hand-eye calibration needs at least 2 motions",
+        ));
+    }
+    let robot: Vec<na::Isometry3<f64>> = robot_motions.iter().map(|m| m.0).collect();
+    let camera: Vec<na::Isometry3<f64>> = camera_motions.iter().map(|m| m.0).collect();
+    let finite = |m: &na::Isometry3<f64>| {
+        m.translation.vector.iter().all(|v| v.is_finite())
+            && m.rotation.coords.iter().all(|v| v.is_finite())
+    };
+    if !robot.iter().chain(&camera).all(finite) {
+        return Err(PyValueError::new_err("Motions must be finite"));
+    }
+    check_axes(&robot)?;
+
+    let rotation = match method {
+        Method::Tsai => tsai_rotation(&robot, &camera),
+        Method::Park => park_rotation(&robot, &camera),
+    };
+    let transform = rotation
+        .and_then(|r| Some((r, translation(&robot, &camera, &r)?)))
+        .map(|(r, t)| {
+            na::Isometry3::from_parts(
+                t.into(),
+                na::UnitQuaternion::from_rotation_matrix(&na::Rotation3::from_matrix_unchecked(r)),
+            )
+        })
+        .ok_or_else(|| PyValueError::new_err("Hand-eye calibration failed to converge"))?;
+
+    let (rotation_errors, translation_errors): (Vec<f64>, Vec<f64>) = robot
+        .iter()
+        .zip(&camera)
+        .map(|(a, b)| {
+            let (ax, xb) = (a * transform, transform * b);
+            (
+                ax.rotation.angle_to(&xb.rotation),
+                (ax.translation.vector - xb.translation.vector).magnitude(),
+            )
+        })
+        .unzip();
+    let rms =
+        |errors: &[f64]| (errors.iter().map(|e| e * e).sum::<f64>() / errors.len() as f64).sqrt();
+    let max = |errors: &[f64]| errors.iter().copied().fold(0.0, f64::max);
+    HAND_EYE_RESULT.make(
+        py,
+        (
+            Isometry3(transform),
+            rms(&rotation_errors),
+            rms(&translation_errors),
+            max(&rotation_errors),
+            max(&translation_errors),
+        ),
+    )
+}
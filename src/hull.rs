@@ -0,0 +1,375 @@
+use crate::points::extract_points;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+type V3 = na::Vector3<f64>;
+
+struct Face {
+    vertices: [usize; 3],
+    normal: V3,
+    offset: f64,
+    /// Points which are outside this face, i.e. in front of its plane.
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl Face {
+    fn new(points: &[V3], vertices: [usize; 3]) -> Face {
+        let [a, b, c] = vertices.map(|i| points[i]);
+        let normal = (b - a).cross(&(c - a)).normalize();
+        Face {
+            vertices,
+            normal,
+            offset: normal.dot(&a),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    fn distance(&self, p: &V3) -> f64 {
+        self.normal.dot(p) - self.offset
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+/// Quickhull state: the faces built so far, and which face each directed
+/// edge belongs to so neighbouring faces can be found.
+struct Quickhull<'a> {
+    points: &'a [V3],
+    eps: f64,
+    faces: Vec<Face>,
+    edges: HashMap<(usize, usize), usize>,
+}
+
+impl<'a> Quickhull<'a> {
+    fn add_face(&mut self, vertices: [usize; 3]) -> Result<usize, String> {
+        let face = Face::new(self.points, vertices);
+        if !face.normal.iter().all(|c| c.is_finite()) {
+            return Err("the hull has a face with no area".to_string());
+        }
+        let index = self.faces.len();
+        for edge in face.edges() {
+            if self.edges.insert(edge, index).is_some() {
+                return Err("the hull's faces don't form a closed surface".to_string());
+            }
+        }
+        self.faces.push(face);
+        Ok(index)
+    }
+
+    fn remove_face(&mut self, index: usize) {
+        self.faces[index].alive = false;
+        for edge in self.faces[index].edges() {
+            self.edges.remove(&edge);
+        }
+    }
+
+    /// Adds each point to the outside set of the first face it's in front of,
+    /// dropping points which aren't outside any face as they're in the hull.
+    fn assign(&mut self, candidates: impl IntoIterator<Item = usize>, faces: &[usize]) {
+        for p in candidates {
+            let point = self.points[p];
+            if let Some(f) = faces
+                .iter()
+                .find(|f| self.faces[**f].distance(&point) > self.eps)
+            {
+                self.faces[*f].outside.push(p);
+            }
+        }
+    }
+
+    /// Adds the furthest point outside `face` to the hull, replacing every
+    /// face it can see with faces joining it to the horizon of those faces.
+    fn add_point(&mut self, face: usize) -> Result<(), String> {
+        let face_ref = &self.faces[face];
+        let eye = *face_ref
+            .outside
+            .iter()
+            .max_by(|a, b| {
+                let (da, db) = (
+                    face_ref.distance(&self.points[**a]),
+                    face_ref.distance(&self.points[**b]),
+                );
+                da.total_cmp(&db)
+            })
+            .expect("only faces with outside points are expanded");
+        let eye_point = self.points[eye];
+
+        // Flood fill the faces visible from the eye point
+        let mut visible = vec![face];
+        let mut is_visible = HashMap::from([(face, true)]);
+        let mut i = 0;
+        while i < visible.len() {
+            for (a, b) in self.faces[visible[i]].edges() {
+                let neighbour = *self
+                    .edges
+                    .get(&(b, a))
+                    .ok_or("the hull's faces don't form a closed surface")?;
+                if let Entry::Vacant(entry) = is_visible.entry(neighbour) {
+                    let sees = self.faces[neighbour].distance(&eye_point) > self.eps;
+                    entry.insert(sees);
+                    if sees {
+                        visible.push(neighbour);
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        let mut horizon = Vec::new();
+        let mut orphans = Vec::new();
+        for f in &visible {
+            for (a, b) in self.faces[*f].edges() {
+                let neighbour = self.edges[&(b, a)];
+                if !is_visible[&neighbour] {
+                    horizon.push((a, b));
+                }
+            }
+            orphans.append(&mut self.faces[*f].outside);
+        }
+        for f in &visible {
+            self.remove_face(*f);
+        }
+        let new_faces = horizon
+            .into_iter()
+            .map(|(a, b)| self.add_face([a, b, eye]))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.assign(orphans.into_iter().filter(|p| *p != eye), &new_faces);
+        Ok(())
+    }
+}
+
+/// Builds the convex hull of the points, returning its triangles as indices
+/// into `points` with counter-clockwise winding when viewed from outside.
+fn quickhull(points: &[V3]) -> Result<(Vec<[usize; 3]>, f64), String> {
+    if points.iter().any(|p| !p.iter().all(|c| c.is_finite())) {
+        return Err("points must be finite".to_string());
+    }
+    if points.len() < 4 {
+        return Err("a convex hull needs at least 4 points".to_string());
+    }
+    // Distances smaller than this are lost to rounding, as in Qhull
+    let max_abs = (0..3).map(|i| points.iter().map(|p| p[i].abs()).fold(0.0, f64::max));
+    let eps = 3.0 * f64::EPSILON * max_abs.sum::<f64>();
+
+    // Build an initial tetrahedron from extreme points
+    let extremes: Vec<usize> = (0..3)
+        .flat_map(|axis| {
+            let by_axis = |a: &usize, b: &usize| points[*a][axis].total_cmp(&points[*b][axis]);
+            [
+                (0..points.len()).min_by(by_axis).unwrap(),
+                (0..points.len()).max_by(by_axis).unwrap(),
+            ]
+        })
+        .collect();
+    let (mut i0, mut i1) = (extremes[0], extremes[1]);
+    for a in &extremes {
+        for b in &extremes {
+            if (points[*a] - points[*b]).magnitude() > (points[i0] - points[i1]).magnitude() {
+                (i0, i1) = (*a, *b);
+            }
+        }
+    }
+    let (p0, p1) = (points[i0], points[i1]);
+    if (p1 - p0).magnitude() <= eps {
+        return Err("the points are all the same".to_string());
+    }
+    let line = (p1 - p0).normalize();
+    let from_line = |p: &V3| {
+        let d = p - p0;
+        (d - line * line.dot(&d)).magnitude()
+    };
+    let i2 = (0..points.len())
+        .max_by(|a, b| from_line(&points[*a]).total_cmp(&from_line(&points[*b])))
+        .unwrap();
+    if from_line(&points[i2]) <= eps {
+        return Err("the points are collinear".to_string());
+    }
+    let normal = (p1 - p0).cross(&(points[i2] - p0)).normalize();
+    let from_plane = |p: &V3| normal.dot(&(p - p0));
+    let i3 = (0..points.len())
+        .max_by(|a, b| {
+            from_plane(&points[*a])
+                .abs()
+                .total_cmp(&from_plane(&points[*b]).abs())
+        })
+        .unwrap();
+    if from_plane(&points[i3]).abs() <= eps {
+        return Err("the points are coplanar".to_string());
+    }
+
+    let mut hull = Quickhull {
+        points,
+        eps,
+        faces: Vec::new(),
+        edges: HashMap::new(),
+    };
+    // Wind the faces so their normals point away from the fourth vertex
+    let faces = if from_plane(&points[i3]) < 0.0 {
+        [[i0, i1, i2], [i0, i3, i1], [i1, i3, i2], [i2, i3, i0]]
+    } else {
+        [[i0, i2, i1], [i0, i1, i3], [i1, i2, i3], [i2, i0, i3]]
+    };
+    let initial = faces
+        .into_iter()
+        .map(|f| hull.add_face(f))
+        .collect::<Result<Vec<_>, _>>()?;
+    hull.assign(
+        (0..points.len()).filter(|p| ![i0, i1, i2, i3].contains(p)),
+        &initial,
+    );
+
+    while let Some(face) = hull
+        .faces
+        .iter()
+        .position(|f| f.alive && !f.outside.is_empty())
+    {
+        hull.add_point(face)?;
+    }
+    let triangles = hull
+        .faces
+        .iter()
+        .filter(|f| f.alive)
+        .map(|f| f.vertices)
+        .collect();
+    Ok((triangles, eps))
+}
+
+/// The convex hull of a set of points: the smallest convex polyhedron which
+/// contains them all, made of triangles.
+#[pyclass(module = "deuterium")]
+pub struct ConvexHull {
+    vertices: Vec<V3>,
+    /// The index into the original points of each vertex.
+    indices: Vec<usize>,
+    faces: Vec<[usize; 3]>,
+    normals: Vec<V3>,
+    eps: f64,
+}
+
+#[pymethods]
+impl ConvexHull {
+    /// Computes the convex hull of the points using the quickhull algorithm.
+    /// The points may be a list of Vector3s, a list of 3 element sequences or
+    /// an N x 3 array.
+    ///
+    /// Raises a ValueError if there are fewer than 4 distinct points or they
+    /// are collinear or coplanar, as they then have no volume to enclose.
+    /// Duplicate points and points on the hull's faces are ignored.
+    #[new]
+    fn new(py: Python, points: &PyAny) -> PyResult<ConvexHull> {
+        let points = extract_points(py, points)?;
+        let (triangles, eps) = py
+            .allow_threads(|| quickhull(&points))
+            .map_err(|e| PyValueError::new_err(format!("Cannot build a convex hull: {}", e)))?;
+
+        // Renumber the vertices in the order they appear in the input
+        let mut indices: Vec<usize> = triangles.iter().flatten().copied().collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let renumber: HashMap<usize, usize> =
+            indices.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+        let faces = triangles.iter().map(|t| t.map(|p| renumber[&p])).collect();
+        let normals = triangles
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.map(|p| points[p]);
+                (b - a).cross(&(c - a)).normalize()
+            })
+            .collect();
+        Ok(ConvexHull {
+            vertices: indices.iter().map(|p| points[*p]).collect(),
+            indices,
+            faces,
+            normals,
+            eps,
+        })
+    }
+
+    #[getter]
+    fn get_vertices(&self) -> Vec<Vector3> {
+        self.vertices.iter().map(|v| Vector3(*v)).collect()
+    }
+
+    /// The index into the points the hull was built from of each vertex.
+    #[getter]
+    fn get_vertex_indices(&self) -> Vec<usize> {
+        self.indices.clone()
+    }
+
+    /// Each face as a tuple of three indices into `vertices`, which are
+    /// counter-clockwise when viewed from outside the hull.
+    #[getter]
+    fn get_faces(&self) -> Vec<(usize, usize, usize)> {
+        self.faces.iter().map(|[a, b, c]| (*a, *b, *c)).collect()
+    }
+
+    /// The outward facing unit normal of each face.
+    #[getter]
+    fn get_normals(&self) -> Vec<Vector3> {
+        self.normals.iter().map(|n| Vector3(*n)).collect()
+    }
+
+    fn volume(&self) -> f64 {
+        self.tetrahedra().map(|(volume, _)| volume).sum()
+    }
+
+    fn surface_area(&self) -> f64 {
+        self.faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.map(|v| self.vertices[v]);
+                (b - a).cross(&(c - a)).magnitude() * 0.5
+            })
+            .sum()
+    }
+
+    /// Returns the center of mass of the solid hull, which is usually not the
+    /// mean of its vertices.
+    fn centroid(&self) -> Vector3 {
+        let (volume, moment) = self
+            .tetrahedra()
+            .fold((0.0, V3::zeros()), |(volume, moment), (v, c)| {
+                (volume + v, moment + c * v)
+            });
+        Vector3(moment / volume)
+    }
+
+    /// Returns whether `point` is inside or on the surface of the hull, with
+    /// points within rounding error of the surface counting as on it.
+    fn contains(&self, point: &Vector3) -> bool {
+        self.faces
+            .iter()
+            .zip(&self.normals)
+            .all(|(f, n)| n.dot(&(point.0 - self.vertices[f[0]])) <= self.eps)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConvexHull(vertices={}, faces={})",
+            self.vertices.len(),
+            self.faces.len()
+        )
+    }
+}
+
+impl ConvexHull {
+    /// The volume and centroid of the tetrahedra joining each face to the
+    /// first vertex, which together fill the hull.
+    fn tetrahedra(&self) -> impl Iterator<Item = (f64, V3)> + '_ {
+        let apex = self.vertices[0];
+        self.faces.iter().map(move |f| {
+            let [a, b, c] = f.map(|v| self.vertices[v]);
+            let volume = (a - apex).dot(&(b - apex).cross(&(c - apex))) / 6.0;
+            (volume, (apex + a + b + c) / 4.0)
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use crate::kinematics::{JointKind, KinematicChain};
+use nalgebra as na;
+
+type V3 = na::Vector3<f64>;
+type Iso = na::Isometry3<f64>;
+
+/// What the end effector should reach.
+pub enum Target {
+    Position(V3),
+    Pose(Iso),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Method {
+    /// Damped least squares, `dq = J^T (J J^T + damping^2 I)^-1 e`, with a
+    /// fixed damping.
+    DampedLeastSquares,
+    /// Levenberg-Marquardt, which adapts the damping so that every step
+    /// reduces the error.
+    LevenbergMarquardt,
+}
+
+pub struct Options {
+    pub method: Method,
+    pub max_iterations: usize,
+    pub tolerance: f64,
+    pub angular_tolerance: f64,
+    pub damping: f64,
+}
+
+pub struct Solution {
+    pub q: Vec<f64>,
+    pub pose: Iso,
+    pub converged: bool,
+    pub iterations: usize,
+    pub position_error: f64,
+    pub orientation_error: Option<f64>,
+}
+
+/// The error of `pose` from the target: the position difference, followed
+/// for full poses by the rotation vector (twice the UnitQuaternion log) of
+/// the rotation taking `pose`'s orientation to the target's.
+fn error(target: &Target, pose: &Iso) -> na::DVector<f64> {
+    match target {
+        Target::Position(p) => {
+            na::DVector::from_column_slice((p - pose.translation.vector).as_slice())
+        }
+        Target::Pose(t) => {
+            let position = t.translation.vector - pose.translation.vector;
+            let orientation = (t.rotation * pose.rotation.inverse()).scaled_axis();
+            na::DVector::from_iterator(6, position.iter().chain(orientation.iter()).copied())
+        }
+    }
+}
+
+/// The Jacobian rows that the error depends on.
+fn jacobian(chain: &KinematicChain, target: &Target, q: &[f64]) -> na::DMatrix<f64> {
+    let jacobian = chain.jacobian_matrix(q);
+    let rows = match target {
+        Target::Position(_) => 3,
+        Target::Pose(_) => 6,
+    };
+    jacobian.rows(0, rows).into_owned()
+}
+
+/// Solves for joint values putting the chain's end effector at `target`,
+/// starting from `initial` and keeping within the joints' limits.
+pub fn solve(
+    chain: &KinematicChain,
+    target: &Target,
+    initial: &[f64],
+    options: &Options,
+) -> Solution {
+    let limits: Vec<(f64, f64)> = chain
+        .joints
+        .iter()
+        .filter(|j| j.kind != JointKind::Fixed)
+        .map(|j| j.limits.unwrap_or((f64::NEG_INFINITY, f64::INFINITY)))
+        .collect();
+    let clamp = |q: &mut [f64]| {
+        for (v, (lower, upper)) in q.iter_mut().zip(&limits) {
+            *v = v.clamp(*lower, *upper);
+        }
+    };
+    let errors = |e: &na::DVector<f64>| -> (f64, Option<f64>) {
+        let position = e.rows(0, 3).norm();
+        let orientation = (e.len() == 6).then(|| e.rows(3, 3).norm());
+        (position, orientation)
+    };
+    let reached = |e: &na::DVector<f64>| {
+        let (position, orientation) = errors(e);
+        position <= options.tolerance && orientation.is_none_or(|o| o <= options.angular_tolerance)
+    };
+
+    let mut q = initial.to_vec();
+    clamp(&mut q);
+    let mut pose = chain.end_effector(&q);
+    let mut e = error(target, &pose);
+    let mut lambda = options.damping * options.damping;
+    let mut iterations = 0;
+    while !reached(&e) && iterations < options.max_iterations {
+        iterations += 1;
+        let j = jacobian(chain, target, &q);
+        let step_from = |lambda: f64| -> Option<na::DVector<f64>> {
+            // Joints held at a limit the step pushes against are left out
+            // and the step solved for again with the others
+            let mut free = vec![true; q.len()];
+            loop {
+                let columns: Vec<usize> = (0..q.len()).filter(|&i| free[i]).collect();
+                let j = j.select_columns(&columns);
+                let step = match options.method {
+                    Method::DampedLeastSquares => {
+                        let a = &j * j.transpose()
+                            + na::DMatrix::identity(j.nrows(), j.nrows()) * lambda;
+                        j.transpose() * a.cholesky()?.solve(&e)
+                    }
+                    Method::LevenbergMarquardt => {
+                        let a = j.transpose() * &j
+                            + na::DMatrix::identity(j.ncols(), j.ncols()) * lambda;
+                        a.cholesky()?.solve(&(j.transpose() * &e))
+                    }
+                };
+                let mut full = na::DVector::zeros(q.len());
+                for (&i, s) in columns.iter().zip(step.iter()) {
+                    full[i] = *s;
+                }
+                let blocked: Vec<usize> = columns
+                    .into_iter()
+                    .filter(|&i| {
+                        let (lower, upper) = limits[i];
+                        (q[i] <= lower && full[i] < 0.0) || (q[i] >= upper && full[i] > 0.0)
+                    })
+                    .collect();
+                if blocked.is_empty() {
+                    return Some(full);
+                }
+                for i in blocked {
+                    free[i] = false;
+                }
+            }
+        };
+        let moved = |step: &na::DVector<f64>| {
+            let mut candidate: Vec<f64> = q.iter().zip(step.iter()).map(|(v, s)| v + s).collect();
+            clamp(&mut candidate);
+            let pose = chain.end_effector(&candidate);
+            let e = error(target, &pose);
+            (candidate, pose, e)
+        };
+        let next = match options.method {
+            Method::DampedLeastSquares => step_from(lambda).map(|step| moved(&step)),
+            Method::LevenbergMarquardt => loop {
+                match step_from(lambda).map(|step| moved(&step)) {
+                    Some(next) if next.2.norm() < e.norm() => {
+                        lambda = (lambda / 10.0).max(1e-12);
+                        break Some(next);
+                    }
+                    _ => {
+                        lambda = (lambda * 10.0).max(1e-12);
+                        if lambda > 1e12 {
+                            break None;
+                        }
+                    }
+                }
+            },
+        };
+        // Stop once no step makes progress, e.g. at a joint limit or a
+        // local minimum of the error
+        match next {
+            Some((candidate, next_pose, next_e)) => {
+                let stalled = candidate
+                    .iter()
+                    .zip(&q)
+                    .all(|(a, b)| (a - b).abs() <= 1e-15 * (1.0 + b.abs()));
+                q = candidate;
+                pose = next_pose;
+                e = next_e;
+                if stalled {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+    let (position_error, orientation_error) = errors(&e);
+    Solution {
+        q,
+        pose,
+        converged: reached(&e),
+        iterations,
+        position_error,
+        orientation_error,
+    }
+}
@@ -0,0 +1,253 @@
+use crate::aabb::Aabb;
+use crate::plane::Plane;
+use crate::ray::Ray;
+use crate::results::NamedTuple;
+use crate::sphere::Sphere;
+use crate::triangle::Triangle;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::prelude::*;
+
+type V3 = na::Vector3<f64>;
+
+pub static RAY_HIT: NamedTuple =
+    NamedTuple::new("RayHit", &["distance", "point", "normal", "barycentric"]);
+pub static CLOSEST_POINTS: NamedTuple =
+    NamedTuple::new("ClosestPoints", &["point", "other_point", "distance"]);
+
+/// Where a ray first hits a surface. The normal always faces back against the
+/// ray, and `barycentric` holds the `(u, v)` weights of a triangle's `b` and `c`
+/// vertices for triangle hits.
+pub struct Hit {
+    pub distance: f64,
+    pub point: V3,
+    pub normal: V3,
+    pub barycentric: Option<(f64, f64)>,
+}
+
+impl Hit {
+    fn new(ray: &Ray, distance: f64, normal: V3) -> Hit {
+        Hit {
+            distance,
+            point: ray.point_at(distance),
+            normal: facing(normal, ray),
+            barycentric: None,
+        }
+    }
+
+    pub fn into_result(self, py: Python) -> PyResult<PyObject> {
+        RAY_HIT.make(
+            py,
+            (
+                self.distance,
+                Vector3(self.point),
+                Vector3(self.normal),
+                self.barycentric,
+            ),
+        )
+    }
+}
+
+/// Flips `normal` if needed so it points back against the ray.
+fn facing(normal: V3, ray: &Ray) -> V3 {
+    if normal.dot(&ray.direction) > 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+pub fn ray_plane(ray: &Ray, plane: &Plane) -> Option<Hit> {
+    let denom = plane.normal.dot(&ray.direction);
+    if denom == 0.0 {
+        return None;
+    }
+    let t = -plane.signed_distance_to(&ray.origin) / denom;
+    if t < 0.0 {
+        return None;
+    }
+    Some(Hit::new(ray, t, *plane.normal))
+}
+
+/// Möller–Trumbore ray/triangle intersection, which hits both faces.
+pub fn ray_triangle(ray: &Ray, tri: &Triangle) -> Option<Hit> {
+    let (e1, e2) = (tri.b - tri.a, tri.c - tri.a);
+    let p = ray.direction.cross(&e2);
+    let det = e1.dot(&p);
+    // The ray is parallel to the triangle, or the triangle is degenerate
+    if det.abs() <= f64::EPSILON * e1.magnitude() * e2.magnitude() {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = ray.origin - tri.a;
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&e1);
+    let v = ray.direction.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = e2.dot(&q) * inv_det;
+    if t < 0.0 {
+        return None;
+    }
+    let mut hit = Hit::new(ray, t, e1.cross(&e2).normalize());
+    hit.barycentric = Some((u, v));
+    Some(hit)
+}
+
+/// Hits the sphere's surface, so a ray starting inside hits it on the way out.
+pub fn ray_sphere(ray: &Ray, sphere: &Sphere) -> Option<Hit> {
+    let oc = ray.origin - sphere.center;
+    let b = oc.dot(&ray.direction);
+    let c = oc.magnitude_squared() - sphere.radius * sphere.radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let t = if -b - root >= 0.0 {
+        -b - root
+    } else {
+        -b + root
+    };
+    if t < 0.0 {
+        return None;
+    }
+    let point = ray.point_at(t);
+    let normal = if sphere.radius > 0.0 {
+        (point - sphere.center) / sphere.radius
+    } else {
+        -*ray.direction
+    };
+    Some(Hit::new(ray, t, normal))
+}
+
+/// Slab test against the box's faces, so a ray starting inside hits it on
+/// the way out.
+pub fn ray_aabb(ray: &Ray, aabb: &Aabb) -> Option<Hit> {
+    let (mut t_near, mut t_far) = (f64::NEG_INFINITY, f64::INFINITY);
+    let (mut near_axis, mut far_axis) = (0, 0);
+    for i in 0..3 {
+        let (o, d) = (ray.origin[i], ray.direction[i]);
+        if d == 0.0 {
+            if o < aabb.min[i] || o > aabb.max[i] {
+                return None;
+            }
+            continue;
+        }
+        let (t0, t1) = ((aabb.min[i] - o) / d, (aabb.max[i] - o) / d);
+        let (t0, t1) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+        if t0 > t_near {
+            t_near = t0;
+            near_axis = i;
+        }
+        if t1 < t_far {
+            t_far = t1;
+            far_axis = i;
+        }
+    }
+    if t_far < t_near.max(0.0) {
+        return None;
+    }
+    let (t, axis) = if t_near >= 0.0 {
+        (t_near, near_axis)
+    } else {
+        (t_far, far_axis)
+    };
+    let mut normal = V3::zeros();
+    normal[axis] = 1.0;
+    Some(Hit::new(ray, t, normal))
+}
+
+/// The parameters `(s, t)` of the closest points `p1 + s * d1` and
+/// `p2 + t * d2` on two segments, where `s` lies in `[0, max1]` and `t` in
+/// `[0, max2]`. Rays are segments with an infinite maximum.
+///
+/// From Ericson, Real-Time Collision Detection, section 5.1.9.
+pub fn closest_parameters(p1: &V3, d1: &V3, max1: f64, p2: &V3, d2: &V3, max2: f64) -> (f64, f64) {
+    let r = p1 - p2;
+    let (a, e, f) = (d1.magnitude_squared(), d2.magnitude_squared(), d2.dot(&r));
+    if a == 0.0 && e == 0.0 {
+        return (0.0, 0.0);
+    }
+    if a == 0.0 {
+        return (0.0, (f / e).clamp(0.0, max2));
+    }
+    let c = d1.dot(&r);
+    if e == 0.0 {
+        return ((-c / a).clamp(0.0, max1), 0.0);
+    }
+    let b = d1.dot(d2);
+    let denom = a * e - b * b;
+    // Parallel lines have no unique closest points, so start from p1
+    let s = if denom > 0.0 {
+        ((b * f - c * e) / denom).clamp(0.0, max1)
+    } else {
+        0.0
+    };
+    let t = (b * s + f) / e;
+    if t < 0.0 {
+        ((-c / a).clamp(0.0, max1), 0.0)
+    } else if t > max2 {
+        (((b * max2 - c) / a).clamp(0.0, max1), max2)
+    } else {
+        (s, t)
+    }
+}
+
+/// Builds a `ClosestPoints` result from a pair of points.
+pub fn closest_points_result(py: Python, point: V3, other_point: V3) -> PyResult<PyObject> {
+    CLOSEST_POINTS.make(
+        py,
+        (
+            Vector3(point),
+            Vector3(other_point),
+            (other_point - point).magnitude(),
+        ),
+    )
+}
+
+/// The closest point on (or in) a triangle to `p`, found by working out which
+/// vertex, edge or face region `p` projects into.
+///
+/// From Ericson, Real-Time Collision Detection, section 5.1.5.
+pub fn closest_point_on_triangle(p: &V3, tri: &Triangle) -> V3 {
+    let (a, b, c) = (tri.a, tri.b, tri.c);
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = va + vb + vc;
+    if denom == 0.0 {
+        // A degenerate triangle whose vertices are all outside the regions
+        // above can only be a point
+        return a;
+    }
+    a + ab * (vb / denom) + ac * (vc / denom)
+}
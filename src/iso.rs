@@ -1,6 +1,7 @@
+use crate::frozen::FrozenIsometry3;
 use crate::quat::UnitQuaternion;
+use crate::tolerance::Tolerance;
 use crate::vec3::Vector3;
-use approx::AbsDiffEq;
 use nalgebra as na;
 use pyo3::prelude::*;
 use pyo3::pyclass::CompareOp;
@@ -9,7 +10,19 @@ use pyo3::pyclass::CompareOp;
 pub struct Isometry3(pub na::Isometry3<f64>);
 
 #[pymethods]
+#[allow(clippy::needless_return, clippy::unused_unit, clippy::assign_op_pattern)]
 impl Isometry3 {
+    /// Creates the transform which rotates by `rotation` and then translates
+    /// by `translation`, either of which defaults to none.
+    #[new]
+    #[pyo3(signature = (translation=None, rotation=None))]
+    fn new(translation: Option<&Vector3>, rotation: Option<&UnitQuaternion>) -> Isometry3 {
+        Isometry3(na::Isometry3::from_parts(
+            translation.map_or_else(na::Translation3::identity, |t| t.0.into()),
+            rotation.map_or_else(na::UnitQuaternion::identity, |r| r.0),
+        ))
+    }
+
     #[staticmethod]
     fn identity() -> Isometry3 {
         return Isometry3(na::Isometry3::identity());
@@ -28,8 +41,15 @@ impl Isometry3 {
         }
     }
 
-    fn approx_equals(&self, arg: &Isometry3) -> bool {
-        self.0.abs_diff_eq(&arg.0, 1e-08)
+    #[pyo3(signature = (arg, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        arg: &Isometry3,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &arg.0))
     }
 
     fn __mul__(&self, py: Python, arg: &PyAny) -> PyResult<PyObject> {
@@ -62,6 +82,11 @@ impl Isometry3 {
         Isometry3(self.0.inverse())
     }
 
+    /// Returns an immutable, hashable copy of this transform.
+    fn freeze(&self) -> FrozenIsometry3 {
+        FrozenIsometry3(self.0)
+    }
+
     #[getter]
     fn get_translation(&self) -> Vector3 {
         Vector3::from_translation(&self.0.translation)
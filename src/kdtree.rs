@@ -0,0 +1,314 @@
+use crate::points::extract_points;
+use crate::results::NamedTuple;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+type V3 = na::Vector3<f64>;
+
+static NEAREST: NamedTuple = NamedTuple::new("Nearest", &["index", "distance"]);
+static NEIGHBOURS: NamedTuple = NamedTuple::new("Neighbours", &["indices", "distances"]);
+
+/// A neighbour found by a query, ordered by its squared distance so a
+/// BinaryHeap of them keeps the furthest on top.
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    distance_squared: f64,
+    index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance_squared
+            .total_cmp(&other.distance_squared)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+/// A balanced kd-tree stored implicitly in an array: the node for a range of
+/// `order` is its middle element, which splits the rest of the range along
+/// `axes[middle]` into the left and right subtrees.
+pub struct Tree {
+    points: Vec<V3>,
+    order: Vec<usize>,
+    axes: Vec<u8>,
+}
+
+impl Tree {
+    pub fn new(points: Vec<V3>) -> Tree {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        let mut axes = vec![0; points.len()];
+        Tree::build(&points, &mut order, &mut axes);
+        Tree {
+            points,
+            order,
+            axes,
+        }
+    }
+
+    /// Splits each range along the axis the points in it are most spread out
+    /// along, which keeps the cells close to cubes.
+    fn build(points: &[V3], order: &mut [usize], axes: &mut [u8]) {
+        if order.len() <= 1 {
+            return;
+        }
+        let (mut min, mut max) = (points[order[0]], points[order[0]]);
+        for i in order.iter() {
+            min = min.inf(&points[*i]);
+            max = max.sup(&points[*i]);
+        }
+        let axis = (max - min).imax();
+        let middle = order.len() / 2;
+        order.select_nth_unstable_by(middle, |a, b| points[*a][axis].total_cmp(&points[*b][axis]));
+        axes[middle] = axis as u8;
+        let (left, right) = order.split_at_mut(middle);
+        let (left_axes, right_axes) = axes.split_at_mut(middle);
+        Tree::build(points, left, left_axes);
+        Tree::build(points, &mut right[1..], &mut right_axes[1..]);
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Calls `visit` with the point index and squared distance from `p` of
+    /// each node in `range` which may be within squared distance `bound()`
+    /// of `p`, searching the nearer subtree of each node first.
+    fn search(
+        &self,
+        p: &V3,
+        range: Range<usize>,
+        visit: &mut impl FnMut(usize, f64),
+        bound: &impl Fn() -> f64,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let middle = (range.start + range.end) / 2;
+        let index = self.order[middle];
+        let point = &self.points[index];
+        visit(index, (point - p).magnitude_squared());
+        let axis = self.axes[middle] as usize;
+        let diff = p[axis] - point[axis];
+        let (near, far) = if diff < 0.0 {
+            (range.start..middle, middle + 1..range.end)
+        } else {
+            (middle + 1..range.end, range.start..middle)
+        };
+        self.search(p, near, visit, bound);
+        if diff * diff <= bound() {
+            self.search(p, far, visit, bound);
+        }
+    }
+
+    /// The index of the point nearest `p` and its squared distance, or None
+    /// if the tree is empty. Ties go to the lowest index.
+    pub fn nearest(&self, p: &V3) -> Option<(usize, f64)> {
+        let best = std::cell::Cell::new(None::<Candidate>);
+        self.search(
+            p,
+            0..self.len(),
+            &mut |index, distance_squared| {
+                let candidate = Candidate {
+                    distance_squared,
+                    index,
+                };
+                match best.get() {
+                    Some(b) if b <= candidate => {}
+                    _ => best.set(Some(candidate)),
+                }
+            },
+            &|| best.get().map_or(f64::INFINITY, |b| b.distance_squared),
+        );
+        best.get().map(|b| (b.index, b.distance_squared))
+    }
+
+    /// The indices of the `k` points nearest `p` and their squared distances,
+    /// nearest first.
+    pub fn k_nearest(&self, p: &V3, k: usize) -> Vec<(usize, f64)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let heap = std::cell::RefCell::new(BinaryHeap::with_capacity(k + 1));
+        self.search(
+            p,
+            0..self.len(),
+            &mut |index, distance_squared| {
+                let mut heap = heap.borrow_mut();
+                heap.push(Candidate {
+                    distance_squared,
+                    index,
+                });
+                if heap.len() > k {
+                    heap.pop();
+                }
+            },
+            &|| {
+                let heap = heap.borrow();
+                match heap.peek() {
+                    Some(furthest) if heap.len() == k => furthest.distance_squared,
+                    _ => f64::INFINITY,
+                }
+            },
+        );
+        heap.into_inner()
+            .into_sorted_vec()
+            .into_iter()
+            .map(|c| (c.index, c.distance_squared))
+            .collect()
+    }
+
+    /// The indices of the points within `radius` of `p` and their squared
+    /// distances, nearest first.
+    pub fn within_radius(&self, p: &V3, radius: f64) -> Vec<(usize, f64)> {
+        let radius_squared = radius * radius;
+        let mut found = Vec::new();
+        self.search(
+            p,
+            0..self.len(),
+            &mut |index, distance_squared| {
+                if distance_squared <= radius_squared {
+                    found.push(Candidate {
+                        distance_squared,
+                        index,
+                    });
+                }
+            },
+            &|| radius_squared,
+        );
+        found.sort_unstable();
+        found
+            .into_iter()
+            .map(|c| (c.index, c.distance_squared))
+            .collect()
+    }
+}
+
+fn unzip_distances(found: Vec<(usize, f64)>) -> (Vec<usize>, Vec<f64>) {
+    found.into_iter().map(|(i, d2)| (i, d2.sqrt())).unzip()
+}
+
+fn check_radius(radius: f64) -> PyResult<()> {
+    if radius.is_nan() || radius < 0.0 {
+        return Err(PyValueError::new_err(
+            "radius must be a non-negative number",
+        ));
+    }
+    Ok(())
+}
+
+/// A spatial index over a fixed set of points for fast nearest neighbour
+/// queries. Query results refer to points by their index in the set the
+/// tree was built from, and distances are Euclidean.
+///
+/// The `_batch` queries take many points at once and release the GIL while
+/// they run, so other Python threads can run alongside them.
+#[pyclass(module = "deuterium")]
+pub struct KdTree(pub Tree);
+
+impl KdTree {
+    fn check_not_empty(&self) -> PyResult<()> {
+        if self.0.len() == 0 {
+            return Err(PyValueError::new_err("KdTree is empty"));
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl KdTree {
+    /// Builds a tree from a list of Vector3s, a list of 3 element sequences or
+    /// an N x 3 array.
+    #[new]
+    fn new(py: Python, points: &PyAny) -> PyResult<KdTree> {
+        let points = extract_points(py, points)?;
+        if points.iter().any(|p| !p.iter().all(|c| c.is_finite())) {
+            return Err(PyValueError::new_err("KdTree points must be finite"));
+        }
+        Ok(KdTree(py.allow_threads(|| Tree::new(points))))
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the point at `index` in the set the tree was built from.
+    fn point(&self, index: isize) -> PyResult<Vector3> {
+        let index = crate::seq::normalize_index(index, self.0.len())?;
+        Ok(Vector3(self.0.points[index]))
+    }
+
+    /// Returns the point nearest `point` as a `Nearest(index, distance)`.
+    /// Raises a ValueError if the tree is empty.
+    fn nearest(&self, py: Python, point: &Vector3) -> PyResult<PyObject> {
+        self.check_not_empty()?;
+        let (index, distance_squared) = self.0.nearest(&point.0).unwrap();
+        NEAREST.make(py, (index, distance_squared.sqrt()))
+    }
+
+    /// Returns the `k` points nearest `point`, or every point if there are
+    /// fewer than `k`, as `Neighbours(indices, distances)` nearest first.
+    fn k_nearest(&self, py: Python, point: &Vector3, k: usize) -> PyResult<PyObject> {
+        NEIGHBOURS.make(py, unzip_distances(self.0.k_nearest(&point.0, k)))
+    }
+
+    /// Returns every point within `radius` of `point` (inclusive) as
+    /// `Neighbours(indices, distances)` nearest first.
+    fn within_radius(&self, py: Python, point: &Vector3, radius: f64) -> PyResult<PyObject> {
+        check_radius(radius)?;
+        NEIGHBOURS.make(py, unzip_distances(self.0.within_radius(&point.0, radius)))
+    }
+
+    /// Finds the nearest point to each of `points`, returning
+    /// `Neighbours(indices, distances)` with one entry for each query point.
+    fn nearest_batch(&self, py: Python, points: &PyAny) -> PyResult<PyObject> {
+        self.check_not_empty()?;
+        let queries = extract_points(py, points)?;
+        let found: Vec<(usize, f64)> =
+            py.allow_threads(|| queries.iter().map(|p| self.0.nearest(p).unwrap()).collect());
+        NEIGHBOURS.make(py, unzip_distances(found))
+    }
+
+    /// Finds the `k` nearest points to each of `points`, returning
+    /// `Neighbours(indices, distances)` with a list of each for every query.
+    fn k_nearest_batch(&self, py: Python, points: &PyAny, k: usize) -> PyResult<PyObject> {
+        let queries = extract_points(py, points)?;
+        let found: Vec<(Vec<usize>, Vec<f64>)> = py.allow_threads(|| {
+            queries
+                .iter()
+                .map(|p| unzip_distances(self.0.k_nearest(p, k)))
+                .collect()
+        });
+        NEIGHBOURS.make(py, found.into_iter().unzip::<_, _, Vec<_>, Vec<_>>())
+    }
+
+    /// Finds the points within `radius` of each of `points`, returning
+    /// `Neighbours(indices, distances)` with a list of each for every query.
+    fn within_radius_batch(&self, py: Python, points: &PyAny, radius: f64) -> PyResult<PyObject> {
+        check_radius(radius)?;
+        let queries = extract_points(py, points)?;
+        let found: Vec<(Vec<usize>, Vec<f64>)> = py.allow_threads(|| {
+            queries
+                .iter()
+                .map(|p| unzip_distances(self.0.within_radius(p, radius)))
+                .collect()
+        });
+        NEIGHBOURS.make(py, found.into_iter().unzip::<_, _, Vec<_>, Vec<_>>())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("KdTree({} points)", self.0.len())
+    }
+}
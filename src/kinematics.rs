@@ -0,0 +1,613 @@
+use crate::ik;
+use crate::iso::Isometry3;
+use crate::results::NamedTuple;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+
+type V3 = na::Vector3<f64>;
+type Iso = na::Isometry3<f64>;
+
+static IK_RESULT: NamedTuple = NamedTuple::new(
+    "IkResult",
+    &[
+        "q",
+        "pose",
+        "converged",
+        "iterations",
+        "position_error",
+        "orientation_error",
+    ],
+);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum JointKind {
+    Revolute,
+    Prismatic,
+    Fixed,
+}
+
+impl JointKind {
+    fn new(kind: &str) -> PyResult<JointKind> {
+        match kind {
+            "revolute" => Ok(JointKind::Revolute),
+            "prismatic" => Ok(JointKind::Prismatic),
+            "fixed" => Ok(JointKind::Fixed),
+            _ => Err(PyValueError::new_err(format!(
+                "Joint kind must be 'revolute', 'prismatic' or 'fixed', not '{}'",
+                kind
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            JointKind::Revolute => "revolute",
+            JointKind::Prismatic => "prismatic",
+            JointKind::Fixed => "fixed",
+        }
+    }
+}
+
+/// A joint of a serial chain, which moves its link relative to the previous
+/// one by `origin * motion(q + offset) * tip`, where `motion` rotates about
+/// (revolute joints) or translates along (prismatic joints) `axis` by the
+/// joint value `q`.
+///
+/// URDF-style joints have just an `origin`, placing the joint frame in the
+/// parent link's frame. Denavit-Hartenberg joints also need the `tip`
+/// transform after the motion to reach the next link frame. Fixed joints
+/// don't move and take no joint value.
+///
+/// Moving joints may have `limits`, the `(lower, upper)` range of joint
+/// values that inverse kinematics keeps to.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct Joint {
+    pub kind: JointKind,
+    pub axis: na::Unit<V3>,
+    pub origin: Iso,
+    pub tip: Iso,
+    pub offset: f64,
+    pub limits: Option<(f64, f64)>,
+}
+
+impl Joint {
+    fn motion(&self, q: f64) -> Iso {
+        let q = q + self.offset;
+        match self.kind {
+            JointKind::Revolute => rotation(self.axis.into_inner(), q),
+            JointKind::Prismatic => Iso::from_parts(
+                na::Translation3::from(self.axis.into_inner() * q),
+                na::UnitQuaternion::identity(),
+            ),
+            JointKind::Fixed => Iso::identity(),
+        }
+    }
+
+    fn check_finite(&self) -> PyResult<()> {
+        let finite = |iso: &Iso| {
+            iso.translation.vector.iter().all(|v| v.is_finite())
+                && iso.rotation.coords.iter().all(|v| v.is_finite())
+        };
+        if !(finite(&self.origin) && finite(&self.tip) && self.offset.is_finite()) {
+            return Err(PyValueError::new_err("Joint parameters must be finite"));
+        }
+        match self.limits {
+            Some((lower, upper)) if lower.is_nan() || upper.is_nan() || lower > upper => {
+                Err(PyValueError::new_err(
+                    "Joint limits must be a (lower, upper) range with lower <= upper",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn translation(x: f64, y: f64, z: f64) -> Iso {
+    Iso::translation(x, y, z)
+}
+
+fn rotation(axis: V3, angle: f64) -> Iso {
+    Iso::rotation(axis * angle)
+}
+
+#[pymethods]
+impl Joint {
+    /// Creates a joint of the given `kind`, `"revolute"`, `"prismatic"` or
+    /// `"fixed"`, moving about or along `axis` (the z axis by default) in the
+    /// joint frame, which `origin` places in the parent link's frame.
+    #[new]
+    #[pyo3(signature = (kind, axis=None, origin=None, tip=None, offset=0.0, limits=None))]
+    fn new(
+        kind: &str,
+        axis: Option<&Vector3>,
+        origin: Option<&Isometry3>,
+        tip: Option<&Isometry3>,
+        offset: f64,
+        limits: Option<(f64, f64)>,
+    ) -> PyResult<Joint> {
+        let axis = axis.map_or(V3::z(), |a| a.0);
+        let axis = na::Unit::try_new(axis, 1e-12)
+            .filter(|a| a.iter().all(|v| v.is_finite()))
+            .ok_or_else(|| {
+                PyValueError::new_err("A joint's axis must be a finite, non-zero vector")
+            })?;
+        let joint = Joint {
+            kind: JointKind::new(kind)?,
+            axis,
+            origin: origin.map_or(Iso::identity(), |o| o.0),
+            tip: tip.map_or(Iso::identity(), |t| t.0),
+            offset,
+            limits,
+        };
+        joint.check_finite()?;
+        Ok(joint)
+    }
+
+    /// Creates a joint as a URDF `<joint>` element describes it: `kind` is
+    /// `"revolute"`, `"continuous"`, `"prismatic"` or `"fixed"`, `xyz` and
+    /// `rpy` are the origin's translation and fixed-axis roll, pitch and yaw
+    /// angles in radians, and `axis` defaults to the x axis as in URDF.
+    /// `limits` are the `<limit>` element's `(lower, upper)`.
+    #[staticmethod]
+    #[pyo3(signature = (kind, xyz=(0.0, 0.0, 0.0), rpy=(0.0, 0.0, 0.0), axis=(1.0, 0.0, 0.0), limits=None))]
+    fn urdf(
+        kind: &str,
+        xyz: (f64, f64, f64),
+        rpy: (f64, f64, f64),
+        axis: (f64, f64, f64),
+        limits: Option<(f64, f64)>,
+    ) -> PyResult<Joint> {
+        let kind = match kind {
+            "continuous" => "revolute",
+            kind => kind,
+        };
+        let origin = Iso::from_parts(
+            na::Translation3::new(xyz.0, xyz.1, xyz.2),
+            na::UnitQuaternion::from_euler_angles(rpy.0, rpy.1, rpy.2),
+        );
+        Joint::new(
+            kind,
+            Some(&Vector3(V3::new(axis.0, axis.1, axis.2))),
+            Some(&Isometry3(origin)),
+            None,
+            0.0,
+            limits,
+        )
+    }
+
+    /// Creates a joint from Denavit-Hartenberg parameters: link length `a`,
+    /// link twist `alpha`, link offset `d` and joint angle `theta`. Revolute
+    /// joints add their value to `theta` and prismatic joints to `d`.
+    ///
+    /// With the standard convention the joint transform is
+    /// `Rz(theta) Tz(d) Tx(a) Rx(alpha)`. With `modified=True` it is Craig's
+    /// `Rx(alpha) Tx(a) Rz(theta) Tz(d)`, where `a` and `alpha` describe the
+    /// previous link.
+    #[staticmethod]
+    #[pyo3(signature = (a, alpha, d, theta, kind="revolute", *, modified=false, limits=None))]
+    fn dh(
+        a: f64,
+        alpha: f64,
+        d: f64,
+        theta: f64,
+        kind: &str,
+        modified: bool,
+        limits: Option<(f64, f64)>,
+    ) -> PyResult<Joint> {
+        let kind = JointKind::new(kind)?;
+        let (x, z) = (V3::x(), V3::z());
+        // Tx(a) and Rx(alpha) commute, as do Rz(theta) and Tz(d), so the
+        // motion can be moved next to the fixed parts of either convention
+        let link = translation(a, 0.0, 0.0) * rotation(x, alpha);
+        let (origin, tip, offset) = match (kind, modified) {
+            (JointKind::Revolute, false) => (translation(0.0, 0.0, d), link, theta),
+            (JointKind::Prismatic, false) => (rotation(z, theta), link, d),
+            (JointKind::Fixed, false) => (
+                rotation(z, theta) * translation(0.0, 0.0, d) * link,
+                Iso::identity(),
+                0.0,
+            ),
+            (JointKind::Revolute, true) => {
+                (link * translation(0.0, 0.0, d), Iso::identity(), theta)
+            }
+            (JointKind::Prismatic, true) => (link * rotation(z, theta), Iso::identity(), d),
+            (JointKind::Fixed, true) => (
+                link * rotation(z, theta) * translation(0.0, 0.0, d),
+                Iso::identity(),
+                0.0,
+            ),
+        };
+        let joint = Joint {
+            kind,
+            axis: na::Vector3::z_axis(),
+            origin,
+            tip,
+            offset,
+            limits,
+        };
+        joint.check_finite()?;
+        Ok(joint)
+    }
+
+    #[getter]
+    fn get_kind(&self) -> &'static str {
+        self.kind.name()
+    }
+
+    #[getter]
+    fn get_axis(&self) -> Vector3 {
+        Vector3(self.axis.into_inner())
+    }
+
+    #[getter]
+    fn get_origin(&self) -> Isometry3 {
+        Isometry3(self.origin)
+    }
+
+    #[getter]
+    fn get_tip(&self) -> Isometry3 {
+        Isometry3(self.tip)
+    }
+
+    #[getter]
+    fn get_offset(&self) -> f64 {
+        self.offset
+    }
+
+    #[getter]
+    fn get_limits(&self) -> Option<(f64, f64)> {
+        self.limits
+    }
+
+    /// Returns the transform from the parent link's frame to this joint's
+    /// link frame for joint value `q`.
+    #[pyo3(signature = (q=0.0))]
+    fn transform(&self, q: f64) -> Isometry3 {
+        Isometry3(self.origin * self.motion(q) * self.tip)
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Joint, op: CompareOp) -> Py<PyAny> {
+        let eq = self.kind == other.kind
+            && self.axis == other.axis
+            && self.origin == other.origin
+            && self.tip == other.tip
+            && self.offset == other.offset
+            && self.limits == other.limits;
+        match op {
+            CompareOp::Eq => eq.into_py(py),
+            CompareOp::Ne => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let a = self.axis;
+        format!(
+            "Joint('{}', axis=({}, {}, {}))",
+            self.kind.name(),
+            a.x,
+            a.y,
+            a.z
+        )
+    }
+}
+
+/// A serial chain of joints, such as a robot arm, from its `base` to the
+/// `tool` frame at its end effector.
+///
+/// Each joint's link pose is the product of the base and the joint
+/// transforms up to and including its own, and the end effector's pose
+/// also includes `tool`. Fixed joints take no joint value, so a chain has
+/// one degree of freedom per moving joint.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct KinematicChain {
+    pub joints: Vec<Joint>,
+    pub base: Iso,
+    pub tool: Iso,
+}
+
+impl KinematicChain {
+    pub fn dof(&self) -> usize {
+        self.joints
+            .iter()
+            .filter(|j| j.kind != JointKind::Fixed)
+            .count()
+    }
+
+    pub fn check_values(&self, q: &[f64]) -> PyResult<()> {
+        if q.len() != self.dof() {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} joint values but got {}",
+                self.dof(),
+                q.len()
+            )));
+        }
+        if !q.iter().all(|v| v.is_finite()) {
+            return Err(PyValueError::new_err("Joint values must be finite"));
+        }
+        Ok(())
+    }
+
+    /// The pose of each joint frame before it moves, which holds the joint's
+    /// axis, and of each link, for joint values `q`.
+    fn frames(&self, q: &[f64]) -> Vec<(Iso, Iso)> {
+        let mut values = q.iter();
+        let mut pose = self.base;
+        self.joints
+            .iter()
+            .map(|joint| {
+                let q = match joint.kind {
+                    JointKind::Fixed => 0.0,
+                    _ => *values.next().unwrap(),
+                };
+                let frame = pose * joint.origin;
+                pose = frame * joint.motion(q) * joint.tip;
+                (frame, pose)
+            })
+            .collect()
+    }
+
+    pub fn end_effector(&self, q: &[f64]) -> Iso {
+        let last = self.frames(q).last().map_or(self.base, |(_, link)| *link);
+        last * self.tool
+    }
+
+    /// The geometric Jacobian of the end effector in the base frame, with
+    /// the linear velocity in the first three rows and the angular velocity
+    /// in the last three.
+    pub fn jacobian_matrix(&self, q: &[f64]) -> na::Matrix6xX<f64> {
+        let frames = self.frames(q);
+        let end = frames.last().map_or(self.base, |(_, link)| *link) * self.tool;
+        let end = end.translation.vector;
+        let mut jacobian = na::Matrix6xX::zeros(self.dof());
+        let moving = self
+            .joints
+            .iter()
+            .zip(&frames)
+            .filter(|(joint, _)| joint.kind != JointKind::Fixed);
+        for (column, (joint, (frame, _))) in moving.enumerate() {
+            let axis = frame.rotation * joint.axis.into_inner();
+            let (linear, angular) = match joint.kind {
+                JointKind::Revolute => (axis.cross(&(end - frame.translation.vector)), axis),
+                _ => (axis, V3::zeros()),
+            };
+            jacobian
+                .fixed_view_mut::<3, 1>(0, column)
+                .copy_from(&linear);
+            jacobian
+                .fixed_view_mut::<3, 1>(3, column)
+                .copy_from(&angular);
+        }
+        jacobian
+    }
+}
+
+#[pymethods]
+impl KinematicChain {
+    /// Creates a chain from a list of Joints, ordered from the base, with
+    /// optional `base` and `tool` transforms at either end.
+    #[new]
+    #[pyo3(signature = (joints, base=None, tool=None))]
+    fn new(
+        joints: Vec<PyRef<Joint>>,
+        base: Option<&Isometry3>,
+        tool: Option<&Isometry3>,
+    ) -> KinematicChain {
+        KinematicChain {
+            joints: joints.iter().map(|j| (**j).clone()).collect(),
+            base: base.map_or(Iso::identity(), |b| b.0),
+            tool: tool.map_or(Iso::identity(), |t| t.0),
+        }
+    }
+
+    /// Creates a chain of joints from rows of Denavit-Hartenberg parameters
+    /// `(a, alpha, d, theta)`, see `Joint.dh`. `kinds` lists each joint's
+    /// kind and defaults to all revolute joints, and `limits` lists each
+    /// joint's `(lower, upper)` limits or None.
+    #[staticmethod]
+    #[pyo3(signature = (parameters, kinds=None, *, modified=false, base=None, tool=None, limits=None))]
+    fn from_dh(
+        parameters: Vec<(f64, f64, f64, f64)>,
+        kinds: Option<Vec<&str>>,
+        modified: bool,
+        base: Option<&Isometry3>,
+        tool: Option<&Isometry3>,
+        limits: Option<Vec<Option<(f64, f64)>>>,
+    ) -> PyResult<KinematicChain> {
+        let kinds = kinds.unwrap_or_else(|| vec!["revolute"; parameters.len()]);
+        if kinds.len() != parameters.len() {
+            return Err(PyValueError::new_err(format!(
+                "Got {} rows of parameters but {} joint kinds",
+                parameters.len(),
+                kinds.len()
+            )));
+        }
+        let limits = limits.unwrap_or_else(|| vec![None; parameters.len()]);
+        if limits.len() != parameters.len() {
+            return Err(PyValueError::new_err(format!(
+                "Got {} rows of parameters but {} joint limits",
+                parameters.len(),
+                limits.len()
+            )));
+        }
+        let joints = parameters
+            .into_iter()
+            .zip(kinds)
+            .zip(limits)
+            .map(|(((a, alpha, d, theta), kind), limits)| {
+                Joint::dh(a, alpha, d, theta, kind, modified, limits)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(KinematicChain {
+            joints,
+            base: base.map_or(Iso::identity(), |b| b.0),
+            tool: tool.map_or(Iso::identity(), |t| t.0),
+        })
+    }
+
+    #[getter]
+    fn get_joints(&self) -> Vec<Joint> {
+        self.joints.clone()
+    }
+
+    #[getter]
+    fn get_base(&self) -> Isometry3 {
+        Isometry3(self.base)
+    }
+
+    #[getter]
+    fn get_tool(&self) -> Isometry3 {
+        Isometry3(self.tool)
+    }
+
+    /// The number of joint values the chain takes, one per moving joint.
+    #[getter]
+    fn get_dof(&self) -> usize {
+        self.dof()
+    }
+
+    fn __len__(&self) -> usize {
+        self.joints.len()
+    }
+
+    /// Returns the pose of the end effector, including the tool transform,
+    /// for joint values `q`.
+    fn forward(&self, q: Vec<f64>) -> PyResult<Isometry3> {
+        self.check_values(&q)?;
+        Ok(Isometry3(self.end_effector(&q)))
+    }
+
+    /// Returns the pose of every joint's link frame in the base's
+    /// coordinates for joint values `q`, one per joint.
+    fn link_poses(&self, q: Vec<f64>) -> PyResult<Vec<Isometry3>> {
+        self.check_values(&q)?;
+        Ok(self
+            .frames(&q)
+            .into_iter()
+            .map(|(_, link)| Isometry3(link))
+            .collect())
+    }
+
+    /// Returns the end effector's 6 x dof geometric Jacobian for joint
+    /// values `q` as a list of rows. It maps joint velocities to the
+    /// end effector's linear velocity (the first three rows) and angular
+    /// velocity (the last three), both in the base's coordinates.
+    fn jacobian(&self, q: Vec<f64>) -> PyResult<Vec<Vec<f64>>> {
+        self.check_values(&q)?;
+        let jacobian = self.jacobian_matrix(&q);
+        Ok(jacobian
+            .row_iter()
+            .map(|row| row.iter().copied().collect())
+            .collect())
+    }
+
+    /// Solves numerically for joint values putting the end effector at
+    /// `target`, returning an `IkResult(q, pose, converged, iterations,
+    /// position_error, orientation_error)`.
+    ///
+    /// `target` is an Isometry3 to reach a full pose, or a Vector3 to reach
+    /// a position with any orientation. The search starts from `initial`,
+    /// which defaults to all zeros, and keeps every joint within its limits.
+    ///
+    /// `method` is `"dls"` for damped least squares, which steps by
+    /// `J^T (J J^T + damping^2 I)^-1 e`, or `"lm"` for Levenberg-Marquardt,
+    /// which starts from `damping` and adapts it so that every step reduces
+    /// the error. The orientation error is the rotation vector, twice the
+    /// UnitQuaternion log, of the rotation from the end effector's
+    /// orientation to the target's, and its norm is in radians.
+    ///
+    /// The search stops once the position error is within `tolerance` and
+    /// the orientation error within `angular_tolerance`, after
+    /// `max_iterations` steps, or when it stops making progress, e.g. on an
+    /// unreachable target. `converged` says whether the tolerances were met;
+    /// `orientation_error` is None for position targets.
+    #[pyo3(signature = (target, initial=None, *, method="dls", max_iterations=100, tolerance=1e-6, angular_tolerance=1e-6, damping=0.1))]
+    #[allow(clippy::too_many_arguments)]
+    fn inverse(
+        &self,
+        py: Python,
+        target: &PyAny,
+        initial: Option<Vec<f64>>,
+        method: &str,
+        max_iterations: usize,
+        tolerance: f64,
+        angular_tolerance: f64,
+        damping: f64,
+    ) -> PyResult<PyObject> {
+        let target = if let Ok(pose) = target.extract::<PyRef<Isometry3>>() {
+            ik::Target::Pose(pose.0)
+        } else if let Ok(position) = target.extract::<PyRef<Vector3>>() {
+            ik::Target::Position(position.0)
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "target must be an Isometry3 or a Vector3, not {}",
+                target.get_type().name().unwrap_or("?")
+            )));
+        };
+        let finite = match &target {
+            ik::Target::Pose(pose) => pose
+                .translation
+                .vector
+                .iter()
+                .chain(pose.rotation.coords.iter())
+                .all(|v| v.is_finite()),
+            ik::Target::Position(position) => position.iter().all(|v| v.is_finite()),
+        };
+        if !finite {
+            return Err(PyValueError::new_err("target must be finite"));
+        }
+        let method = match method {
+            "dls" => ik::Method::DampedLeastSquares,
+            "lm" => ik::Method::LevenbergMarquardt,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "method must be 'dls' or 'lm', not '{}'",
+                    method
+                )))
+            }
+        };
+        if !(tolerance >= 0.0 && angular_tolerance >= 0.0) {
+            return Err(PyValueError::new_err("Tolerances must be non-negative"));
+        }
+        // The damping is used squared, which mustn't underflow to zero
+        if !(damping > 0.0 && (damping * damping).is_normal()) {
+            return Err(PyValueError::new_err(
+                "damping must be positive, with a square that is a normal float",
+            ));
+        }
+        let initial = initial.unwrap_or_else(|| vec![0.0; self.dof()]);
+        self.check_values(&initial)?;
+        let options = ik::Options {
+            method,
+            max_iterations,
+            tolerance,
+            angular_tolerance,
+            damping,
+        };
+        let solution = py.allow_threads(|| ik::solve(self, &target, &initial, &options));
+        IK_RESULT.make(
+            py,
+            (
+                solution.q,
+                Isometry3(solution.pose),
+                solution.converged,
+                solution.iterations,
+                solution.position_error,
+                solution.orientation_error,
+            ),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "KinematicChain({} joints, {} dof)",
+            self.joints.len(),
+            self.dof()
+        )
+    }
+}
@@ -1,16 +1,110 @@
 use pyo3::prelude::*;
 
+mod aabb;
+mod bounding;
+mod camera;
+mod cloud;
+mod distortion;
+// pyo3 0.18's #[pymethods] nests impls inside the wrappers it generates for
+// the binary operators, which these modules define
+#[allow(non_local_definitions)]
+mod dualquat;
+mod epipolar;
+mod fit;
+mod frozen;
+mod handeye;
+mod hull;
+mod ik;
+mod intersect;
+#[allow(non_local_definitions)]
 mod iso;
+mod kdtree;
+mod kinematics;
+#[allow(non_local_definitions)]
+mod mat3;
+#[allow(non_local_definitions)]
 mod mat4;
+mod pcd;
+mod plane;
+mod ply;
+mod pnp;
+mod points;
+#[allow(non_local_definitions)]
 mod quat;
+mod ray;
+mod registration;
+mod results;
+mod segment;
+mod seq;
+mod shape;
+mod sphere;
+mod tolerance;
+mod triangle;
+#[allow(non_local_definitions)]
 mod vec3;
+#[allow(non_local_definitions)]
+mod vec4;
+mod xyz;
 
 #[pymodule]
+#[pyo3(name = "_deuterium")]
 /// A Python module wrapping the nalgebra crate to provide pythonic linear algebra
 fn deuterium(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<mat3::Matrix3>()?;
     m.add_class::<mat4::Matrix4>()?;
+    m.add(
+        "IllConditionedWarning",
+        _py.get_type::<mat4::IllConditionedWarning>(),
+    )?;
     m.add_class::<vec3::Vector3>()?;
+    m.add_class::<vec4::Vector4>()?;
     m.add_class::<iso::Isometry3>()?;
     m.add_class::<quat::UnitQuaternion>()?;
+    m.add_class::<dualquat::UnitDualQuaternion>()?;
+    m.add_class::<frozen::FrozenVector3>()?;
+    m.add_class::<frozen::FrozenUnitQuaternion>()?;
+    m.add_class::<frozen::FrozenIsometry3>()?;
+    m.add_class::<tolerance::ToleranceContext>()?;
+    m.add_class::<plane::Plane>()?;
+    m.add_class::<ray::Ray>()?;
+    m.add_class::<segment::LineSegment>()?;
+    m.add_class::<sphere::Sphere>()?;
+    m.add_class::<aabb::Aabb>()?;
+    m.add_class::<triangle::Triangle>()?;
+    m.add_class::<hull::ConvexHull>()?;
+    m.add_class::<kdtree::KdTree>()?;
+    m.add_class::<cloud::PointCloud>()?;
+    m.add_class::<camera::PinholeCamera>()?;
+    m.add_class::<distortion::BrownConrady>()?;
+    m.add_class::<distortion::KannalaBrandt>()?;
+    m.add_class::<distortion::DivisionModel>()?;
+    m.add_class::<kinematics::Joint>()?;
+    m.add_class::<kinematics::KinematicChain>()?;
+    m.add(
+        "PointCloudFormatError",
+        _py.get_type::<cloud::PointCloudFormatError>(),
+    )?;
+    m.add_function(wrap_pyfunction!(bounding::oriented_bounding_box, m)?)?;
+    m.add_function(wrap_pyfunction!(fit::fit_plane, m)?)?;
+    m.add_function(wrap_pyfunction!(fit::fit_line, m)?)?;
+    m.add_function(wrap_pyfunction!(fit::centroid, m)?)?;
+    m.add_function(wrap_pyfunction!(fit::principal_axes, m)?)?;
+    m.add_function(wrap_pyfunction!(fit::fit_plane_ransac, m)?)?;
+    m.add_function(wrap_pyfunction!(registration::align_points, m)?)?;
+    m.add_function(wrap_pyfunction!(registration::estimate_normals, m)?)?;
+    m.add_function(wrap_pyfunction!(registration::icp, m)?)?;
+    m.add_function(wrap_pyfunction!(pnp::solve_pnp, m)?)?;
+    m.add_function(wrap_pyfunction!(pnp::solve_pnp_ransac, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::essential_from_pose, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::poses_from_essential, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::pose_from_essential, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::fundamental_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::essential_from_fundamental, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::fundamental_from_essential, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::sampson_error, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::sampson_errors, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::triangulate, m)?)?;
+    m.add_function(wrap_pyfunction!(epipolar::triangulate_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(handeye::calibrate_hand_eye, m)?)?;
     Ok(())
 }
@@ -0,0 +1,303 @@
+use crate::quat::UnitQuaternion;
+use crate::results::NamedTuple;
+use crate::seq::{self, Selection};
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::{PyTuple, PyType};
+
+pub type Matrix3d = na::Matrix3<f64>;
+pub type Tuple3 = (f64, f64, f64);
+
+static SVD: NamedTuple = NamedTuple::new("Svd", &["u", "singular_values", "v_t"]);
+
+/// A 3x3 matrix, such as a rotation matrix, a camera's intrinsic matrix or
+/// the essential and fundamental matrices of two-view geometry.
+///
+/// Matrix3 supports the same indexing as Matrix4, but is a smaller class for
+/// the 3x3 matrices that don't describe a transform.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct Matrix3(pub Matrix3d);
+
+impl Matrix3 {
+    /// Resolves a `m[...]` key into row and column selections. A single index
+    /// or slice selects whole rows.
+    fn select(arg: &PyAny) -> PyResult<(Selection, Selection)> {
+        if let Ok(pair) = arg.downcast::<PyTuple>() {
+            if pair.len() != 2 {
+                return Err(PyIndexError::new_err(format!(
+                    "Matrix3 takes 2 indices but {} were given",
+                    pair.len()
+                )));
+            }
+            return Ok((
+                Selection::new(pair.get_item(0)?, 3)?,
+                Selection::new(pair.get_item(1)?, 3)?,
+            ));
+        }
+        Ok((Selection::new(arg, 3)?, Selection::Slice((0..3).collect())))
+    }
+
+    /// Extracts a 3x3 nested sequence, where the outer sequence holds either
+    /// the rows or the columns of the matrix as described by `kind`.
+    fn extract_nested(values: &PyAny, kind: &str) -> PyResult<[[f64; 3]; 3]> {
+        let outer: Vec<&PyAny> = values.extract()?;
+        if outer.len() != 3 {
+            return Err(PyValueError::new_err(format!(
+                "Matrix3 expects 3 {} but got {}",
+                kind,
+                outer.len()
+            )));
+        }
+        let mut nested = [[0.0; 3]; 3];
+        for (i, inner) in outer.into_iter().enumerate() {
+            let inner: Vec<f64> = inner.extract()?;
+            if inner.len() != 3 {
+                return Err(PyValueError::new_err(format!(
+                    "Matrix3 expects 3 values in each of its {} but {} {} has {}",
+                    kind,
+                    &kind[..kind.len() - 1],
+                    i,
+                    inner.len()
+                )));
+            }
+            nested[i].copy_from_slice(&inner);
+        }
+        Ok(nested)
+    }
+
+    pub fn try_svd(m: &Matrix3d) -> PyResult<na::SVD<f64, na::U3, na::U3>> {
+        if !m.iter().all(|v| v.is_finite()) {
+            return Err(PyValueError::new_err("Matrix3 contains non-finite values"));
+        }
+        na::SVD::try_new(*m, true, true, f64::EPSILON, 1000)
+            .ok_or_else(|| PyValueError::new_err("SVD did not converge"))
+    }
+}
+
+#[pymethods]
+impl Matrix3 {
+    /// Creates a matrix from a sequence of 3 rows of 3 values, or the identity
+    /// matrix if no rows are given. This is the inverse of `Matrix3.list()`.
+    #[new]
+    fn new(rows: Option<&PyAny>) -> PyResult<Matrix3> {
+        match rows {
+            Some(rows) => Matrix3::from_rows(rows),
+            None => Ok(Matrix3(Matrix3d::identity())),
+        }
+    }
+
+    #[staticmethod]
+    fn identity() -> Matrix3 {
+        Matrix3(Matrix3d::identity())
+    }
+
+    #[staticmethod]
+    fn from_rows(rows: &PyAny) -> PyResult<Matrix3> {
+        let rows = Matrix3::extract_nested(rows, "rows")?;
+        Ok(Matrix3(Matrix3d::from_fn(|r, c| rows[r][c])))
+    }
+
+    #[staticmethod]
+    fn from_columns(columns: &PyAny) -> PyResult<Matrix3> {
+        let columns = Matrix3::extract_nested(columns, "columns")?;
+        Ok(Matrix3(Matrix3d::from_fn(|r, c| columns[c][r])))
+    }
+
+    /// Returns the rotation matrix of a UnitQuaternion.
+    #[staticmethod]
+    fn from_rotation(rotation: &UnitQuaternion) -> Matrix3 {
+        Matrix3(rotation.0.to_rotation_matrix().into_inner())
+    }
+
+    /// Returns the skew-symmetric matrix `[v]x` for which `[v]x * w` is the
+    /// cross product `v.cross(w)`.
+    #[staticmethod]
+    fn cross_matrix(v: &Vector3) -> Matrix3 {
+        Matrix3(v.0.cross_matrix())
+    }
+
+    /// Indexes the matrix like a 2D array, as `Matrix4.__getitem__` does.
+    fn __getitem__(&self, py: Python, arg: &PyAny) -> PyResult<PyObject> {
+        let m = &self.0;
+        Ok(match Matrix3::select(arg)? {
+            (Selection::Index(r), Selection::Index(c)) => m[(r, c)].to_object(py),
+            (Selection::Index(r), Selection::Slice(cs)) => {
+                PyTuple::new(py, cs.iter().map(|c| m[(r, *c)])).into()
+            }
+            (Selection::Slice(rs), Selection::Index(c)) => {
+                PyTuple::new(py, rs.iter().map(|r| m[(*r, c)])).into()
+            }
+            (Selection::Slice(rs), Selection::Slice(cs)) => PyTuple::new(
+                py,
+                rs.iter()
+                    .map(|r| PyTuple::new(py, cs.iter().map(|c| m[(*r, *c)]))),
+            )
+            .into(),
+        })
+    }
+
+    fn __iter__(&self, py: Python) -> PyResult<PyObject> {
+        let rows: PyObject = self.tuple().into_py(py);
+        Ok(rows.as_ref(py).iter()?.into())
+    }
+
+    #[staticmethod]
+    fn __len__() -> usize {
+        3
+    }
+
+    /// Returns row `i` as a tuple.
+    fn row(&self, i: isize) -> PyResult<Tuple3> {
+        let r = seq::normalize_index(i, 3)?;
+        Ok((self.0[(r, 0)], self.0[(r, 1)], self.0[(r, 2)]))
+    }
+
+    /// Returns column `j` as a tuple.
+    fn column(&self, j: isize) -> PyResult<Tuple3> {
+        let c = seq::normalize_index(j, 3)?;
+        Ok((self.0[(0, c)], self.0[(1, c)], self.0[(2, c)]))
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Matrix3, op: CompareOp) -> Py<PyAny> {
+        match op {
+            CompareOp::Eq => (self.0 == other.0).into_py(py),
+            CompareOp::Ne => (self.0 != other.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (arg, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        arg: &Matrix3,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &arg.0))
+    }
+
+    fn __mul__(&self, py: Python, arg: &PyAny) -> PyResult<PyObject> {
+        if let Ok(mat) = arg.extract::<PyRef<Matrix3>>() {
+            return Ok(Py::new(py, Matrix3(self.0 * mat.0))?.to_object(py));
+        }
+        if let Ok(vec) = arg.extract::<PyRef<Vector3>>() {
+            return Ok(Py::new(py, Vector3(self.0 * vec.0))?.to_object(py));
+        }
+        if let Ok(scalar) = arg.extract::<f64>() {
+            return Ok(Py::new(py, Matrix3(self.0 * scalar))?.to_object(py));
+        }
+        Ok(py.NotImplemented())
+    }
+
+    fn __rmul__(&self, scalar: f64) -> Matrix3 {
+        Matrix3(self.0 * scalar)
+    }
+
+    fn __truediv__(&self, scalar: f64) -> Matrix3 {
+        Matrix3(self.0 / scalar)
+    }
+
+    fn __add__(&self, other: &Matrix3) -> Matrix3 {
+        Matrix3(self.0 + other.0)
+    }
+
+    fn __sub__(&self, other: &Matrix3) -> Matrix3 {
+        Matrix3(self.0 - other.0)
+    }
+
+    fn __neg__(&self) -> Matrix3 {
+        Matrix3(-self.0)
+    }
+
+    /// Returns the sum of the diagonal elements.
+    fn trace(&self) -> f64 {
+        self.0.trace()
+    }
+
+    /// Returns the Frobenius norm, the square root of the sum of the squares
+    /// of every element.
+    fn norm(&self) -> f64 {
+        self.0.norm()
+    }
+
+    fn determinant(&self) -> f64 {
+        self.0.determinant()
+    }
+
+    fn transposed(&self) -> Matrix3 {
+        Matrix3(self.0.transpose())
+    }
+
+    /// Returns the inverse of this matrix, raising a ValueError if it is singular.
+    fn inverse(&self) -> PyResult<Matrix3> {
+        match self.0.try_inverse() {
+            Some(inverse) if inverse.iter().all(|v| v.is_finite()) => Ok(Matrix3(inverse)),
+            _ => Err(PyValueError::new_err("Matrix3 is singular")),
+        }
+    }
+
+    /// Returns the number of singular values greater than `eps`, which
+    /// defaults to `4 * machine epsilon * largest singular value`.
+    fn rank(&self, eps: Option<f64>) -> PyResult<usize> {
+        let svd = Matrix3::try_svd(&self.0)?;
+        let eps = match eps {
+            Some(eps) if eps.is_nan() || eps < 0.0 => {
+                return Err(PyValueError::new_err("eps must be a non-negative number"))
+            }
+            Some(eps) => eps,
+            None => 4.0 * f64::EPSILON * svd.singular_values[0],
+        };
+        Ok(svd.singular_values.iter().filter(|s| **s > eps).count())
+    }
+
+    /// Computes the singular value decomposition `m = u * diag(singular_values) * v_t`,
+    /// returned as a `(u, singular_values, v_t)` named tuple. The singular values
+    /// are sorted in descending order.
+    fn svd(&self, py: Python) -> PyResult<PyObject> {
+        let svd = Matrix3::try_svd(&self.0)?;
+        SVD.make(
+            py,
+            (
+                Matrix3(svd.u.unwrap()),
+                Vector3(svd.singular_values),
+                Matrix3(svd.v_t.unwrap()),
+            ),
+        )
+    }
+
+    fn tuple(&self) -> (Tuple3, Tuple3, Tuple3) {
+        let row = |r: usize| (self.0[(r, 0)], self.0[(r, 1)], self.0[(r, 2)]);
+        (row(0), row(1), row(2))
+    }
+
+    fn list(&self) -> [[f64; 3]; 3] {
+        let row = |r: usize| [self.0[(r, 0)], self.0[(r, 1)], self.0[(r, 2)]];
+        [row(0), row(1), row(2)]
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, ([[f64; 3]; 3],)) {
+        (py.get_type::<Matrix3>().into(), (self.list(),))
+    }
+
+    fn __repr__(&self) -> String {
+        let cells = self.0.map(|v| format!("{:?}", v));
+        let widths: Vec<usize> = (0..3)
+            .map(|c| (0..3).map(|r| cells[(r, c)].len()).max().unwrap())
+            .collect();
+        let rows: Vec<String> = (0..3)
+            .map(|r| {
+                (0..3)
+                    .map(|c| format!("{:width$}", cells[(r, c)], width = widths[c]))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        format!("Matrix3<{}>", rows.join(",\n        "))
+    }
+}
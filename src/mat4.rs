@@ -1,24 +1,247 @@
+use crate::iso::Isometry3;
+use crate::quat::UnitQuaternion;
+use crate::results::NamedTuple;
+use crate::seq::{self, Selection};
+use crate::shape;
+use crate::tolerance::Tolerance;
 use crate::vec3::Vector3;
-use approx::AbsDiffEq;
+use crate::vec4::Vector4;
 use nalgebra as na;
 use nalgebra::SMatrix;
-use pyo3::exceptions::PyIndexError;
+use pyo3::exceptions::{PyIndexError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::pyclass::CompareOp;
 use pyo3::types::PyTuple;
 
 pub type Matrix4d = SMatrix<f64, 4, 4>;
+pub type Tuple4 = (f64, f64, f64, f64);
+
+// pyo3 0.18's create_exception! checks a cfg which newer compilers don't know.
+#[allow(unexpected_cfgs)]
+mod warnings {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyRuntimeWarning;
+
+    create_exception!(
+        deuterium,
+        IllConditionedWarning,
+        PyRuntimeWarning,
+        "Warns that a matrix is so badly conditioned that results computed from it may be inaccurate."
+    );
+}
+pub use warnings::IllConditionedWarning;
+
+/// Condition numbers above this lose more than about 12 significant digits.
+const ILL_CONDITIONED: f64 = 1e12;
+
+fn singular() -> PyErr {
+    PyValueError::new_err("Matrix4 is singular")
+}
+
+static SVD: NamedTuple = NamedTuple::new("Svd", &["u", "singular_values", "v_t"]);
+static SYMMETRIC_EIGEN: NamedTuple =
+    NamedTuple::new("SymmetricEigen", &["eigenvalues", "eigenvectors"]);
+static QR: NamedTuple = NamedTuple::new("Qr", &["q", "r"]);
+static LU: NamedTuple = NamedTuple::new("Lu", &["p", "l", "u"]);
+static POLAR: NamedTuple = NamedTuple::new("Polar", &["rotation", "stretch"]);
 
 #[pyclass]
 pub struct Matrix4(pub Matrix4d);
 
+impl Matrix4 {
+    /// Resolves a `m[...]` key into row and column selections. A single index
+    /// or slice selects whole rows.
+    fn select(arg: &PyAny) -> PyResult<(Selection, Selection)> {
+        if let Ok(pair) = arg.downcast::<PyTuple>() {
+            if pair.len() != 2 {
+                return Err(PyIndexError::new_err(format!(
+                    "Matrix4 takes 2 indices but {} were given",
+                    pair.len()
+                )));
+            }
+            return Ok((
+                Selection::new(pair.get_item(0)?, 4)?,
+                Selection::new(pair.get_item(1)?, 4)?,
+            ));
+        }
+        Ok((Selection::new(arg, 4)?, Selection::Slice((0..4).collect())))
+    }
+
+    /// Extracts a 4x4 nested sequence, where the outer sequence holds either
+    /// the rows or the columns of the matrix as described by `kind`.
+    fn extract_nested(values: &PyAny, kind: &str) -> PyResult<[[f64; 4]; 4]> {
+        let outer: Vec<&PyAny> = values.extract()?;
+        if outer.len() != 4 {
+            return Err(PyValueError::new_err(format!(
+                "Matrix4 expects 4 {} but got {}",
+                kind,
+                outer.len()
+            )));
+        }
+        let mut nested = [[0.0; 4]; 4];
+        for (i, inner) in outer.into_iter().enumerate() {
+            let inner: Vec<f64> = inner.extract()?;
+            if inner.len() != 4 {
+                return Err(PyValueError::new_err(format!(
+                    "Matrix4 expects 4 values in each of its {} but {} {} has {}",
+                    kind,
+                    &kind[..kind.len() - 1],
+                    i,
+                    inner.len()
+                )));
+            }
+            nested[i].copy_from_slice(&inner);
+        }
+        Ok(nested)
+    }
+
+    fn check_finite(&self) -> PyResult<()> {
+        if self.0.iter().all(|v| v.is_finite()) {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err("Matrix4 contains non-finite values"))
+        }
+    }
+
+    fn check_symmetric(&self) -> PyResult<()> {
+        if Tolerance::current().eq(&self.0, &self.0.transpose()) {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err("Matrix4 is not symmetric"))
+        }
+    }
+
+    pub fn try_svd(m: &Matrix4d) -> PyResult<na::SVD<f64, na::U4, na::U4>> {
+        if !m.iter().all(|v| v.is_finite()) {
+            return Err(PyValueError::new_err("Matrix4 contains non-finite values"));
+        }
+        na::SVD::try_new(*m, true, true, f64::EPSILON, 1000)
+            .ok_or_else(|| PyValueError::new_err("SVD did not converge"))
+    }
+
+    fn singular_value_eps(svd: &na::SVD<f64, na::U4, na::U4>, eps: Option<f64>) -> PyResult<f64> {
+        match eps {
+            Some(eps) if eps.is_nan() || eps < 0.0 => {
+                Err(PyValueError::new_err("eps must be a non-negative number"))
+            }
+            Some(eps) => Ok(eps),
+            None => Ok(4.0 * f64::EPSILON * svd.singular_values[0]),
+        }
+    }
+
+    /// Computes the LU decomposition and inverse of this matrix, raising if it
+    /// is singular and warning if it is badly conditioned.
+    ///
+    /// The 1-norm condition number `|m| * |m^-1|` is used as it is cheap to
+    /// compute once the inverse is known.
+    fn checked_lu(&self, py: Python) -> PyResult<(na::LU<f64, na::U4, na::U4>, Matrix4d)> {
+        self.check_finite()?;
+        let lu = self.0.lu();
+        let inverse = lu.try_inverse().ok_or_else(singular)?;
+        let norm_1 = |m: &Matrix4d| m.abs().row_sum().max();
+        let condition = norm_1(&self.0) * norm_1(&inverse);
+        if !condition.is_finite() {
+            return Err(singular());
+        }
+        if condition > ILL_CONDITIONED {
+            PyErr::warn(
+                py,
+                py.get_type::<IllConditionedWarning>(),
+                &format!(
+                    "Matrix4 is badly conditioned (condition number {:e}), results may be inaccurate",
+                    condition
+                ),
+                1,
+            )?;
+        }
+        Ok((lu, inverse))
+    }
+
+    fn extract_values(values: &PyAny, len: usize) -> PyResult<Vec<f64>> {
+        let values: Vec<f64> = values.extract()?;
+        if values.len() != len {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} values but got {}",
+                len,
+                values.len()
+            )));
+        }
+        Ok(values)
+    }
+}
+
 #[pymethods]
+#[allow(clippy::needless_return, clippy::unused_unit, clippy::type_complexity)]
 impl Matrix4 {
+    /// Creates a matrix from a sequence of 4 rows of 4 values, or the identity
+    /// matrix if no rows are given. This is the inverse of `Matrix4.list()`.
+    #[new]
+    fn new(rows: Option<&PyAny>) -> PyResult<Matrix4> {
+        match rows {
+            Some(rows) => Matrix4::from_rows(rows),
+            None => Ok(Matrix4(Matrix4d::identity())),
+        }
+    }
+
     #[staticmethod]
     fn identity() -> Matrix4 {
         return Matrix4(Matrix4d::identity());
     }
 
+    #[staticmethod]
+    fn from_rows(rows: &PyAny) -> PyResult<Matrix4> {
+        let rows = Matrix4::extract_nested(rows, "rows")?;
+        Ok(Matrix4(Matrix4d::from_fn(|r, c| rows[r][c])))
+    }
+
+    #[staticmethod]
+    fn from_columns(columns: &PyAny) -> PyResult<Matrix4> {
+        let columns = Matrix4::extract_nested(columns, "columns")?;
+        Ok(Matrix4(Matrix4d::from_fn(|r, c| columns[c][r])))
+    }
+
+    /// Creates a matrix from 16 values, in row-major order by default or in
+    /// column-major order if `order` is `"column"`.
+    #[staticmethod]
+    #[pyo3(signature = (values, order="row"))]
+    fn from_flat(values: &PyAny, order: &str) -> PyResult<Matrix4> {
+        let values = Matrix4::extract_values(values, 16)?;
+        match order {
+            "row" => Ok(Matrix4(Matrix4d::from_row_slice(&values))),
+            "column" => Ok(Matrix4(Matrix4d::from_column_slice(&values))),
+            _ => Err(PyValueError::new_err(format!(
+                "order must be 'row' or 'column', not '{}'",
+                order
+            ))),
+        }
+    }
+
+    #[staticmethod]
+    fn from_isometry(iso: &Isometry3) -> Matrix4 {
+        Matrix4(iso.0.to_homogeneous())
+    }
+
+    #[staticmethod]
+    fn from_rotation(rotation: &UnitQuaternion) -> Matrix4 {
+        Matrix4(rotation.0.to_homogeneous())
+    }
+
+    /// Creates a rotation by `angle` radians about `axis`, which needn't be
+    /// normalized but must be finite and non-zero.
+    #[staticmethod]
+    fn from_axis_angle(axis: &Vector3, angle: f64) -> PyResult<Matrix4> {
+        if !axis.0.iter().all(|v| v.is_finite()) {
+            return Err(PyValueError::new_err("Rotation axis must be finite"));
+        }
+        let axis = shape::unit(axis.0, "Rotation axis")?;
+        Ok(Matrix4(na::Rotation3::from_axis_angle(&axis, angle).to_homogeneous()))
+    }
+
+    #[staticmethod]
+    fn from_scale(scale: &Vector3) -> Matrix4 {
+        Matrix4(Matrix4d::new_nonuniform_scaling(&scale.0))
+    }
+
     #[staticmethod]
     fn from_translation(v: &Vector3) -> Matrix4 {
         let mut m = Matrix4d::identity();
@@ -28,33 +251,109 @@ impl Matrix4 {
         return Matrix4(m);
     }
 
-    fn __getitem__(&self, py: Python, arg: &PyAny) -> Result<Py<PyAny>, PyErr> {
-        let idx: Result<(isize, isize), PyErr> = arg.extract();
-        match idx {
-            Err(_) => {
-                let i: Result<isize, PyErr> = arg.extract();
-                match i {
-                    Ok(i_int) => {
-                        if i_int > 3 || i_int < 0 {
-                            Err(PyIndexError::new_err(i_int))?;
-                        }
-                        Ok(PyTuple::new(py, self.0.row(i_int as usize).iter()).into())
-                    }
-                    Err(e) => Err(e),
+    /// Indexes the matrix like a 2D array:
+    ///
+    /// - `m[i]` returns row `i` as a tuple,
+    /// - `m[i, j]` returns a single element,
+    /// - `m[:, j]` returns column `j` as a tuple,
+    /// - slices in either position return tuples, or tuples of row tuples.
+    ///
+    /// Negative indices count back from the end, as with Python sequences.
+    fn __getitem__(&self, py: Python, arg: &PyAny) -> PyResult<PyObject> {
+        let m = &self.0;
+        Ok(match Matrix4::select(arg)? {
+            (Selection::Index(r), Selection::Index(c)) => m[(r, c)].to_object(py),
+            (Selection::Index(r), Selection::Slice(cs)) => {
+                PyTuple::new(py, cs.iter().map(|c| m[(r, *c)])).into()
+            }
+            (Selection::Slice(rs), Selection::Index(c)) => {
+                PyTuple::new(py, rs.iter().map(|r| m[(*r, c)])).into()
+            }
+            (Selection::Slice(rs), Selection::Slice(cs)) => PyTuple::new(
+                py,
+                rs.iter()
+                    .map(|r| PyTuple::new(py, cs.iter().map(|c| m[(*r, *c)]))),
+            )
+            .into(),
+        })
+    }
+
+    /// Sets elements using the same indexing as `__getitem__`. Rows, columns
+    /// and slices are set from sequences of exactly the selected shape.
+    fn __setitem__(&mut self, arg: &PyAny, value: &PyAny) -> PyResult<()> {
+        match Matrix4::select(arg)? {
+            (Selection::Index(r), Selection::Index(c)) => {
+                self.0[(r, c)] = value.extract()?;
+            }
+            (Selection::Index(r), Selection::Slice(cs)) => {
+                let values = Matrix4::extract_values(value, cs.len())?;
+                for (c, v) in cs.into_iter().zip(values) {
+                    self.0[(r, c)] = v;
                 }
             }
-            Ok(pair) => {
-                if pair.0 < 0 || pair.1 < 0 || pair.0 > 3 || pair.1 > 3 {
-                    Err(PyIndexError::new_err(pair))
-                } else {
-                    Ok(self.0[(pair.0 as usize, pair.1 as usize)].to_object(py))
+            (Selection::Slice(rs), Selection::Index(c)) => {
+                let values = Matrix4::extract_values(value, rs.len())?;
+                for (r, v) in rs.into_iter().zip(values) {
+                    self.0[(r, c)] = v;
+                }
+            }
+            (Selection::Slice(rs), Selection::Slice(cs)) => {
+                let rows: Vec<&PyAny> = value.extract()?;
+                if rows.len() != rs.len() {
+                    return Err(PyValueError::new_err(format!(
+                        "Expected {} rows but got {}",
+                        rs.len(),
+                        rows.len()
+                    )));
+                }
+                // Validate every row before modifying the matrix
+                let rows = rows
+                    .into_iter()
+                    .map(|row| Matrix4::extract_values(row, cs.len()))
+                    .collect::<PyResult<Vec<_>>>()?;
+                for (r, row) in rs.into_iter().zip(rows) {
+                    for (c, v) in cs.iter().zip(row) {
+                        self.0[(r, *c)] = v;
+                    }
                 }
             }
         }
+        Ok(())
     }
 
-    fn __setitem__(&mut self, idx: (usize, usize), value: f64) -> () {
-        self.0[idx] = value;
+    fn __iter__(&self, py: Python) -> PyResult<PyObject> {
+        let rows: PyObject = self.tuple().into_py(py);
+        Ok(rows.as_ref(py).iter()?.into())
+    }
+
+    /// Returns row `i` as a tuple.
+    fn row(&self, i: isize) -> PyResult<Tuple4> {
+        let r = seq::normalize_index(i, 4)?;
+        Ok((self.0[(r, 0)], self.0[(r, 1)], self.0[(r, 2)], self.0[(r, 3)]))
+    }
+
+    /// Returns column `j` as a tuple.
+    fn column(&self, j: isize) -> PyResult<Tuple4> {
+        let c = seq::normalize_index(j, 4)?;
+        Ok((self.0[(0, c)], self.0[(1, c)], self.0[(2, c)], self.0[(3, c)]))
+    }
+
+    /// Sets row `i` from a sequence of four values.
+    fn set_row(&mut self, i: isize, values: &PyAny) -> PyResult<()> {
+        let r = seq::normalize_index(i, 4)?;
+        for (c, v) in Matrix4::extract_values(values, 4)?.into_iter().enumerate() {
+            self.0[(r, c)] = v;
+        }
+        Ok(())
+    }
+
+    /// Sets column `j` from a sequence of four values.
+    fn set_column(&mut self, j: isize, values: &PyAny) -> PyResult<()> {
+        let c = seq::normalize_index(j, 4)?;
+        for (r, v) in Matrix4::extract_values(values, 4)?.into_iter().enumerate() {
+            self.0[(r, c)] = v;
+        }
+        Ok(())
     }
 
     fn __richcmp__(&self, py: Python, other: &Matrix4, op: CompareOp) -> Py<PyAny> {
@@ -65,8 +364,15 @@ impl Matrix4 {
         }
     }
 
-    fn approx_equals(&self, arg: &Matrix4) -> bool {
-        self.0.abs_diff_eq(&arg.0, 1e-08)
+    #[pyo3(signature = (arg, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        arg: &Matrix4,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &arg.0))
     }
 
     fn __mul__(&self, py: Python, arg: &PyAny) -> PyResult<PyObject> {
@@ -78,11 +384,113 @@ impl Matrix4 {
         if let Ok(vec) = vecr {
             return Ok(Py::new(py, Vector3::from_4(&(self.0 * vec.as_4())))?.to_object(py));
         }
+        let vec4r: PyResult<PyRef<Vector4>> = arg.extract();
+        if let Ok(vec) = vec4r {
+            return Ok(Py::new(py, Vector4(self.0 * vec.0))?.to_object(py));
+        }
+        let scalarr: PyResult<f64> = arg.extract();
+        if let Ok(scalar) = scalarr {
+            return Ok(Py::new(py, Matrix4(self.0 * scalar))?.to_object(py));
+        }
         Ok(py.NotImplemented())
     }
 
-    fn __imul__(&mut self, arg: &Matrix4) -> () {
-        self.0 = self.0 * arg.0;
+    fn __rmul__(&self, scalar: f64) -> Matrix4 {
+        Matrix4(self.0 * scalar)
+    }
+
+    fn __imul__(slf: &PyCell<Self>, arg: &PyAny) -> PyResult<()> {
+        // `m *= m` passes the matrix being modified as the argument too, so
+        // read it before borrowing self mutably
+        let matr: PyResult<na::Matrix4<f64>> = arg.extract::<PyRef<Matrix4>>().map(|m| m.0);
+        if let Ok(mat) = matr {
+            slf.borrow_mut().0 *= mat;
+            return Ok(());
+        }
+        let scalarr: PyResult<f64> = arg.extract();
+        if let Ok(scalar) = scalarr {
+            slf.borrow_mut().0 *= scalar;
+            return Ok(());
+        }
+        Err(PyTypeError::new_err(format!(
+            "Cannot multiply a Matrix4 in-place by {}",
+            arg.get_type().name().unwrap_or("?")
+        )))
+    }
+
+    fn __truediv__(&self, scalar: f64) -> Matrix4 {
+        Matrix4(self.0 / scalar)
+    }
+
+    fn __itruediv__(&mut self, scalar: f64) {
+        self.0 /= scalar;
+    }
+
+    fn __add__(&self, other: &Matrix4) -> Matrix4 {
+        Matrix4(self.0 + other.0)
+    }
+
+    fn __iadd__(&mut self, other: &Matrix4) {
+        self.0 += other.0;
+    }
+
+    fn __sub__(&self, other: &Matrix4) -> Matrix4 {
+        Matrix4(self.0 - other.0)
+    }
+
+    fn __isub__(&mut self, other: &Matrix4) {
+        self.0 -= other.0;
+    }
+
+    fn __neg__(&self) -> Matrix4 {
+        Matrix4(-self.0)
+    }
+
+    /// Returns a matrix with the absolute value of every element.
+    fn __abs__(&self) -> Matrix4 {
+        Matrix4(self.0.abs())
+    }
+
+    /// Returns a matrix with the absolute value of every element.
+    fn abs(&self) -> Matrix4 {
+        Matrix4(self.0.abs())
+    }
+
+    /// Returns the element-wise (Hadamard) product of this matrix and `other`.
+    fn component_mul(&self, other: &Matrix4) -> Matrix4 {
+        Matrix4(self.0.component_mul(&other.0))
+    }
+
+    /// Returns the sum of the diagonal elements.
+    fn trace(&self) -> f64 {
+        self.0.trace()
+    }
+
+    /// Returns the Frobenius norm, the square root of the sum of the squares
+    /// of every element.
+    fn norm(&self) -> f64 {
+        self.0.norm()
+    }
+
+    fn norm_squared(&self) -> f64 {
+        self.0.norm_squared()
+    }
+
+    /// Returns the largest element.
+    fn max(&self) -> f64 {
+        self.0.max()
+    }
+
+    /// Returns the smallest element.
+    fn min(&self) -> f64 {
+        self.0.min()
+    }
+
+    /// Returns true if every element is within `tol` of the identity matrix.
+    /// `tol` defaults to the current `abs_tol`, see `deuterium.tolerance`.
+    fn is_identity(&self, tol: Option<f64>) -> bool {
+        self.0
+            .is_identity(tol.unwrap_or_else(|| Tolerance::current().abs_tol))
     }
 
     #[staticmethod]
@@ -94,16 +502,202 @@ impl Matrix4 {
         self.0 = arg.0 * self.0;
     }
 
-    // fn invert(&mut self) -> PyResult<()> {
-    //     // TODO: Should this Matrix be an nalgebra Projective?
-    //     self.0 = self.0.pseudo_inverse(0.00001)?;
-    //     Ok(());
-    // }
+    /// Returns the inverse of this matrix, raising a ValueError if it is singular.
+    ///
+    /// Emits an IllConditionedWarning if the matrix is badly conditioned.
+    fn inverse(&self, py: Python) -> PyResult<Matrix4> {
+        let (_, inverse) = self.checked_lu(py)?;
+        Ok(Matrix4(inverse))
+    }
+
+    /// Performs `inverse()` in-place.
+    fn invert(&mut self, py: Python) -> PyResult<()> {
+        let (_, inverse) = self.checked_lu(py)?;
+        self.0 = inverse;
+        Ok(())
+    }
+
+    /// Solves `self * x = b` for `x`, where `b` is a Vector4 or a Matrix4, using
+    /// an LU decomposition. Raises a ValueError if the matrix is singular.
+    ///
+    /// Emits an IllConditionedWarning if the matrix is badly conditioned, as
+    /// the solution may then be inaccurate.
+    fn solve(&self, py: Python, b: &PyAny) -> PyResult<PyObject> {
+        let (lu, _) = self.checked_lu(py)?;
+        let vecr: PyResult<PyRef<Vector4>> = b.extract();
+        if let Ok(vec) = vecr {
+            let x = lu.solve(&vec.0).ok_or_else(singular)?;
+            return Ok(Py::new(py, Vector4(x))?.to_object(py));
+        }
+        let matr: PyResult<PyRef<Matrix4>> = b.extract();
+        if let Ok(mat) = matr {
+            let x = lu.solve(&mat.0).ok_or_else(singular)?;
+            return Ok(Py::new(py, Matrix4(x))?.to_object(py));
+        }
+        Err(PyTypeError::new_err(format!(
+            "Cannot solve a Matrix4 system for {}",
+            b.get_type().name().unwrap_or("?")
+        )))
+    }
+
+    /// Returns the minimum norm least-squares solution `x` of `self * x = b`,
+    /// where `b` is a Vector4 or a Matrix4. Unlike `solve` this also works for
+    /// singular matrices. Singular values below `eps` are treated as zero, see
+    /// `pseudo_inverse`.
+    fn solve_least_squares(&self, py: Python, b: &PyAny, eps: Option<f64>) -> PyResult<PyObject> {
+        let pinv = self.pseudo_inverse(eps)?.0;
+        let vecr: PyResult<PyRef<Vector4>> = b.extract();
+        if let Ok(vec) = vecr {
+            return Ok(Py::new(py, Vector4(pinv * vec.0))?.to_object(py));
+        }
+        let matr: PyResult<PyRef<Matrix4>> = b.extract();
+        if let Ok(mat) = matr {
+            return Ok(Py::new(py, Matrix4(pinv * mat.0))?.to_object(py));
+        }
+        Err(PyTypeError::new_err(format!(
+            "Cannot solve a Matrix4 system for {}",
+            b.get_type().name().unwrap_or("?")
+        )))
+    }
+
+    /// Returns the Moore-Penrose pseudo-inverse of this matrix, computed with
+    /// an SVD. Singular values below `eps` are treated as zero. `eps` defaults
+    /// to `4 * machine epsilon * largest singular value`, the tolerance
+    /// `numpy.linalg.matrix_rank` uses. Note that `numpy.linalg.pinv` instead
+    /// defaults to `1e-15 * largest singular value`.
+    fn pseudo_inverse(&self, eps: Option<f64>) -> PyResult<Matrix4> {
+        let svd = Matrix4::try_svd(&self.0)?;
+        let eps = Matrix4::singular_value_eps(&svd, eps)?;
+        let (u, v_t) = (svd.u.unwrap(), svd.v_t.unwrap());
+        let inverse_values = svd
+            .singular_values
+            .map(|s| if s > eps { 1.0 / s } else { 0.0 });
+        Ok(Matrix4(
+            v_t.transpose() * Matrix4d::from_diagonal(&inverse_values) * u.transpose(),
+        ))
+    }
+
+    /// Returns the number of singular values greater than `eps`, which
+    /// defaults as in `pseudo_inverse`.
+    fn rank(&self, eps: Option<f64>) -> PyResult<usize> {
+        let svd = Matrix4::try_svd(&self.0)?;
+        let eps = Matrix4::singular_value_eps(&svd, eps)?;
+        Ok(svd.singular_values.iter().filter(|s| **s > eps).count())
+    }
+
+    /// Returns the 2-norm condition number, the ratio of the largest to the
+    /// smallest singular value. This is infinite for singular matrices.
+    fn condition_number(&self) -> PyResult<f64> {
+        let svd = Matrix4::try_svd(&self.0)?;
+        let s = &svd.singular_values;
+        Ok(if s[3] == 0.0 { f64::INFINITY } else { s[0] / s[3] })
+    }
 
     fn transposed(&self) -> Matrix4 {
         Matrix4(self.0.transpose())
     }
 
+    /// Computes the singular value decomposition `m = u * diag(singular_values) * v_t`,
+    /// returned as a `(u, singular_values, v_t)` named tuple. The singular values
+    /// are sorted in descending order.
+    fn svd(&self, py: Python) -> PyResult<PyObject> {
+        let svd = Matrix4::try_svd(&self.0)?;
+        SVD.make(
+            py,
+            (
+                Matrix4(svd.u.unwrap()),
+                Vector4(svd.singular_values),
+                Matrix4(svd.v_t.unwrap()),
+            ),
+        )
+    }
+
+    /// Computes the eigendecomposition of a symmetric matrix, returned as an
+    /// `(eigenvalues, eigenvectors)` named tuple. The eigenvalues are sorted in
+    /// descending order and the matching eigenvectors are the columns of
+    /// `eigenvectors`.
+    ///
+    /// Raises a ValueError if the matrix isn't symmetric within the current
+    /// tolerance, see `deuterium.tolerance`.
+    fn symmetric_eigen(&self, py: Python) -> PyResult<PyObject> {
+        self.check_finite()?;
+        self.check_symmetric()?;
+        let eigen = na::SymmetricEigen::try_new(self.0, f64::EPSILON, 1000)
+            .ok_or_else(|| PyValueError::new_err("Eigendecomposition did not converge"))?;
+
+        let mut order = [0, 1, 2, 3];
+        order.sort_by(|a, b| eigen.eigenvalues[*b].total_cmp(&eigen.eigenvalues[*a]));
+        let values = na::Vector4::from_fn(|i, _| eigen.eigenvalues[order[i]]);
+        let vectors = Matrix4d::from_fn(|r, c| eigen.eigenvectors[(r, order[c])]);
+        SYMMETRIC_EIGEN.make(py, (Vector4(values), Matrix4(vectors)))
+    }
+
+    /// Computes the QR decomposition `m = q * r`, where `q` is orthogonal and
+    /// `r` is upper triangular, returned as a `(q, r)` named tuple.
+    fn qr(&self, py: Python) -> PyResult<PyObject> {
+        self.check_finite()?;
+        let qr = self.0.qr();
+        QR.make(py, (Matrix4(qr.q()), Matrix4(qr.r())))
+    }
+
+    /// Computes the LU decomposition with partial pivoting `p * m = l * u`,
+    /// where `p` is a permutation matrix, `l` is unit lower triangular and `u`
+    /// is upper triangular, returned as a `(p, l, u)` named tuple.
+    fn lu(&self, py: Python) -> PyResult<PyObject> {
+        self.check_finite()?;
+        let (permutation, l, u) = self.0.lu().unpack();
+        let mut p = Matrix4d::identity();
+        permutation.permute_rows(&mut p);
+        LU.make(py, (Matrix4(p), Matrix4(l), Matrix4(u)))
+    }
+
+    /// Computes the Cholesky decomposition `m = l * l.transposed()` of a
+    /// symmetric positive-definite matrix, returning the lower triangular `l`.
+    ///
+    /// Raises a ValueError if the matrix isn't symmetric positive-definite.
+    fn cholesky(&self) -> PyResult<Matrix4> {
+        self.check_finite()?;
+        self.check_symmetric()?;
+        match na::Cholesky::new(self.0) {
+            Some(c) => Ok(Matrix4(c.unpack())),
+            None => Err(PyValueError::new_err(
+                "Matrix4 is not positive-definite",
+            )),
+        }
+    }
+
+    /// Computes the polar decomposition of the upper-left 3x3 (linear) part of
+    /// this matrix as `linear = rotation * stretch`, returned as a
+    /// `(rotation, stretch)` named tuple. `rotation` is the nearest proper
+    /// rotation, as a UnitQuaternion, and `stretch` is a Matrix4 holding the 3x3
+    /// stretch in its upper-left corner, with a 1 in the bottom right.
+    ///
+    /// This is useful for re-orthonormalizing rotation matrices which have
+    /// drifted. The translation is ignored. If the linear part contains a
+    /// reflection the stretch will have a negative eigenvalue.
+    fn polar(&self, py: Python) -> PyResult<PyObject> {
+        self.check_finite()?;
+        let linear: na::Matrix3<f64> = self.0.fixed_view::<3, 3>(0, 0).into();
+        let svd = na::SVD::try_new(linear, true, true, f64::EPSILON, 1000)
+            .ok_or_else(|| PyValueError::new_err("SVD did not converge"))?;
+        let mut u = svd.u.unwrap();
+        let v_t = svd.v_t.unwrap();
+        if (u * v_t).determinant() < 0.0 {
+            // Flip the axis with the smallest singular value to avoid a reflection
+            let mut column = u.column_mut(2);
+            column.neg_mut();
+        }
+        let rotation = u * v_t;
+        let stretch = rotation.transpose() * linear;
+
+        let mut stretch4 = Matrix4d::identity();
+        stretch4.fixed_view_mut::<3, 3>(0, 0).copy_from(&stretch);
+        let rotation = na::UnitQuaternion::from_rotation_matrix(
+            &na::Rotation3::from_matrix_unchecked(rotation),
+        );
+        POLAR.make(py, (UnitQuaternion(rotation), Matrix4(stretch4)))
+    }
+
     #[getter]
     fn get_translation(&self) -> Vector3 {
         Vector3(na::Vector3::new(
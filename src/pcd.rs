@@ -0,0 +1,363 @@
+use crate::cloud::{parse_number, Cloud, Encoding, Input, Scalar};
+use nalgebra as na;
+use std::io::{self, Write};
+
+struct Field {
+    name: String,
+    scalar: Scalar,
+    count: usize,
+}
+
+/// The header entries, in the order PCD files must give them.
+const KEYWORDS: [&str; 10] = [
+    "VERSION",
+    "FIELDS",
+    "SIZE",
+    "TYPE",
+    "COUNT",
+    "WIDTH",
+    "HEIGHT",
+    "VIEWPOINT",
+    "POINTS",
+    "DATA",
+];
+
+fn scalar(kind: &str, size: &str) -> Option<Scalar> {
+    match (kind, size) {
+        ("I", "1") => Some(Scalar::I8),
+        ("U", "1") => Some(Scalar::U8),
+        ("I", "2") => Some(Scalar::I16),
+        ("U", "2") => Some(Scalar::U16),
+        ("I", "4") => Some(Scalar::I32),
+        ("U", "4") => Some(Scalar::U32),
+        ("F", "4") => Some(Scalar::F32),
+        ("F", "8") => Some(Scalar::F64),
+        _ => None,
+    }
+}
+
+/// Reads the header up to and including the DATA line, returning the
+/// fields, the number of points and the encoding.
+fn read_header(input: &mut Input) -> Result<(Vec<Field>, usize, Encoding), String> {
+    let mut entries: [Option<Vec<&str>>; 10] = Default::default();
+    let mut last = None;
+    loop {
+        let line = match input.line() {
+            Some(line) => line?,
+            None => return Err("the header has no DATA line".into()),
+        };
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) if !keyword.starts_with('#') => keyword,
+            _ => continue,
+        };
+        let index = KEYWORDS
+            .iter()
+            .position(|k| *k == keyword)
+            .ok_or_else(|| input.error(&format!("unknown header entry '{}'", keyword)))?;
+        if entries[index].is_some() {
+            return Err(input.error(&format!("the header has more than one {} line", keyword)));
+        }
+        match last {
+            Some(last) if index < last => {
+                let message = format!("{} must come before {}", keyword, KEYWORDS[last]);
+                return Err(input.error(&message));
+            }
+            _ => {}
+        }
+        last = Some(index);
+        entries[index] = Some(words.collect());
+        if keyword == "DATA" {
+            break;
+        }
+    }
+    let [_, fields, sizes, types, counts, width, height, _, points, data] = entries;
+    let missing = |keyword: &str| format!("the header has no {} line", keyword);
+    let fields = fields.ok_or_else(|| missing("FIELDS"))?;
+    let sizes = sizes.ok_or_else(|| missing("SIZE"))?;
+    let types = types.ok_or_else(|| missing("TYPE"))?;
+    let counts = counts.unwrap_or_else(|| vec!["1"; fields.len()]);
+    for (keyword, values) in [("SIZE", &sizes), ("TYPE", &types), ("COUNT", &counts)] {
+        if values.len() != fields.len() {
+            return Err(format!(
+                "{} has {} entries but FIELDS has {}",
+                keyword,
+                values.len(),
+                fields.len()
+            ));
+        }
+    }
+    let fields = (0..fields.len())
+        .map(|i| {
+            let scalar = scalar(types[i], sizes[i]).ok_or_else(|| {
+                format!(
+                    "unsupported TYPE {} with SIZE {} for field '{}'",
+                    types[i], sizes[i], fields[i]
+                )
+            })?;
+            let count = counts[i]
+                .parse()
+                .map_err(|_| format!("invalid COUNT '{}' for field '{}'", counts[i], fields[i]))?;
+            Ok(Field {
+                name: fields[i].to_string(),
+                scalar,
+                count,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let number = |keyword: &str, values: Option<Vec<&str>>| -> Result<Option<usize>, String> {
+        match values.as_deref() {
+            None => Ok(None),
+            Some([value]) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid {} '{}'", keyword, value)),
+            Some(values) => Err(format!(
+                "{} should be a single number but got '{}'",
+                keyword,
+                values.join(" ")
+            )),
+        }
+    };
+    let width = number("WIDTH", width)?.ok_or_else(|| missing("WIDTH"))?;
+    let height = number("HEIGHT", height)?.unwrap_or(1);
+    let size = width
+        .checked_mul(height)
+        .ok_or("WIDTH x HEIGHT is too large")?;
+    match number("POINTS", points)? {
+        Some(points) if points != size => {
+            return Err(format!(
+                "POINTS is {} but WIDTH x HEIGHT is {}",
+                points, size
+            ))
+        }
+        _ => {}
+    }
+    let encoding = match data.unwrap_or_default().as_slice() {
+        ["ascii"] => Encoding::Ascii,
+        ["binary"] => Encoding::Binary { big_endian: false },
+        ["binary_compressed"] => {
+            return Err("binary_compressed PCD data is not supported".into());
+        }
+        data => return Err(format!("unknown DATA type '{}'", data.join(" "))),
+    };
+    Ok((fields, size, encoding))
+}
+
+/// Where to find a value in a point's fields: its index among the values
+/// when reading text, and its byte offset when reading binary data.
+#[derive(Clone, Copy)]
+struct Slot {
+    index: usize,
+    offset: usize,
+    scalar: Scalar,
+}
+
+fn find_fields(fields: &[Field], names: [&str; 3]) -> Result<Option<[Slot; 3]>, String> {
+    let found = names.map(|name| find_field(fields, name).transpose());
+    if found.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+    let mut slots = Vec::new();
+    for (name, slot) in names.iter().zip(found) {
+        match slot {
+            Some(slot) => slots.push(slot?),
+            None => {
+                return Err(format!(
+                    "the fields include some of {} but not '{}'",
+                    names.join(", "),
+                    name
+                ))
+            }
+        }
+    }
+    Ok(Some([slots[0], slots[1], slots[2]]))
+}
+
+fn find_field(fields: &[Field], name: &str) -> Result<Option<Slot>, String> {
+    let (mut index, mut offset) = (0, 0);
+    for field in fields {
+        if field.name == name {
+            if field.count != 1 {
+                return Err(format!("field '{}' has COUNT {}", name, field.count));
+            }
+            return Ok(Some(Slot {
+                index,
+                offset,
+                scalar: field.scalar,
+            }));
+        }
+        index = checked_add(index, field.count)?;
+        offset = checked_add(offset, field_bytes(field)?)?;
+    }
+    Ok(None)
+}
+
+fn field_bytes(field: &Field) -> Result<usize, String> {
+    field
+        .count
+        .checked_mul(field.scalar.size())
+        .ok_or_else(|| format!("field '{}' has too large a COUNT", field.name))
+}
+
+fn checked_add(a: usize, b: usize) -> Result<usize, String> {
+    a.checked_add(b)
+        .ok_or_else(|| "the fields' COUNTs are too large".to_string())
+}
+
+/// PCL packs colors into the bits of a single 4 byte value as 0xAARRGGBB.
+fn unpack_color(bits: u32) -> [u8; 3] {
+    [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8]
+}
+
+fn pack_color([r, g, b]: [u8; 3]) -> u32 {
+    (r as u32) << 16 | (g as u32) << 8 | b as u32
+}
+
+/// Parses a packed color written as text. PCL writes these as the integer
+/// value of the bits, but other tools write the float the bits represent.
+fn parse_color(token: &str, scalar: Scalar) -> Result<u32, String> {
+    if let Ok(bits) = token.parse::<u32>() {
+        return Ok(bits);
+    }
+    match scalar {
+        Scalar::F32 => token
+            .parse::<f32>()
+            .map(f32::to_bits)
+            .map_err(|_| format!("expected a packed color but got '{}'", token)),
+        _ => Err(format!("expected a packed color but got '{}'", token)),
+    }
+}
+
+pub fn read(data: &[u8]) -> Result<Cloud, String> {
+    let mut input = Input::new(data);
+    let (fields, size, encoding) = read_header(&mut input)?;
+    let xyz =
+        find_fields(&fields, ["x", "y", "z"])?.ok_or("the fields don't include x, y and z")?;
+    let normal = find_fields(&fields, ["normal_x", "normal_y", "normal_z"])?;
+    let color = match find_field(&fields, "rgb")? {
+        Some(slot) => Some(slot),
+        None => find_field(&fields, "rgba")?,
+    };
+    if let Some(slot) = color {
+        if slot.scalar.size() != 4 {
+            return Err("the rgb field must have SIZE 4".into());
+        }
+    }
+    let (mut values_per_point, mut bytes_per_point) = (0, 0);
+    for field in &fields {
+        values_per_point = checked_add(values_per_point, field.count)?;
+        bytes_per_point = checked_add(bytes_per_point, field_bytes(field)?)?;
+    }
+
+    // Every point takes at least a byte, so don't trust a larger size
+    let capacity = size.min(data.len());
+    let mut cloud = Cloud {
+        points: Vec::with_capacity(capacity),
+        normals: normal.map(|_| Vec::with_capacity(capacity)),
+        colors: color.map(|_| Vec::with_capacity(capacity)),
+    };
+    for i in 0..size {
+        let truncated = || format!("unexpected end of file in point {}", i);
+        match encoding {
+            Encoding::Ascii => {
+                let line = input.line().ok_or_else(truncated)??;
+                let tokens: Vec<&str> = line.split_whitespace().collect();
+                if tokens.len() != values_per_point {
+                    return Err(input.error(&format!(
+                        "expected {} values but got {}",
+                        values_per_point,
+                        tokens.len()
+                    )));
+                }
+                let value =
+                    |slot: Slot| parse_number(tokens[slot.index]).map_err(|e| input.error(&e));
+                let vector = |[x, y, z]: [Slot; 3]| -> Result<_, String> {
+                    Ok(na::Vector3::new(value(x)?, value(y)?, value(z)?))
+                };
+                cloud.points.push(vector(xyz)?);
+                if let (Some(normals), Some(normal)) = (&mut cloud.normals, normal) {
+                    normals.push(vector(normal)?);
+                }
+                if let (Some(colors), Some(slot)) = (&mut cloud.colors, color) {
+                    let bits = parse_color(tokens[slot.index], slot.scalar)
+                        .map_err(|e| input.error(&e))?;
+                    colors.push(unpack_color(bits));
+                }
+            }
+            Encoding::Binary { .. } => {
+                let bytes = input.bytes(bytes_per_point).ok_or_else(truncated)?;
+                let value = |slot: Slot| slot.scalar.decode(&bytes[slot.offset..], false);
+                let vector = |[x, y, z]: [Slot; 3]| na::Vector3::new(value(x), value(y), value(z));
+                cloud.points.push(vector(xyz));
+                if let (Some(normals), Some(normal)) = (&mut cloud.normals, normal) {
+                    normals.push(vector(normal));
+                }
+                if let (Some(colors), Some(slot)) = (&mut cloud.colors, color) {
+                    let bits = bytes[slot.offset..slot.offset + 4].try_into().unwrap();
+                    colors.push(unpack_color(u32::from_le_bytes(bits)));
+                }
+            }
+        }
+    }
+    Ok(cloud)
+}
+
+/// Writes a PCD v0.7 file. Binary data is always little endian.
+pub fn write(out: &mut impl Write, cloud: &Cloud, encoding: Encoding) -> io::Result<()> {
+    let mut fields = vec!["x", "y", "z"];
+    let mut sizes = vec!["8"; 3];
+    let mut types = vec!["F"; 3];
+    if cloud.normals.is_some() {
+        fields.extend(["normal_x", "normal_y", "normal_z"]);
+        sizes.extend(["8"; 3]);
+        types.extend(["F"; 3]);
+    }
+    if cloud.colors.is_some() {
+        // PCL's own point types store rgb as a float
+        fields.push("rgb");
+        sizes.push("4");
+        types.push("F");
+    }
+    writeln!(out, "# .PCD v0.7 - Point Cloud Data file format")?;
+    writeln!(out, "VERSION 0.7")?;
+    writeln!(out, "FIELDS {}", fields.join(" "))?;
+    writeln!(out, "SIZE {}", sizes.join(" "))?;
+    writeln!(out, "TYPE {}", types.join(" "))?;
+    writeln!(out, "COUNT {}", vec!["1"; fields.len()].join(" "))?;
+    writeln!(out, "WIDTH {}", cloud.points.len())?;
+    writeln!(out, "HEIGHT 1")?;
+    writeln!(out, "VIEWPOINT 0 0 0 1 0 0 0")?;
+    writeln!(out, "POINTS {}", cloud.points.len())?;
+    match encoding {
+        Encoding::Ascii => writeln!(out, "DATA ascii")?,
+        Encoding::Binary { .. } => writeln!(out, "DATA binary")?,
+    }
+
+    for (i, point) in cloud.points.iter().enumerate() {
+        let normal = cloud.normals.as_ref().map(|normals| normals[i]);
+        let color = cloud.colors.as_ref().map(|colors| pack_color(colors[i]));
+        match encoding {
+            Encoding::Ascii => {
+                write!(out, "{} {} {}", point.x, point.y, point.z)?;
+                if let Some(n) = normal {
+                    write!(out, " {} {} {}", n.x, n.y, n.z)?;
+                }
+                if let Some(bits) = color {
+                    write!(out, " {}", bits)?;
+                }
+                writeln!(out)?;
+            }
+            Encoding::Binary { .. } => {
+                for value in point.iter().chain(normal.iter().flat_map(|n| n.iter())) {
+                    out.write_all(&value.to_le_bytes())?;
+                }
+                if let Some(bits) = color {
+                    out.write_all(&bits.to_le_bytes())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
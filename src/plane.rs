@@ -0,0 +1,138 @@
+use crate::shape::{self, Transform};
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
+
+/// An infinite plane, made up of every point `p` where `normal.dot(p) == offset`.
+///
+/// The normal is always unit length, so `offset` is the signed distance of
+/// the plane from the origin along its normal.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct Plane {
+    pub normal: na::Unit<na::Vector3<f64>>,
+    pub offset: f64,
+}
+
+impl Plane {
+    pub fn signed_distance_to(&self, p: &na::Vector3<f64>) -> f64 {
+        self.normal.dot(p) - self.offset
+    }
+}
+
+#[pymethods]
+impl Plane {
+    /// Creates a plane from a normal, which is normalized, and its signed
+    /// distance from the origin along that normal.
+    #[new]
+    fn new(normal: &Vector3, offset: f64) -> PyResult<Plane> {
+        Ok(Plane {
+            normal: shape::unit(normal.0, "Plane normal")?,
+            offset,
+        })
+    }
+
+    /// Creates the plane through `point` perpendicular to `normal`.
+    #[staticmethod]
+    fn from_point_normal(point: &Vector3, normal: &Vector3) -> PyResult<Plane> {
+        let normal = shape::unit(normal.0, "Plane normal")?;
+        Ok(Plane {
+            offset: normal.dot(&point.0),
+            normal,
+        })
+    }
+
+    /// Creates the plane through three points, facing the side from which
+    /// they appear counter-clockwise. Raises a ValueError if they are collinear.
+    #[staticmethod]
+    fn from_points(a: &Vector3, b: &Vector3, c: &Vector3) -> PyResult<Plane> {
+        let normal = shape::unit((b.0 - a.0).cross(&(c.0 - a.0)), "Plane normal")?;
+        Ok(Plane {
+            offset: normal.dot(&a.0),
+            normal,
+        })
+    }
+
+    #[getter]
+    fn get_normal(&self) -> Vector3 {
+        Vector3(*self.normal)
+    }
+
+    #[getter]
+    fn get_offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Returns the distance of `point` from the plane, which is positive on
+    /// the side the normal points to and negative on the other.
+    fn signed_distance(&self, point: &Vector3) -> f64 {
+        self.signed_distance_to(&point.0)
+    }
+
+    /// Returns the closest point on the plane to `point`.
+    fn project_point(&self, point: &Vector3) -> Vector3 {
+        Vector3(point.0 - *self.normal * self.signed_distance_to(&point.0))
+    }
+
+    /// Returns the same plane facing the other way.
+    fn flipped(&self) -> Plane {
+        Plane {
+            normal: -self.normal,
+            offset: -self.offset,
+        }
+    }
+
+    fn transformed(&self, arg: &PyAny) -> PyResult<Plane> {
+        let t = Transform::extract(arg, "Plane")?;
+        let point = t.point(&(*self.normal * self.offset));
+        let normal = shape::unit(t.normal(&self.normal)?, "Plane normal")?;
+        Ok(Plane {
+            offset: normal.dot(&point),
+            normal,
+        })
+    }
+
+    fn transform(&mut self, arg: &PyAny) -> PyResult<()> {
+        *self = self.transformed(arg)?;
+        Ok(())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Plane, op: CompareOp) -> Py<PyAny> {
+        let eq = self.normal == other.normal && self.offset == other.offset;
+        match op {
+            CompareOp::Eq => eq.into_py(py),
+            CompareOp::Ne => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &Plane,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        Ok(tol.eq(&*self.normal, &*other.normal) && tol.eq(&self.offset, &other.offset))
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (Vector3, f64)) {
+        (
+            py.get_type::<Plane>().into(),
+            (self.get_normal(), self.offset),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Plane(normal={}, offset={})",
+            shape::repr(&self.normal),
+            self.offset
+        )
+    }
+}
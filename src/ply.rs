@@ -0,0 +1,313 @@
+use crate::cloud::{parse_number, Cloud, Encoding, Input, Scalar};
+use nalgebra as na;
+use std::io::{self, Write};
+
+enum Kind {
+    Scalar(Scalar),
+    /// A count of the given type followed by that many items
+    List(Scalar, Scalar),
+}
+
+struct Property {
+    name: String,
+    kind: Kind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+fn scalar(name: &str) -> Option<Scalar> {
+    match name {
+        "char" | "int8" => Some(Scalar::I8),
+        "uchar" | "uint8" => Some(Scalar::U8),
+        "short" | "int16" => Some(Scalar::I16),
+        "ushort" | "uint16" => Some(Scalar::U16),
+        "int" | "int32" => Some(Scalar::I32),
+        "uint" | "uint32" => Some(Scalar::U32),
+        "float" | "float32" => Some(Scalar::F32),
+        "double" | "float64" => Some(Scalar::F64),
+        _ => None,
+    }
+}
+
+fn parse_scalar(input: &Input, name: &str) -> Result<Scalar, String> {
+    scalar(name).ok_or_else(|| input.error(&format!("unknown property type '{}'", name)))
+}
+
+/// Reads the header up to and including `end_header`.
+fn read_header(input: &mut Input) -> Result<(Encoding, Vec<Element>), String> {
+    match input.line() {
+        Some(Ok("ply")) => {}
+        _ => return Err("not a PLY file, the first line must be 'ply'".into()),
+    }
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        let line = match input.line() {
+            Some(line) => line?,
+            None => return Err("the header has no end_header line".into()),
+        };
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["format", format, version] => {
+                if encoding.is_some() {
+                    return Err(input.error("the header has more than one format line"));
+                }
+                if *version != "1.0" {
+                    return Err(input.error(&format!("unsupported PLY version '{}'", version)));
+                }
+                encoding = Some(match *format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::Binary { big_endian: false },
+                    "binary_big_endian" => Encoding::Binary { big_endian: true },
+                    _ => return Err(input.error(&format!("unknown format '{}'", format))),
+                });
+            }
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| {
+                    input.error(&format!("invalid count '{}' for element '{}'", count, name))
+                })?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            ["property", rest @ ..] => {
+                let (kind, name) = match rest {
+                    ["list", count, item, name] => (
+                        Kind::List(parse_scalar(input, count)?, parse_scalar(input, item)?),
+                        name,
+                    ),
+                    [kind, name] => (Kind::Scalar(parse_scalar(input, kind)?), name),
+                    _ => return Err(input.error(&format!("malformed property '{}'", line))),
+                };
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| input.error("property before any element"))?;
+                if element.properties.iter().any(|p| p.name == *name) {
+                    return Err(input.error(&format!(
+                        "element '{}' has more than one '{}' property",
+                        element.name, name
+                    )));
+                }
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind,
+                });
+            }
+            ["end_header"] => break,
+            _ => return Err(input.error(&format!("unexpected header line '{}'", line))),
+        }
+    }
+    let encoding = encoding.ok_or("the header has no format line")?;
+    Ok((encoding, elements))
+}
+
+/// Reads one element into `values`, one for each property, with NaN for
+/// list properties.
+fn read_row(
+    input: &mut Input,
+    element: &Element,
+    index: usize,
+    encoding: Encoding,
+    values: &mut Vec<f64>,
+) -> Result<(), String> {
+    values.clear();
+    let truncated = || format!("unexpected end of file in {} {}", element.name, index);
+    match encoding {
+        Encoding::Ascii => {
+            let line = input.line().ok_or_else(truncated)??;
+            let mut tokens = line.split_whitespace();
+            let mut next = || {
+                let token = tokens.next().ok_or_else(|| {
+                    input.error(&format!("too few values for {} {}", element.name, index))
+                })?;
+                parse_number(token).map_err(|e| input.error(&e))
+            };
+            for property in &element.properties {
+                match property.kind {
+                    Kind::Scalar(_) => values.push(next()?),
+                    Kind::List(..) => {
+                        let count =
+                            list_length(next()?, element, index).map_err(|e| input.error(&e))?;
+                        for _ in 0..count {
+                            next()?;
+                        }
+                        values.push(f64::NAN);
+                    }
+                }
+            }
+            if tokens.next().is_some() {
+                return Err(input.error(&format!("too many values for {} {}", element.name, index)));
+            }
+        }
+        Encoding::Binary { big_endian } => {
+            for property in &element.properties {
+                match property.kind {
+                    Kind::Scalar(scalar) => {
+                        let bytes = input.bytes(scalar.size()).ok_or_else(truncated)?;
+                        values.push(scalar.decode(bytes, big_endian));
+                    }
+                    Kind::List(count, item) => {
+                        let bytes = input.bytes(count.size()).ok_or_else(truncated)?;
+                        let count = list_length(count.decode(bytes, big_endian), element, index)?;
+                        let size = count.checked_mul(item.size()).ok_or_else(truncated)?;
+                        input.bytes(size).ok_or_else(truncated)?;
+                        values.push(f64::NAN);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a list's length is a whole, non-negative number.
+fn list_length(count: f64, element: &Element, index: usize) -> Result<usize, String> {
+    if count < 0.0 {
+        return Err(format!(
+            "negative list length in {} {}",
+            element.name, index
+        ));
+    }
+    if !(count.is_finite() && count.fract() == 0.0) {
+        return Err(format!(
+            "invalid list length {} in {} {}",
+            count, element.name, index
+        ));
+    }
+    Ok(count as usize)
+}
+
+/// The indices of the named vertex properties, None if they're all missing,
+/// or an error if only some are present or any is a list.
+fn find_properties(vertex: &Element, names: [&str; 3]) -> Result<Option<[usize; 3]>, String> {
+    let found = names.map(|name| vertex.properties.iter().position(|p| p.name == name));
+    if found.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+    for (name, index) in names.iter().zip(found) {
+        match index.map(|i| &vertex.properties[i].kind) {
+            None => {
+                return Err(format!(
+                    "the vertex element has some of {} but not '{}'",
+                    names.join(", "),
+                    name
+                ))
+            }
+            Some(Kind::List(..)) => {
+                return Err(format!("the vertex property '{}' is a list", name))
+            }
+            Some(Kind::Scalar(_)) => {}
+        }
+    }
+    Ok(Some(found.map(Option::unwrap)))
+}
+
+pub fn read(data: &[u8]) -> Result<Cloud, String> {
+    let mut input = Input::new(data);
+    let (encoding, elements) = read_header(&mut input)?;
+    let vertex = elements
+        .iter()
+        .find(|e| e.name == "vertex")
+        .ok_or("the header has no vertex element")?;
+    let xyz = find_properties(vertex, ["x", "y", "z"])?
+        .ok_or("the vertex element has no x, y and z properties")?;
+    let normal = find_properties(vertex, ["nx", "ny", "nz"])?;
+    let color = find_properties(vertex, ["red", "green", "blue"])?;
+    let color_scalars = color.map(|indices| {
+        indices.map(|i| match vertex.properties[i].kind {
+            Kind::Scalar(scalar) => scalar,
+            Kind::List(..) => unreachable!(),
+        })
+    });
+
+    // Every vertex takes at least a byte, so don't trust a larger count
+    let capacity = vertex.count.min(data.len());
+    let mut cloud = Cloud {
+        points: Vec::with_capacity(capacity),
+        normals: normal.map(|_| Vec::with_capacity(capacity)),
+        colors: color.map(|_| Vec::with_capacity(capacity)),
+    };
+    let mut values = Vec::new();
+    for element in &elements {
+        for index in 0..element.count {
+            read_row(&mut input, element, index, encoding, &mut values)?;
+            if element.name != "vertex" {
+                continue;
+            }
+            let vector = |[x, y, z]: [usize; 3]| na::Vector3::new(values[x], values[y], values[z]);
+            cloud.points.push(vector(xyz));
+            if let (Some(normals), Some(normal)) = (&mut cloud.normals, normal) {
+                normals.push(vector(normal));
+            }
+            if let (Some(colors), Some(color), Some(scalars)) =
+                (&mut cloud.colors, color, color_scalars)
+            {
+                colors.push([0, 1, 2].map(|c| scalars[c].color_component(values[color[c]])));
+            }
+        }
+    }
+    Ok(cloud)
+}
+
+pub fn write(out: &mut impl Write, cloud: &Cloud, encoding: Encoding) -> io::Result<()> {
+    let format = match encoding {
+        Encoding::Ascii => "ascii",
+        Encoding::Binary { big_endian: false } => "binary_little_endian",
+        Encoding::Binary { big_endian: true } => "binary_big_endian",
+    };
+    writeln!(out, "ply\nformat {} 1.0", format)?;
+    writeln!(out, "element vertex {}", cloud.points.len())?;
+    writeln!(
+        out,
+        "property double x\nproperty double y\nproperty double z"
+    )?;
+    if cloud.normals.is_some() {
+        writeln!(
+            out,
+            "property double nx\nproperty double ny\nproperty double nz"
+        )?;
+    }
+    if cloud.colors.is_some() {
+        writeln!(
+            out,
+            "property uchar red\nproperty uchar green\nproperty uchar blue"
+        )?;
+    }
+    writeln!(out, "end_header")?;
+
+    for (i, point) in cloud.points.iter().enumerate() {
+        let normal = cloud.normals.as_ref().map(|normals| normals[i]);
+        let color = cloud.colors.as_ref().map(|colors| colors[i]);
+        match encoding {
+            Encoding::Ascii => {
+                write!(out, "{} {} {}", point.x, point.y, point.z)?;
+                if let Some(n) = normal {
+                    write!(out, " {} {} {}", n.x, n.y, n.z)?;
+                }
+                if let Some([r, g, b]) = color {
+                    write!(out, " {} {} {}", r, g, b)?;
+                }
+                writeln!(out)?;
+            }
+            Encoding::Binary { big_endian } => {
+                for value in point.iter().chain(normal.iter().flat_map(|n| n.iter())) {
+                    if big_endian {
+                        out.write_all(&value.to_be_bytes())?;
+                    } else {
+                        out.write_all(&value.to_le_bytes())?;
+                    }
+                }
+                if let Some(color) = color {
+                    out.write_all(&color)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,535 @@
+use crate::camera::PinholeCamera;
+use crate::iso::Isometry3;
+use crate::points::{self, extract_pairs, extract_points};
+use crate::registration::kabsch;
+use crate::results::NamedTuple;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+
+type V2 = na::Vector2<f64>;
+type V3 = na::Vector3<f64>;
+
+static PNP_RESULT: NamedTuple =
+    NamedTuple::new("PnpResult", &["pose", "inliers", "reprojection_error"]);
+
+/// Variances this small relative to the largest are treated as zero, i.e.
+/// the points don't extend in that direction.
+const DEGENERATE_RATIO: f64 = 1e-12;
+
+/// The number of correspondences each RANSAC hypothesis is estimated from.
+/// EPnP's solution is exact for five points, planar or not.
+const SAMPLE_SIZE: usize = 5;
+
+/// The fewest inliers a RANSAC pose is accepted with, as for `solve_pnp`.
+const MIN_INLIERS: usize = 4;
+
+/// The 2D-3D correspondences a pose is estimated from.
+struct Problem<'a> {
+    world: &'a [V3],
+    pixels: &'a [V2],
+    camera: &'a PinholeCamera,
+}
+
+impl Problem<'_> {
+    fn subset(&self, indices: &[usize]) -> (Vec<V3>, Vec<V2>) {
+        indices
+            .iter()
+            .map(|i| (self.world[*i], self.pixels[*i]))
+            .unzip()
+    }
+
+    /// The distance in pixels between each observed pixel and the projection
+    /// of its point, which is infinite for points not in front of the camera.
+    fn errors(&self, pose: &na::Isometry3<f64>) -> Vec<f64> {
+        self.world
+            .iter()
+            .zip(self.pixels)
+            .map(|(p, pixel)| {
+                match self
+                    .camera
+                    .project_point(&pose.transform_point(&na::Point3::from(*p)).coords)
+                {
+                    Some((u, v)) => (V2::new(u, v) - pixel).magnitude(),
+                    None => f64::INFINITY,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The ranks of the points' spread: 3 for points in general position, 2 for
+/// coplanar points and less for collinear or coincident ones.
+fn spread(world: &[V3]) -> usize {
+    let (_, covariance) = points::mean_and_covariance(world);
+    let (_, variances) = points::principal_axes(covariance);
+    variances
+        .iter()
+        .filter(|v| **v > variances[0] * DEGENERATE_RATIO)
+        .count()
+}
+
+/// Estimates the world to camera pose with EPnP (Lepetit, Moreno-Noguer and
+/// Fua, 2009), which expresses the points as weighted sums of four control
+/// points (three for coplanar points) and finds those control points in
+/// camera coordinates. `normalized` holds the normalized image coordinates
+/// of each point's projection.
+///
+/// Returns None if the points are collinear or no finite pose is found.
+fn epnp(world: &[V3], normalized: &[V2]) -> Option<na::Isometry3<f64>> {
+    let (mean, covariance) = points::mean_and_covariance(world);
+    let (axes, variances) = points::principal_axes(covariance);
+    if variances[1] <= variances[0] * DEGENERATE_RATIO {
+        return None;
+    }
+    let axis_count = if variances[2] <= variances[0] * DEGENERATE_RATIO {
+        2
+    } else {
+        3
+    };
+    let k = axis_count + 1;
+    let scales: Vec<f64> = (0..axis_count).map(|i| variances[i].sqrt()).collect();
+    let mut controls = vec![mean];
+    controls.extend((0..axis_count).map(|i| mean + axes.matrix().column(i) * scales[i]));
+
+    // Each point's weights for the control points, which sum to one
+    let alphas: Vec<Vec<f64>> = world
+        .iter()
+        .map(|p| {
+            let d = p - mean;
+            let mut alpha: Vec<f64> = (0..axis_count)
+                .map(|i| axes.matrix().column(i).dot(&d) / scales[i])
+                .collect();
+            alpha.insert(0, 1.0 - alpha.iter().sum::<f64>());
+            alpha
+        })
+        .collect();
+
+    // The camera coordinates of the control points, stacked into a vector x,
+    // satisfy M x = 0, so x is in the (approximate) null space of M
+    let mut m = na::DMatrix::<f64>::zeros(2 * world.len(), 3 * k);
+    for (i, (alpha, uv)) in alphas.iter().zip(normalized).enumerate() {
+        for j in 0..k {
+            m[(2 * i, 3 * j)] = alpha[j];
+            m[(2 * i, 3 * j + 2)] = -alpha[j] * uv.x;
+            m[(2 * i + 1, 3 * j + 1)] = alpha[j];
+            m[(2 * i + 1, 3 * j + 2)] = -alpha[j] * uv.y;
+        }
+    }
+    let eigen = (m.transpose() * m).symmetric_eigen();
+    let mut order: Vec<usize> = (0..3 * k).collect();
+    order.sort_by(|a, b| eigen.eigenvalues[*a].total_cmp(&eigen.eigenvalues[*b]));
+    let kernel: Vec<na::DVector<f64>> = order
+        .iter()
+        .map(|i| eigen.eigenvectors.column(*i).into_owned())
+        .collect();
+
+    // The distances between the control points are known, which fixes the
+    // combination of null space vectors up to sign
+    let pairs: Vec<(usize, usize)> = (0..k)
+        .flat_map(|i| (i + 1..k).map(move |j| (i, j)))
+        .collect();
+    let distances: Vec<f64> = pairs
+        .iter()
+        .map(|(i, j)| (controls[*i] - controls[*j]).magnitude_squared())
+        .collect();
+
+    let pose_for = |betas: &[f64]| -> Option<(na::Isometry3<f64>, f64)> {
+        let control = |j: usize| -> V3 {
+            (0..betas.len())
+                .map(|a| kernel[a].fixed_rows::<3>(3 * j) * betas[a])
+                .sum()
+        };
+        let camera_controls: Vec<V3> = (0..k).map(control).collect();
+        let mut camera: Vec<V3> = alphas
+            .iter()
+            .map(|alpha| (0..k).map(|j| camera_controls[j] * alpha[j]).sum())
+            .collect();
+        if camera.iter().map(|p| p.z).sum::<f64>() < 0.0 {
+            camera.iter_mut().for_each(|p| *p = -*p);
+        }
+        let pose = kabsch(world, &camera, None);
+        let error: f64 = world
+            .iter()
+            .zip(normalized)
+            .map(|(p, uv)| {
+                let p = pose.transform_point(&na::Point3::from(*p));
+                (V2::new(p.x / p.z, p.y / p.z) - uv).magnitude_squared()
+            })
+            .sum();
+        match error.is_finite() {
+            true => Some((pose, error)),
+            false => None,
+        }
+    };
+
+    let mut best: Option<(na::Isometry3<f64>, f64)> = None;
+    let mut previous_betas: Vec<f64> = Vec::new();
+    for n in 1..=k.min(4) {
+        let differences = |a: usize, (i, j): (usize, usize)| -> V3 {
+            kernel[a].fixed_rows::<3>(3 * i) - kernel[a].fixed_rows::<3>(3 * j)
+        };
+        let betas = linearized_betas(n, &pairs, &distances, &differences).unwrap_or_else(|| {
+            let mut betas = previous_betas.clone();
+            betas.push(0.0);
+            betas
+        });
+        let betas = refine_betas(betas, &pairs, &distances, &differences);
+        if let Some((pose, error)) = pose_for(&betas) {
+            if best.as_ref().is_none_or(|(_, best)| error < *best) {
+                best = Some((pose, error));
+            }
+        }
+        previous_betas = betas;
+    }
+    best.map(|(pose, _)| pose)
+}
+
+/// Estimates the weights of the first `n` null space vectors by treating
+/// each product of two weights as an unknown in the (linear) distance
+/// constraints. Returns None when there are fewer constraints than unknowns.
+fn linearized_betas(
+    n: usize,
+    pairs: &[(usize, usize)],
+    distances: &[f64],
+    differences: &impl Fn(usize, (usize, usize)) -> V3,
+) -> Option<Vec<f64>> {
+    let products: Vec<(usize, usize)> = (0..n).flat_map(|a| (a..n).map(move |b| (a, b))).collect();
+    if products.len() > pairs.len() {
+        return None;
+    }
+    let l = na::DMatrix::from_fn(pairs.len(), products.len(), |p, q| {
+        let (a, b) = products[q];
+        let dot = differences(a, pairs[p]).dot(&differences(b, pairs[p]));
+        if a == b {
+            dot
+        } else {
+            2.0 * dot
+        }
+    });
+    let rho = na::DVector::from_column_slice(distances);
+    let solution = l.svd(true, true).solve(&rho, 1e-12).ok()?;
+    let product =
+        |a: usize, b: usize| solution[products.iter().position(|p| *p == (a, b)).unwrap()];
+    // The squares give the weights' sizes and their products with the first
+    // weight give their signs relative to it
+    Some(
+        (0..n)
+            .map(|a| {
+                let size = product(a, a).abs().sqrt();
+                match a == 0 || product(0, a) >= 0.0 {
+                    true => size,
+                    false => -size,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Refines the null space weights with Gauss-Newton so the distances
+/// between the camera control points match those between the world ones.
+fn refine_betas(
+    mut betas: Vec<f64>,
+    pairs: &[(usize, usize)],
+    distances: &[f64],
+    differences: &impl Fn(usize, (usize, usize)) -> V3,
+) -> Vec<f64> {
+    let n = betas.len();
+    for _ in 0..10 {
+        let mut jacobian = na::DMatrix::<f64>::zeros(pairs.len(), n);
+        let mut residuals = na::DVector::<f64>::zeros(pairs.len());
+        for (p, pair) in pairs.iter().enumerate() {
+            let d: V3 = (0..n).map(|a| differences(a, *pair) * betas[a]).sum();
+            residuals[p] = d.magnitude_squared() - distances[p];
+            for a in 0..n {
+                jacobian[(p, a)] = 2.0 * d.dot(&differences(a, *pair));
+            }
+        }
+        match jacobian.svd(true, true).solve(&residuals, 1e-12) {
+            Ok(step) if step.iter().all(|s| s.is_finite()) => {
+                betas.iter_mut().zip(step.iter()).for_each(|(b, s)| *b -= s)
+            }
+            _ => break,
+        }
+    }
+    betas
+}
+
+/// Refines a pose with Levenberg-Marquardt, minimising the squared
+/// reprojection errors in pixels. Steps rotate and translate the camera by
+/// a small motion, so the pose stays a rigid transform.
+fn refine(
+    problem: &Problem,
+    mut pose: na::Isometry3<f64>,
+    max_iterations: usize,
+) -> na::Isometry3<f64> {
+    let (fx, fy) = (problem.camera.fx, problem.camera.fy);
+    let cost =
+        |pose: &na::Isometry3<f64>| -> f64 { problem.errors(pose).iter().map(|e| e * e).sum() };
+    let mut current = cost(&pose);
+    let mut lambda = 1e-3;
+    for _ in 0..max_iterations {
+        let mut jtj = na::Matrix6::<f64>::zeros();
+        let mut jtr = na::Vector6::<f64>::zeros();
+        for (p, pixel) in problem.world.iter().zip(problem.pixels) {
+            let p = pose.transform_point(&na::Point3::from(*p)).coords;
+            let (u, v) = match problem.camera.project_point(&p) {
+                Some(uv) => uv,
+                None => continue,
+            };
+            let projection = na::Matrix2x3::new(
+                fx / p.z,
+                0.0,
+                -fx * p.x / (p.z * p.z),
+                0.0,
+                fy / p.z,
+                -fy * p.y / (p.z * p.z),
+            );
+            // A small rotation w and translation t move p by w x p + t
+            let mut motion = na::Matrix3x6::<f64>::zeros();
+            motion
+                .fixed_view_mut::<3, 3>(0, 0)
+                .copy_from(&-p.cross_matrix());
+            motion
+                .fixed_view_mut::<3, 3>(0, 3)
+                .copy_from(&na::Matrix3::identity());
+            let jacobian = projection * motion;
+            jtj += jacobian.transpose() * jacobian;
+            jtr += jacobian.transpose() * (V2::new(u, v) - pixel);
+        }
+        let improved = loop {
+            let mut damped = jtj;
+            for i in 0..6 {
+                damped[(i, i)] += lambda * jtj[(i, i)].max(1e-12);
+            }
+            let step = match damped.lu().solve(&-jtr) {
+                Some(step) => step,
+                None => break None,
+            };
+            let candidate = na::Isometry3::from_parts(
+                V3::new(step[3], step[4], step[5]).into(),
+                na::UnitQuaternion::from_scaled_axis(V3::new(step[0], step[1], step[2])),
+            ) * pose;
+            let candidate_cost = cost(&candidate);
+            if candidate_cost < current {
+                lambda = (lambda / 10.0).max(1e-12);
+                break Some((candidate, candidate_cost, step.amax()));
+            }
+            lambda *= 10.0;
+            if lambda > 1e12 {
+                break None;
+            }
+        };
+        match improved {
+            Some((candidate, candidate_cost, step)) => {
+                pose = candidate;
+                let converged = current - candidate_cost <= current * 1e-15 || step < 1e-15;
+                current = candidate_cost;
+                if converged {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+    pose
+}
+
+fn rms(errors: &[f64], inliers: &[bool]) -> f64 {
+    let (sum, count) = errors
+        .iter()
+        .zip(inliers)
+        .filter(|(_, inlier)| **inlier)
+        .fold((0.0, 0), |(sum, count), (e, _)| (sum + e * e, count + 1));
+    (sum / count as f64).sqrt()
+}
+
+fn extract_problem(py: Python, points: &PyAny, pixels: &PyAny) -> PyResult<(Vec<V3>, Vec<V2>)> {
+    let world = extract_points(py, points)?;
+    let pixels: Vec<V2> = extract_pairs(py, pixels, "pixel")?
+        .into_iter()
+        .map(|(u, v)| V2::new(u, v))
+        .collect();
+    if pixels.len() != world.len() {
+        return Err(PyValueError::new_err(format!(
+            "Expected {} pixels but got {}",
+            world.len(),
+            pixels.len()
+        )));
+    }
+    if world.len() < 4 {
+        return Err(PyValueError::new_err(
+            "Estimating a pose needs at least 4 points",
+        ));
+    }
+    if !world.iter().all(|p| p.iter().all(|c| c.is_finite()))
+        || !pixels.iter().all(|p| p.iter().all(|c| c.is_finite()))
+    {
+        return Err(PyValueError::new_err("Points and pixels must be finite"));
+    }
+    if spread(&world) < 2 {
+        return Err(PyValueError::new_err(
+            "Cannot estimate a pose from collinear points",
+        ));
+    }
+    Ok((world, pixels))
+}
+
+fn no_pose() -> PyErr {
+    PyValueError::new_err("Could not estimate a pose from the points")
+}
+
+/// Estimates a camera's pose from points in the world and the pixels they
+/// project to (the Perspective-n-Point problem), returning a
+/// `PnpResult(pose, inliers, reprojection_error)`.
+///
+/// `pose` is the Isometry3 mapping world coordinates into camera
+/// coordinates, as taken by PinholeCamera's methods. It is found with EPnP
+/// and then refined with up to `max_iterations` of Levenberg-Marquardt.
+/// `inliers` is True for every point, and `reprojection_error` is the root
+/// mean square distance in pixels between the pixels and the projected
+/// points.
+///
+/// Points may be given as a list of Vector3s, a list of 3 element sequences
+/// or an N x 3 array, and pixels as a list of `(u, v)` sequences or an N x 2
+/// array. At least 4 points are needed, which must not be collinear. The
+/// pixels should be undistorted first if the camera has lens distortion.
+#[pyfunction]
+#[pyo3(signature = (points, pixels, camera, *, max_iterations=20))]
+pub fn solve_pnp(
+    py: Python,
+    points: &PyAny,
+    pixels: &PyAny,
+    camera: &PinholeCamera,
+    max_iterations: usize,
+) -> PyResult<PyObject> {
+    let (world, pixels) = extract_problem(py, points, pixels)?;
+    let problem = Problem {
+        world: &world,
+        pixels: &pixels,
+        camera,
+    };
+    let (pose, errors) = py
+        .allow_threads(|| {
+            let normalized: Vec<V2> = pixels
+                .iter()
+                .map(|p| {
+                    let (x, y) = camera.pixel_to_normalized(p.x, p.y);
+                    V2::new(x, y)
+                })
+                .collect();
+            let pose = refine(&problem, epnp(&world, &normalized)?, max_iterations);
+            let errors = problem.errors(&pose);
+            Some((pose, errors))
+        })
+        .ok_or_else(no_pose)?;
+    let inliers = vec![true; world.len()];
+    let error = rms(&errors, &inliers);
+    PNP_RESULT.make(py, (Isometry3(pose), inliers, error))
+}
+
+/// Estimates a camera's pose as `solve_pnp` does from correspondences which
+/// include outliers, using RANSAC. Points whose projection is within
+/// `threshold` pixels of their pixel are inliers.
+///
+/// Each of the `iterations` estimates a pose from 5 random correspondences,
+/// keeping the one with the most inliers, which is then refined on its
+/// inliers. The random correspondences are chosen by a generator seeded
+/// with `seed`, so the result is deterministic. `inliers` is a list of
+/// bools, and `reprojection_error` is the root mean square error of the
+/// inliers only.
+///
+/// Raises a ValueError if no sample gives a pose with at least 4 inliers.
+#[pyfunction]
+#[pyo3(signature = (points, pixels, camera, threshold, *, iterations=100, seed=0, max_iterations=20))]
+#[allow(clippy::too_many_arguments)]
+pub fn solve_pnp_ransac(
+    py: Python,
+    points: &PyAny,
+    pixels: &PyAny,
+    camera: &PinholeCamera,
+    threshold: f64,
+    iterations: usize,
+    seed: u64,
+    max_iterations: usize,
+) -> PyResult<PyObject> {
+    if threshold.is_nan() || threshold <= 0.0 {
+        return Err(PyValueError::new_err("threshold must be a positive number"));
+    }
+    let (world, pixels) = extract_problem(py, points, pixels)?;
+    let problem = Problem {
+        world: &world,
+        pixels: &pixels,
+        camera,
+    };
+    let result = py.allow_threads(|| {
+        let normalized: Vec<V2> = pixels
+            .iter()
+            .map(|p| {
+                let (x, y) = camera.pixel_to_normalized(p.x, p.y);
+                V2::new(x, y)
+            })
+            .collect();
+        let inliers_of = |pose: &na::Isometry3<f64>| -> Vec<bool> {
+            problem
+                .errors(pose)
+                .iter()
+                .map(|e| *e <= threshold)
+                .collect()
+        };
+        let count = |inliers: &[bool]| inliers.iter().filter(|i| **i).count();
+        let sample_size = SAMPLE_SIZE.min(world.len());
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut best: Option<(na::Isometry3<f64>, Vec<bool>)> = None;
+        for _ in 0..iterations {
+            let sample = rand::seq::index::sample(&mut rng, world.len(), sample_size).into_vec();
+            let (sample_world, _) = problem.subset(&sample);
+            if spread(&sample_world) < 2 {
+                continue;
+            }
+            let sample_normalized: Vec<V2> = sample.iter().map(|i| normalized[*i]).collect();
+            let pose = match epnp(&sample_world, &sample_normalized) {
+                Some(pose) => pose,
+                None => continue,
+            };
+            let inliers = inliers_of(&pose);
+            let better = match &best {
+                Some((_, best)) => count(&inliers) > count(best),
+                None => true,
+            };
+            if better {
+                let done = count(&inliers) == world.len();
+                best = Some((pose, inliers));
+                if done {
+                    break;
+                }
+            }
+        }
+        let (pose, inliers) = best.filter(|(_, inliers)| count(inliers) >= MIN_INLIERS)?;
+
+        // Refine the pose on its inliers, keeping the refinement unless it
+        // loses inliers
+        let indices: Vec<usize> = (0..world.len()).filter(|i| inliers[*i]).collect();
+        let (inlier_world, inlier_pixels) = problem.subset(&indices);
+        let inlier_problem = Problem {
+            world: &inlier_world,
+            pixels: &inlier_pixels,
+            camera,
+        };
+        let refined = refine(&inlier_problem, pose, max_iterations);
+        let refined_inliers = inliers_of(&refined);
+        let (pose, inliers) = if count(&refined_inliers) >= count(&inliers) {
+            (refined, refined_inliers)
+        } else {
+            (pose, inliers)
+        };
+        let errors = problem.errors(&pose);
+        Some((pose, inliers, errors))
+    });
+    let (pose, inliers, errors) = result.ok_or_else(no_pose)?;
+    let error = rms(&errors, &inliers);
+    PNP_RESULT.make(py, (Isometry3(pose), inliers, error))
+}
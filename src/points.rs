@@ -0,0 +1,131 @@
+use crate::frozen::FrozenVector3;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Extracts a set of points from a sequence of Vector3s (or FrozenVector3s),
+/// a sequence of 3 element sequences, or an N x 3 float64 buffer such as a
+/// numpy array.
+pub fn extract_points(py: Python, points: &PyAny) -> PyResult<Vec<na::Vector3<f64>>> {
+    if let Ok(buffer) = PyBuffer::<f64>::get(points) {
+        let shape = buffer.shape();
+        if shape.len() != 2 || shape[1] != 3 {
+            return Err(PyValueError::new_err(format!(
+                "Expected an N x 3 array of points but got shape {:?}",
+                shape
+            )));
+        }
+        let values = buffer.to_vec(py)?;
+        return Ok(values
+            .chunks_exact(3)
+            .map(na::Vector3::from_column_slice)
+            .collect());
+    }
+    let mut result = Vec::with_capacity(points.len().unwrap_or(0));
+    for (i, point) in points.iter()?.enumerate() {
+        let point = point?;
+        if let Ok(v) = point.extract::<PyRef<Vector3>>() {
+            result.push(v.0);
+        } else if let Ok(v) = point.extract::<PyRef<FrozenVector3>>() {
+            result.push(v.0);
+        } else {
+            let values: Vec<f64> = point.extract()?;
+            if values.len() != 3 {
+                return Err(PyValueError::new_err(format!(
+                    "Expected points with 3 components but point {} has {}",
+                    i,
+                    values.len()
+                )));
+            }
+            result.push(na::Vector3::from_vec(values));
+        }
+    }
+    Ok(result)
+}
+
+/// Extracts points as `extract_points` does, raising a ValueError if there
+/// are none.
+pub fn extract_nonempty_points(py: Python, points: &PyAny) -> PyResult<Vec<na::Vector3<f64>>> {
+    let points = extract_points(py, points)?;
+    if points.is_empty() {
+        return Err(PyValueError::new_err("Expected at least one point"));
+    }
+    Ok(points)
+}
+
+/// Extracts points as `extract_nonempty_points` does, also raising a
+/// ValueError if any of them isn't finite.
+pub fn extract_finite_points(py: Python, points: &PyAny) -> PyResult<Vec<na::Vector3<f64>>> {
+    let points = extract_nonempty_points(py, points)?;
+    if !points.iter().all(|p| p.iter().all(|c| c.is_finite())) {
+        return Err(PyValueError::new_err("Points must be finite"));
+    }
+    Ok(points)
+}
+
+/// Extracts 2D coordinates, such as pixels, from a sequence of 2 element
+/// sequences or an N x 2 float64 buffer. `what` names one of them for error
+/// messages.
+pub fn extract_pairs(py: Python, pairs: &PyAny, what: &str) -> PyResult<Vec<(f64, f64)>> {
+    if let Ok(buffer) = PyBuffer::<f64>::get(pairs) {
+        let shape = buffer.shape();
+        if shape.len() != 2 || shape[1] != 2 {
+            return Err(PyValueError::new_err(format!(
+                "Expected an N x 2 array of {}s but got shape {:?}",
+                what, shape
+            )));
+        }
+        let values = buffer.to_vec(py)?;
+        return Ok(values.chunks_exact(2).map(|xy| (xy[0], xy[1])).collect());
+    }
+    let mut result = Vec::with_capacity(pairs.len().unwrap_or(0));
+    for (i, pair) in pairs.iter()?.enumerate() {
+        let values: Vec<f64> = pair?.extract()?;
+        if values.len() != 2 {
+            return Err(PyValueError::new_err(format!(
+                "Expected {}s with 2 coordinates but {} {} has {}",
+                what,
+                what,
+                i,
+                values.len()
+            )));
+        }
+        result.push((values[0], values[1]));
+    }
+    Ok(result)
+}
+
+/// The mean of the points and their (population) covariance matrix.
+pub fn mean_and_covariance(points: &[na::Vector3<f64>]) -> (na::Vector3<f64>, na::Matrix3<f64>) {
+    let n = points.len() as f64;
+    let mean = points.iter().sum::<na::Vector3<f64>>() / n;
+    let covariance = points
+        .iter()
+        .map(|p| {
+            let d = p - mean;
+            d * d.transpose()
+        })
+        .sum::<na::Matrix3<f64>>()
+        / n;
+    (mean, covariance)
+}
+
+/// The eigenvectors of a symmetric 3x3 matrix as the columns of a rotation
+/// matrix, sorted by descending eigenvalue, along with the eigenvalues.
+pub fn principal_axes(covariance: na::Matrix3<f64>) -> (na::Rotation3<f64>, na::Vector3<f64>) {
+    let eigen = covariance.symmetric_eigen();
+    let mut order = [0, 1, 2];
+    order.sort_by(|a, b| eigen.eigenvalues[*b].total_cmp(&eigen.eigenvalues[*a]));
+    let mut axes =
+        na::Matrix3::from_columns(&order.map(|i| eigen.eigenvectors.column(i).into_owned()));
+    // Keep the axes right-handed so they form a rotation
+    if axes.determinant() < 0.0 {
+        axes.set_column(2, &-axes.column(2));
+    }
+    (
+        na::Rotation3::from_matrix_unchecked(axes),
+        na::Vector3::from(order.map(|i| eigen.eigenvalues[i])),
+    )
+}
@@ -1,15 +1,22 @@
-use approx::AbsDiffEq;
 use nalgebra as na;
-use pyo3::exceptions::PyIndexError;
 use pyo3::prelude::*;
 use pyo3::pyclass::CompareOp;
 
+use crate::frozen::FrozenUnitQuaternion;
+use crate::seq::{self, ComponentIter};
+use crate::tolerance::Tolerance;
 use crate::vec3::Vector3;
 
 #[pyclass(sequence)]
 pub struct UnitQuaternion(pub na::UnitQuaternion<f64>);
 
 #[pymethods]
+#[allow(
+    clippy::needless_return,
+    clippy::unused_unit,
+    clippy::assign_op_pattern,
+    clippy::manual_map
+)]
 impl UnitQuaternion {
     #[new]
     fn new() -> Self {
@@ -37,19 +44,47 @@ impl UnitQuaternion {
         UnitQuaternion(na::UnitQuaternion::from_euler_angles(roll, pitch, yaw))
     }
 
-    fn __getitem__(&self, idx: isize) -> Result<f64, PyErr> {
-        let i: usize = if idx < 0 && idx > -5 {
-            (4 + idx) as usize
-        } else {
-            match TryInto::<usize>::try_into(idx) {
-                Err(_) => Err(PyIndexError::new_err(idx))?,
-                Ok(ui) => ui,
-            }
-        };
-        match self.0.coords.get(i) {
-            Some(v) => Ok(v.clone()),
-            None => Err(PyIndexError::new_err(idx)),
-        }
+    #[getter]
+    fn get_i(&self) -> f64 {
+        self.0.i
+    }
+
+    #[getter]
+    fn get_j(&self) -> f64 {
+        self.0.j
+    }
+
+    #[getter]
+    fn get_k(&self) -> f64 {
+        self.0.k
+    }
+
+    #[getter]
+    fn get_w(&self) -> f64 {
+        self.0.w
+    }
+
+    /// Returns a component for an integer index, or a tuple of components for
+    /// a slice, in (i, j, k, w) order.
+    fn __getitem__(&self, py: Python, idx: &PyAny) -> PyResult<PyObject> {
+        seq::get_item(py, self.0.coords.as_slice(), idx)
+    }
+
+    fn __iter__(&self) -> ComponentIter {
+        ComponentIter::new(self.0.coords.as_slice())
+    }
+
+    fn __reversed__(&self) -> ComponentIter {
+        ComponentIter::new_reversed(self.0.coords.as_slice())
+    }
+
+    fn __contains__(&self, value: &PyAny) -> bool {
+        seq::contains(self.0.coords.as_slice(), value)
+    }
+
+    #[classattr]
+    fn __match_args__() -> (&'static str, &'static str, &'static str, &'static str) {
+        ("i", "j", "k", "w")
     }
 
     // fn __setitem__(&mut self, idx: isize, value: f64) -> Result<(), PyErr> {
@@ -64,9 +99,6 @@ impl UnitQuaternion {
     //     Ok(())
     // }
 
-    #[classattr]
-    const __contains__: Option<PyObject> = None;
-
     fn __richcmp__(&self, py: Python, other: &UnitQuaternion, op: CompareOp) -> Py<PyAny> {
         match op {
             CompareOp::Eq => (self.0 == other.0).into_py(py),
@@ -75,8 +107,20 @@ impl UnitQuaternion {
         }
     }
 
-    fn approx_equals(&self, v: &UnitQuaternion) -> bool {
-        self.0.abs_diff_eq(&v.0, 1e-08)
+    /// Compares the components of both quaternions. If `as_rotation` is true
+    /// `q` and `-q` are also considered equal, as they represent the same rotation.
+    #[pyo3(signature = (v, *, abs_tol=None, rel_tol=None, max_ulps=None, as_rotation=false))]
+    fn approx_equals(
+        &self,
+        v: &UnitQuaternion,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+        as_rotation: bool,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        let (a, b) = (&self.0.coords, &v.0.coords);
+        Ok(tol.eq(a, b) || (as_rotation && tol.eq(a, &-b)))
     }
 
     #[staticmethod]
@@ -158,6 +202,11 @@ impl UnitQuaternion {
         self.0.euler_angles()
     }
 
+    /// Returns an immutable, hashable copy of this rotation.
+    fn freeze(&self) -> FrozenUnitQuaternion {
+        FrozenUnitQuaternion(self.0)
+    }
+
     fn tuple(&self) -> (f64, f64, f64, f64) {
         (self.0[0], self.0[1], self.0[2], self.0[3])
     }
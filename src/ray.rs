@@ -0,0 +1,170 @@
+use crate::aabb::Aabb;
+use crate::intersect;
+use crate::plane::Plane;
+use crate::shape::{self, Transform};
+use crate::sphere::Sphere;
+use crate::tolerance::Tolerance;
+use crate::triangle::Triangle;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
+
+/// A half-line starting at `origin` and extending forever along `direction`,
+/// which is always unit length so that distances along the ray are lengths.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct Ray {
+    pub origin: na::Vector3<f64>,
+    pub direction: na::Unit<na::Vector3<f64>>,
+}
+
+impl Ray {
+    pub fn point_at(&self, t: f64) -> na::Vector3<f64> {
+        self.origin + *self.direction * t
+    }
+}
+
+#[pymethods]
+impl Ray {
+    /// Creates a ray from its origin and direction, which is normalized.
+    #[new]
+    fn new(origin: &Vector3, direction: &Vector3) -> PyResult<Ray> {
+        Ok(Ray {
+            origin: origin.0,
+            direction: shape::unit(direction.0, "Ray direction")?,
+        })
+    }
+
+    /// Creates the ray starting at `origin` which passes through `target`.
+    #[staticmethod]
+    fn from_points(origin: &Vector3, target: &Vector3) -> PyResult<Ray> {
+        Ok(Ray {
+            origin: origin.0,
+            direction: shape::unit(target.0 - origin.0, "Ray direction")?,
+        })
+    }
+
+    #[getter]
+    fn get_origin(&self) -> Vector3 {
+        Vector3(self.origin)
+    }
+
+    #[getter]
+    fn get_direction(&self) -> Vector3 {
+        Vector3(*self.direction)
+    }
+
+    /// Returns the point `t` along the ray from its origin.
+    fn at(&self, t: f64) -> Vector3 {
+        Vector3(self.point_at(t))
+    }
+
+    /// Returns where the ray first hits `shape`, which may be a Plane,
+    /// Triangle, Sphere or Aabb, as a `RayHit(distance, point, normal,
+    /// barycentric)`. Returns None if the ray misses, or only hits further
+    /// than `max_distance` along it.
+    ///
+    /// The normal faces back against the ray. `barycentric` is None except
+    /// for triangles, where it's the `(u, v)` accepted by `Triangle.at`.
+    #[pyo3(signature = (shape, max_distance=None))]
+    fn intersect(
+        &self,
+        py: Python,
+        shape: &PyAny,
+        max_distance: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let hit = if let Ok(plane) = shape.extract::<PyRef<Plane>>() {
+            intersect::ray_plane(self, &plane)
+        } else if let Ok(tri) = shape.extract::<PyRef<Triangle>>() {
+            intersect::ray_triangle(self, &tri)
+        } else if let Ok(sphere) = shape.extract::<PyRef<Sphere>>() {
+            intersect::ray_sphere(self, &sphere)
+        } else if let Ok(aabb) = shape.extract::<PyRef<Aabb>>() {
+            intersect::ray_aabb(self, &aabb)
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "Cannot intersect a Ray with {}",
+                shape.get_type().name().unwrap_or("?")
+            )));
+        };
+        match hit {
+            Some(hit) if hit.distance <= max_distance.unwrap_or(f64::INFINITY) => {
+                hit.into_result(py)
+            }
+            _ => Ok(py.None()),
+        }
+    }
+
+    /// Returns the closest points between this ray and `other` as a
+    /// `ClosestPoints(point, other_point, distance)`, where `point` is on this
+    /// ray. Parallel rays have many closest points, of which one is returned.
+    fn closest_points(&self, py: Python, other: &Ray) -> PyResult<PyObject> {
+        let (s, t) = intersect::closest_parameters(
+            &self.origin,
+            &self.direction,
+            f64::INFINITY,
+            &other.origin,
+            &other.direction,
+            f64::INFINITY,
+        );
+        intersect::closest_points_result(py, self.point_at(s), other.point_at(t))
+    }
+
+    /// Returns the closest point on the ray to `point`.
+    fn closest_point(&self, point: &Vector3) -> Vector3 {
+        let t = self.direction.dot(&(point.0 - self.origin)).max(0.0);
+        Vector3(self.point_at(t))
+    }
+
+    fn transformed(&self, arg: &PyAny) -> PyResult<Ray> {
+        let t = Transform::extract(arg, "Ray")?;
+        Ok(Ray {
+            origin: t.point(&self.origin),
+            direction: shape::unit(t.vector(&self.direction), "Ray direction")?,
+        })
+    }
+
+    fn transform(&mut self, arg: &PyAny) -> PyResult<()> {
+        *self = self.transformed(arg)?;
+        Ok(())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Ray, op: CompareOp) -> Py<PyAny> {
+        let eq = self.origin == other.origin && self.direction == other.direction;
+        match op {
+            CompareOp::Eq => eq.into_py(py),
+            CompareOp::Ne => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &Ray,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        Ok(tol.eq(&self.origin, &other.origin) && tol.eq(&*self.direction, &*other.direction))
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (Vector3, Vector3)) {
+        (
+            py.get_type::<Ray>().into(),
+            (self.get_origin(), self.get_direction()),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Ray(origin={}, direction={})",
+            shape::repr(&self.origin),
+            shape::repr(&self.direction)
+        )
+    }
+}
@@ -0,0 +1,377 @@
+use crate::iso::Isometry3;
+use crate::kdtree::Tree;
+use crate::points::{self, extract_finite_points, extract_points};
+use crate::results::NamedTuple;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+type V3 = na::Vector3<f64>;
+
+static ICP_RESULT: NamedTuple = NamedTuple::new(
+    "IcpResult",
+    &[
+        "transform",
+        "fitness",
+        "rmse",
+        "correspondences",
+        "iterations",
+        "converged",
+    ],
+);
+
+/// The rigid transform which best maps each `source` point onto the matching
+/// `target` point in the (weighted) least-squares sense, found with the
+/// Kabsch algorithm.
+pub fn kabsch(source: &[V3], target: &[V3], weights: Option<&[f64]>) -> na::Isometry3<f64> {
+    let weight = |i: usize| weights.map_or(1.0, |w| w[i]);
+    let total: f64 = (0..source.len()).map(weight).sum();
+    let centroid =
+        |points: &[V3]| (0..points.len()).map(|i| points[i] * weight(i)).sum::<V3>() / total;
+    let (source_centroid, target_centroid) = (centroid(source), centroid(target));
+    let covariance: na::Matrix3<f64> = (0..source.len())
+        .map(|i| {
+            (source[i] - source_centroid) * (target[i] - target_centroid).transpose() * weight(i)
+        })
+        .sum();
+    let svd = covariance.svd(true, true);
+    let (u, v) = (svd.u.unwrap(), svd.v_t.unwrap().transpose());
+    // Flip the least significant axis if needed so the result is a rotation
+    // rather than a reflection
+    let d = (v * u.transpose()).determinant().signum();
+    let rotation = v * na::Matrix3::from_diagonal(&V3::new(1.0, 1.0, d)) * u.transpose();
+    let rotation =
+        na::UnitQuaternion::from_rotation_matrix(&na::Rotation3::from_matrix_unchecked(rotation));
+    let translation = target_centroid - rotation * source_centroid;
+    na::Isometry3::from_parts(translation.into(), rotation)
+}
+
+/// Estimates the surface normal at each point from the plane best fitting its
+/// `k` nearest neighbours. Normals are unit length, but their sign is
+/// arbitrary.
+pub fn estimate(points: &[V3], tree: &Tree, k: usize) -> Vec<V3> {
+    points
+        .iter()
+        .map(|p| {
+            let neighbours: Vec<V3> = tree
+                .k_nearest(p, k)
+                .into_iter()
+                .map(|(i, _)| points[i])
+                .collect();
+            let (_, covariance) = points::mean_and_covariance(&neighbours);
+            let (axes, _) = points::principal_axes(covariance);
+            axes.matrix().column(2).into_owned()
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Method {
+    PointToPoint,
+    PointToPlane,
+}
+
+struct Correspondences {
+    pairs: Vec<(usize, usize)>,
+    sum_squared: f64,
+}
+
+/// Pairs each transformed source point with its nearest target point, unless
+/// that is further than `max_distance`.
+fn correspond(
+    source: &[V3],
+    transform: &na::Isometry3<f64>,
+    tree: &Tree,
+    max_distance: f64,
+) -> Correspondences {
+    let mut pairs = Vec::new();
+    let mut sum_squared = 0.0;
+    for (i, p) in source.iter().enumerate() {
+        let (j, distance_squared) = tree
+            .nearest(&transform.transform_point(&(*p).into()).coords)
+            .expect("the target is not empty");
+        if distance_squared <= max_distance * max_distance {
+            pairs.push((i, j));
+            sum_squared += distance_squared;
+        }
+    }
+    Correspondences { pairs, sum_squared }
+}
+
+/// The small rigid motion which best moves the (already transformed) source
+/// points onto the tangent planes of their target points, linearising the
+/// rotation. Directions the planes don't constrain are left unchanged.
+fn point_to_plane_step(
+    source: &[V3],
+    target: &[V3],
+    normals: &[V3],
+    pairs: &[(usize, usize)],
+) -> na::Isometry3<f64> {
+    let mut ata = na::Matrix6::<f64>::zeros();
+    let mut atb = na::Vector6::<f64>::zeros();
+    for (i, j) in pairs {
+        let (p, q, n) = (source[*i], target[*j], normals[*j]);
+        let row = na::Vector6::from_iterator(p.cross(&n).iter().chain(n.iter()).copied());
+        ata += row * row.transpose();
+        atb -= row * (p - q).dot(&n);
+    }
+    let x = ata
+        .svd(true, true)
+        .solve(&atb, 1e-12)
+        .unwrap_or_else(|_| na::Vector6::zeros());
+    na::Isometry3::from_parts(
+        V3::new(x[3], x[4], x[5]).into(),
+        na::UnitQuaternion::from_scaled_axis(V3::new(x[0], x[1], x[2])),
+    )
+}
+
+struct Icp {
+    transform: na::Isometry3<f64>,
+    correspondences: Correspondences,
+    iterations: usize,
+    converged: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_icp(
+    source: &[V3],
+    target: &[V3],
+    normals: Option<&[V3]>,
+    tree: &Tree,
+    initial: na::Isometry3<f64>,
+    max_iterations: usize,
+    max_distance: f64,
+    tolerance: f64,
+) -> Icp {
+    let mut transform = initial;
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iterations {
+        let correspondences = correspond(source, &transform, tree, max_distance);
+        if correspondences.pairs.is_empty() {
+            break;
+        }
+        iterations += 1;
+        let step = match normals {
+            None => {
+                let (from, to): (Vec<V3>, Vec<V3>) = correspondences
+                    .pairs
+                    .iter()
+                    .map(|(i, j)| {
+                        (
+                            transform.transform_point(&source[*i].into()).coords,
+                            target[*j],
+                        )
+                    })
+                    .unzip();
+                kabsch(&from, &to, None)
+            }
+            Some(normals) => {
+                let moved: Vec<V3> = source
+                    .iter()
+                    .map(|p| transform.transform_point(&(*p).into()).coords)
+                    .collect();
+                point_to_plane_step(&moved, target, normals, &correspondences.pairs)
+            }
+        };
+        transform = step * transform;
+        if step.translation.vector.magnitude() < tolerance && step.rotation.angle() < tolerance {
+            converged = true;
+            break;
+        }
+    }
+    Icp {
+        correspondences: correspond(source, &transform, tree, max_distance),
+        transform,
+        iterations,
+        converged,
+    }
+}
+
+/// Returns the Isometry3 which best maps each of the `source` points onto the
+/// `target` point at the same index, minimising the (optionally weighted) sum
+/// of squared distances between them.
+///
+/// Points may be given as lists of Vector3s, lists of 3 element sequences or
+/// N x 3 arrays, and must be finite. With fewer than three non-collinear
+/// points the rotation is not unique, and one of the best rotations is
+/// returned.
+#[pyfunction]
+pub fn align_points(
+    py: Python,
+    source: &PyAny,
+    target: &PyAny,
+    weights: Option<Vec<f64>>,
+) -> PyResult<Isometry3> {
+    let source = extract_finite_points(py, source)?;
+    let target = extract_finite_points(py, target)?;
+    if target.len() != source.len() {
+        return Err(PyValueError::new_err(format!(
+            "Expected {} target points but got {}",
+            source.len(),
+            target.len()
+        )));
+    }
+    if let Some(weights) = &weights {
+        if weights.len() != source.len() {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} weights but got {}",
+                source.len(),
+                weights.len()
+            )));
+        }
+        if weights.iter().any(|w| *w < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+            return Err(PyValueError::new_err(
+                "weights must be non-negative and not all zero",
+            ));
+        }
+    }
+    Ok(Isometry3(kabsch(&source, &target, weights.as_deref())))
+}
+
+/// Estimates a unit surface normal for each point from the plane through its
+/// `k` nearest neighbours (including itself). The sign of each normal is
+/// arbitrary. Raises a ValueError if there are no points or any isn't finite.
+#[pyfunction]
+#[pyo3(signature = (points, k=10))]
+pub fn estimate_normals(py: Python, points: &PyAny, k: usize) -> PyResult<Vec<Vector3>> {
+    if k < 3 {
+        return Err(PyValueError::new_err(
+            "k must be at least 3 to estimate normals",
+        ));
+    }
+    let points = extract_finite_points(py, points)?;
+    let normals = py.allow_threads(|| {
+        let tree = Tree::new(points.clone());
+        estimate(&points, &tree, k)
+    });
+    Ok(normals.into_iter().map(Vector3).collect())
+}
+
+/// Registers `source` onto `target` with the Iterative Closest Point
+/// algorithm, returning an `IcpResult(transform, fitness, rmse,
+/// correspondences, iterations, converged)`.
+///
+/// Starting from `initial` (the identity by default), each iteration pairs
+/// every transformed source point with its nearest target point and then
+/// moves the source to best fit those pairs. Pairs further apart than
+/// `max_correspondence_distance` are rejected as outliers.
+///
+/// With `method="point_to_point"` the distances between paired points are
+/// minimised. With `method="point_to_plane"` the distances from the source
+/// points to the target's tangent planes are minimised instead, which
+/// converges in fewer iterations on smooth surfaces. The target normals are
+/// estimated from 10 neighbours unless `target_normals` is given.
+///
+/// Iteration stops after `max_iterations`, or once an iteration moves the
+/// source by less than `tolerance` in both translation and rotation angle
+/// (radians), when `converged` is True. In the result `transform` maps the
+/// source onto the target, `correspondences` lists the `(source_index,
+/// target_index)` pairs for that transform, `fitness` is the fraction of
+/// source points which have a pair and `rmse` is the root mean square
+/// distance between pairs (infinite if there are none). Raises a ValueError
+/// if either point set is empty or has a point which isn't finite.
+#[pyfunction]
+#[pyo3(signature = (
+    source,
+    target,
+    initial=None,
+    *,
+    method="point_to_point",
+    max_iterations=50,
+    max_correspondence_distance=f64::INFINITY,
+    tolerance=1e-6,
+    target_normals=None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn icp(
+    py: Python,
+    source: &PyAny,
+    target: &PyAny,
+    initial: Option<&Isometry3>,
+    method: &str,
+    max_iterations: usize,
+    max_correspondence_distance: f64,
+    tolerance: f64,
+    target_normals: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    let method = match method {
+        "point_to_point" => Method::PointToPoint,
+        "point_to_plane" => Method::PointToPlane,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "method must be 'point_to_point' or 'point_to_plane', not '{}'",
+                method
+            )))
+        }
+    };
+    if max_correspondence_distance.is_nan() || max_correspondence_distance <= 0.0 {
+        return Err(PyValueError::new_err(
+            "max_correspondence_distance must be a positive number",
+        ));
+    }
+    let source = extract_finite_points(py, source)?;
+    let target = extract_finite_points(py, target)?;
+    let mut normals = match target_normals {
+        Some(normals) => {
+            let normals: Vec<V3> = extract_points(py, normals)?
+                .into_iter()
+                .map(|n| n.normalize())
+                .collect();
+            if normals.len() != target.len() {
+                return Err(PyValueError::new_err(format!(
+                    "Expected {} target normals but got {}",
+                    target.len(),
+                    normals.len()
+                )));
+            }
+            Some(normals)
+        }
+        None => None,
+    };
+    if method == Method::PointToPlane && normals.is_none() && target.len() < 3 {
+        return Err(PyValueError::new_err(
+            "point_to_plane needs at least 3 target points to estimate normals",
+        ));
+    }
+    let initial = initial.map_or(na::Isometry3::identity(), |i| i.0);
+
+    let result = py.allow_threads(|| {
+        let tree = Tree::new(target.clone());
+        if method == Method::PointToPlane && normals.is_none() {
+            normals = Some(estimate(&target, &tree, 10));
+        }
+        let normals = match method {
+            Method::PointToPoint => None,
+            Method::PointToPlane => normals.as_deref(),
+        };
+        run_icp(
+            &source,
+            &target,
+            normals,
+            &tree,
+            initial,
+            max_iterations,
+            max_correspondence_distance,
+            tolerance,
+        )
+    });
+    let pairs = &result.correspondences.pairs;
+    let rmse = if pairs.is_empty() {
+        f64::INFINITY
+    } else {
+        (result.correspondences.sum_squared / pairs.len() as f64).sqrt()
+    };
+    ICP_RESULT.make(
+        py,
+        (
+            Isometry3(result.transform),
+            pairs.len() as f64 / source.len() as f64,
+            rmse,
+            pairs.clone(),
+            result.iterations,
+            result.converged,
+        ),
+    )
+}
@@ -0,0 +1,34 @@
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{IntoPyDict, PyTuple, PyType};
+
+/// A `collections.namedtuple` type, created the first time it is used, which
+/// methods return when they have several results. Callers can unpack these
+/// like plain tuples or access each result by name.
+pub struct NamedTuple {
+    name: &'static str,
+    fields: &'static [&'static str],
+    ty: GILOnceCell<Py<PyType>>,
+}
+
+impl NamedTuple {
+    pub const fn new(name: &'static str, fields: &'static [&'static str]) -> Self {
+        NamedTuple {
+            name,
+            fields,
+            ty: GILOnceCell::new(),
+        }
+    }
+
+    pub fn make(&self, py: Python, values: impl IntoPy<Py<PyTuple>>) -> PyResult<PyObject> {
+        let ty = self.ty.get_or_try_init(py, || -> PyResult<Py<PyType>> {
+            let kwargs = [("module", "deuterium")].into_py_dict(py);
+            let ty = py
+                .import("collections")?
+                .getattr("namedtuple")?
+                .call((self.name, self.fields.to_vec()), Some(kwargs))?;
+            Ok(ty.downcast::<PyType>()?.into())
+        })?;
+        Ok(ty.as_ref(py).call1(values)?.into())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::intersect;
+use crate::shape::{self, Transform};
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
+
+/// The finite straight line between two points.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct LineSegment {
+    pub start: na::Vector3<f64>,
+    pub end: na::Vector3<f64>,
+}
+
+#[pymethods]
+impl LineSegment {
+    #[new]
+    fn new(start: &Vector3, end: &Vector3) -> LineSegment {
+        LineSegment {
+            start: start.0,
+            end: end.0,
+        }
+    }
+
+    #[getter]
+    fn get_start(&self) -> Vector3 {
+        Vector3(self.start)
+    }
+
+    #[getter]
+    fn get_end(&self) -> Vector3 {
+        Vector3(self.end)
+    }
+
+    /// Returns the vector from `start` to `end`.
+    fn direction(&self) -> Vector3 {
+        Vector3(self.end - self.start)
+    }
+
+    fn length(&self) -> f64 {
+        (self.end - self.start).magnitude()
+    }
+
+    fn midpoint(&self) -> Vector3 {
+        Vector3(self.start.lerp(&self.end, 0.5))
+    }
+
+    /// Returns the point which is `t` of the way from `start` to `end`.
+    fn at(&self, t: f64) -> Vector3 {
+        Vector3(self.start.lerp(&self.end, t))
+    }
+
+    /// Returns the same segment running from `end` to `start`.
+    fn reversed(&self) -> LineSegment {
+        LineSegment {
+            start: self.end,
+            end: self.start,
+        }
+    }
+
+    /// Returns the closest points between this segment and `other` as a
+    /// `ClosestPoints(point, other_point, distance)`, where `point` is on this
+    /// segment. Parallel segments may have many closest points, of which one
+    /// is returned.
+    fn closest_points(&self, py: Python, other: &LineSegment) -> PyResult<PyObject> {
+        let (d1, d2) = (self.end - self.start, other.end - other.start);
+        let (s, t) = intersect::closest_parameters(&self.start, &d1, 1.0, &other.start, &d2, 1.0);
+        intersect::closest_points_result(py, self.start + d1 * s, other.start + d2 * t)
+    }
+
+    /// Returns the closest point on the segment to `point`.
+    fn closest_point(&self, point: &Vector3) -> Vector3 {
+        let d = self.end - self.start;
+        let length_squared = d.magnitude_squared();
+        if length_squared == 0.0 {
+            return Vector3(self.start);
+        }
+        let t = (d.dot(&(point.0 - self.start)) / length_squared).clamp(0.0, 1.0);
+        Vector3(self.start + d * t)
+    }
+
+    fn transformed(&self, arg: &PyAny) -> PyResult<LineSegment> {
+        let t = Transform::extract(arg, "LineSegment")?;
+        Ok(LineSegment {
+            start: t.point(&self.start),
+            end: t.point(&self.end),
+        })
+    }
+
+    fn transform(&mut self, arg: &PyAny) -> PyResult<()> {
+        *self = self.transformed(arg)?;
+        Ok(())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &LineSegment, op: CompareOp) -> Py<PyAny> {
+        let eq = self.start == other.start && self.end == other.end;
+        match op {
+            CompareOp::Eq => eq.into_py(py),
+            CompareOp::Ne => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &LineSegment,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        Ok(tol.eq(&self.start, &other.start) && tol.eq(&self.end, &other.end))
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (Vector3, Vector3)) {
+        (
+            py.get_type::<LineSegment>().into(),
+            (self.get_start(), self.get_end()),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LineSegment(start={}, end={})",
+            shape::repr(&self.start),
+            shape::repr(&self.end)
+        )
+    }
+}
@@ -0,0 +1,130 @@
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PySlice, PyTuple};
+
+/// Converts a Python index, which may be negative, into an index into a
+/// sequence of length `len`, raising an IndexError if it is out of range.
+pub fn normalize_index(idx: isize, len: usize) -> PyResult<usize> {
+    let i = if idx < 0 { idx + len as isize } else { idx };
+    if i < 0 || i >= len as isize {
+        return Err(PyIndexError::new_err(idx));
+    }
+    Ok(i as usize)
+}
+
+/// Returns the indices selected by a Python slice over a sequence of length `len`.
+pub fn slice_indices(slice: &PySlice, len: usize) -> PyResult<Vec<usize>> {
+    let ind = slice.indices(len as std::os::raw::c_long)?;
+    Ok((0..ind.slicelength)
+        .map(|n| (ind.start + n * ind.step) as usize)
+        .collect())
+}
+
+/// What a single Python index or slice selects from a sequence.
+pub enum Selection {
+    Index(usize),
+    Slice(Vec<usize>),
+}
+
+impl Selection {
+    /// Resolves an integer (which may be negative) or a slice against a
+    /// sequence of length `len`.
+    pub fn new(arg: &PyAny, len: usize) -> PyResult<Selection> {
+        if let Ok(slice) = arg.downcast::<PySlice>() {
+            return Ok(Selection::Slice(slice_indices(slice, len)?));
+        }
+        let idx: isize = arg.extract()?;
+        Ok(Selection::Index(normalize_index(idx, len)?))
+    }
+}
+
+/// Implements `__getitem__` for a fixed size sequence of floats, where an
+/// integer index returns a float and a slice returns a tuple.
+pub fn get_item(py: Python, values: &[f64], arg: &PyAny) -> PyResult<PyObject> {
+    if let Ok(slice) = arg.downcast::<PySlice>() {
+        let items = slice_indices(slice, values.len())?
+            .into_iter()
+            .map(|i| values[i]);
+        return Ok(PyTuple::new(py, items).into());
+    }
+    let idx: isize = arg.extract()?;
+    Ok(values[normalize_index(idx, values.len())?].to_object(py))
+}
+
+/// Implements `__setitem__` for a fixed size sequence of floats. Slices must be
+/// assigned a sequence of the same length, as the sequence can't be resized.
+pub fn set_item(values: &mut [f64], arg: &PyAny, value: &PyAny) -> PyResult<()> {
+    if let Ok(slice) = arg.downcast::<PySlice>() {
+        let indices = slice_indices(slice, values.len())?;
+        let new: Vec<f64> = value.extract()?;
+        if new.len() != indices.len() {
+            return Err(PyValueError::new_err(format!(
+                "Cannot assign {} values to a slice of length {}",
+                new.len(),
+                indices.len()
+            )));
+        }
+        for (i, v) in indices.into_iter().zip(new) {
+            values[i] = v;
+        }
+        return Ok(());
+    }
+    let idx: isize = arg.extract()?;
+    values[normalize_index(idx, values.len())?] = value.extract()?;
+    Ok(())
+}
+
+/// Whether any of `values` equals `value`. Like other sequences, anything
+/// which isn't a number is simply not contained.
+pub fn contains(values: &[f64], value: &PyAny) -> bool {
+    match value.extract::<f64>() {
+        Ok(value) => values.contains(&value),
+        Err(_) => false,
+    }
+}
+
+/// An iterator over a copy of up to four components.
+#[pyclass]
+pub struct ComponentIter {
+    values: [f64; 4],
+    len: usize,
+    next: usize,
+}
+
+impl ComponentIter {
+    pub fn new(values: &[f64]) -> Self {
+        let mut copy = [0.0; 4];
+        copy[..values.len()].copy_from_slice(values);
+        ComponentIter {
+            values: copy,
+            len: values.len(),
+            next: 0,
+        }
+    }
+
+    pub fn new_reversed(values: &[f64]) -> Self {
+        let mut iter = ComponentIter::new(values);
+        iter.values[..values.len()].reverse();
+        iter
+    }
+}
+
+#[pymethods]
+impl ComponentIter {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<f64> {
+        if self.next < self.len {
+            self.next += 1;
+            Some(self.values[self.next - 1])
+        } else {
+            None
+        }
+    }
+
+    fn __length_hint__(&self) -> usize {
+        self.len - self.next
+    }
+}
@@ -0,0 +1,115 @@
+use crate::iso::Isometry3;
+use crate::mat4::Matrix4;
+use crate::tolerance::Tolerance;
+use nalgebra as na;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+
+/// A transform which the geometric primitives can be transformed by, either
+/// an Isometry3 or an affine Matrix4.
+pub enum Transform {
+    Isometry(na::Isometry3<f64>),
+    Affine(na::Matrix4<f64>),
+}
+
+impl Transform {
+    /// Extracts an Isometry3 or Matrix4, raising a TypeError naming `shape` for
+    /// anything else. Matrices must be affine, as projections don't map shapes
+    /// onto shapes of the same kind.
+    pub fn extract(arg: &PyAny, shape: &str) -> PyResult<Transform> {
+        let isor: PyResult<PyRef<Isometry3>> = arg.extract();
+        if let Ok(iso) = isor {
+            return Ok(Transform::Isometry(iso.0));
+        }
+        let matr: PyResult<PyRef<Matrix4>> = arg.extract();
+        if let Ok(mat) = matr {
+            if mat.0.fixed_view::<1, 4>(3, 0) != na::RowVector4::new(0.0, 0.0, 0.0, 1.0) {
+                return Err(PyValueError::new_err(format!(
+                    "Cannot transform a {} by a projective Matrix4",
+                    shape
+                )));
+            }
+            return Ok(Transform::Affine(mat.0));
+        }
+        Err(PyTypeError::new_err(format!(
+            "Cannot transform a {} by {}",
+            shape,
+            arg.get_type().name().unwrap_or("?")
+        )))
+    }
+
+    /// The rotation, scaling and shearing part of the transform.
+    pub fn linear(&self) -> na::Matrix3<f64> {
+        match self {
+            Transform::Isometry(iso) => *iso.rotation.to_rotation_matrix().matrix(),
+            Transform::Affine(m) => m.fixed_view::<3, 3>(0, 0).into_owned(),
+        }
+    }
+
+    pub fn point(&self, p: &na::Vector3<f64>) -> na::Vector3<f64> {
+        match self {
+            Transform::Isometry(iso) => iso.transform_point(&na::Point3::from(*p)).coords,
+            Transform::Affine(m) => m.transform_point(&na::Point3::from(*p)).coords,
+        }
+    }
+
+    pub fn vector(&self, v: &na::Vector3<f64>) -> na::Vector3<f64> {
+        match self {
+            Transform::Isometry(iso) => iso.transform_vector(v),
+            Transform::Affine(m) => m.transform_vector(v),
+        }
+    }
+
+    /// Transforms a surface normal, which needs the inverse transpose of the
+    /// linear part to stay perpendicular to the surface under non-uniform
+    /// scaling. The result is not normalized.
+    pub fn normal(&self, n: &na::Vector3<f64>) -> PyResult<na::Vector3<f64>> {
+        match self {
+            Transform::Isometry(iso) => Ok(iso.transform_vector(n)),
+            Transform::Affine(_) => {
+                let inverse = self
+                    .linear()
+                    .try_inverse()
+                    .ok_or_else(|| PyValueError::new_err("Matrix4 is singular"))?;
+                Ok(inverse.transpose() * n)
+            }
+        }
+    }
+
+    /// The factor the transform scales every length by, raising a ValueError
+    /// if it scales different directions by different amounts.
+    pub fn uniform_scale(&self, shape: &str) -> PyResult<f64> {
+        match self {
+            Transform::Isometry(_) => Ok(1.0),
+            Transform::Affine(_) => {
+                let s = self.linear().singular_values();
+                let (max, min) = (s.max(), s.min());
+                if !Tolerance::current().eq(&max, &min) {
+                    return Err(PyValueError::new_err(format!(
+                        "Cannot transform a {} by a Matrix4 with non-uniform scaling",
+                        shape
+                    )));
+                }
+                Ok(max)
+            }
+        }
+    }
+}
+
+/// Normalizes `v`, raising a ValueError naming `what` if it has zero length.
+///
+/// Vectors which are already unit length to within rounding are kept as they
+/// are, so that shapes survive a round trip through their constructors (and
+/// so through pickling) unchanged.
+pub fn unit(v: na::Vector3<f64>, what: &str) -> PyResult<na::Unit<na::Vector3<f64>>> {
+    if (v.magnitude() - 1.0).abs() <= 4.0 * f64::EPSILON {
+        return Ok(na::Unit::new_unchecked(v));
+    }
+    na::Unit::try_new(v, 0.0)
+        .ok_or_else(|| PyValueError::new_err(format!("{} must have a non-zero length", what)))
+}
+
+/// Formats a vector as its Vector3 repr, for the primitives' reprs.
+pub fn repr(v: &na::Vector3<f64>) -> String {
+    format!("Vector3({}, {}, {})", v.x, v.y, v.z)
+}
@@ -0,0 +1,113 @@
+use crate::bounding;
+use crate::points::extract_finite_points;
+use crate::shape::{self, Transform};
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
+
+/// A solid ball of points within `radius` of `center`.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct Sphere {
+    pub center: na::Vector3<f64>,
+    pub radius: f64,
+}
+
+#[pymethods]
+impl Sphere {
+    #[new]
+    fn new(center: &Vector3, radius: f64) -> PyResult<Sphere> {
+        if radius.is_nan() || radius < 0.0 {
+            return Err(PyValueError::new_err(
+                "Sphere radius must be a non-negative number",
+            ));
+        }
+        Ok(Sphere {
+            center: center.0,
+            radius,
+        })
+    }
+
+    /// Returns the smallest sphere containing every point, which may be given
+    /// as a list of Vector3s, a list of 3 element sequences or an N x 3 array.
+    ///
+    /// Uses Welzl's algorithm, which finds the exact minimal sphere in
+    /// expected linear time. Raises a ValueError if any point isn't finite.
+    #[staticmethod]
+    fn from_points(py: Python, points: &PyAny) -> PyResult<Sphere> {
+        let ball = bounding::minimal_ball(&extract_finite_points(py, points)?);
+        Ok(Sphere {
+            center: ball.center,
+            radius: ball.radius,
+        })
+    }
+
+    #[getter]
+    fn get_center(&self) -> Vector3 {
+        Vector3(self.center)
+    }
+
+    #[getter]
+    fn get_radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn contains_point(&self, point: &Vector3) -> bool {
+        (point.0 - self.center).magnitude_squared() <= self.radius * self.radius
+    }
+
+    /// Transforms the sphere, raising a ValueError for a Matrix4 which scales
+    /// non-uniformly, as that would turn the sphere into an ellipsoid.
+    fn transformed(&self, arg: &PyAny) -> PyResult<Sphere> {
+        let t = Transform::extract(arg, "Sphere")?;
+        Ok(Sphere {
+            center: t.point(&self.center),
+            radius: self.radius * t.uniform_scale("Sphere")?,
+        })
+    }
+
+    fn transform(&mut self, arg: &PyAny) -> PyResult<()> {
+        *self = self.transformed(arg)?;
+        Ok(())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Sphere, op: CompareOp) -> Py<PyAny> {
+        let eq = self.center == other.center && self.radius == other.radius;
+        match op {
+            CompareOp::Eq => eq.into_py(py),
+            CompareOp::Ne => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &Sphere,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        Ok(tol.eq(&self.center, &other.center) && tol.eq(&self.radius, &other.radius))
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (Vector3, f64)) {
+        (
+            py.get_type::<Sphere>().into(),
+            (self.get_center(), self.radius),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Sphere(center={}, radius={})",
+            shape::repr(&self.center),
+            self.radius
+        )
+    }
+}
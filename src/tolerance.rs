@@ -0,0 +1,121 @@
+use approx::{RelativeEq, UlpsEq};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cell::Cell;
+
+/// The tolerances used by every `approx_equals` method.
+///
+/// Two values are considered approximately equal if every component is within
+/// `abs_tol` of the other, within `rel_tol` relative to the larger magnitude of
+/// the two, or within `max_ulps` representable floats of the other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    pub abs_tol: f64,
+    pub rel_tol: f64,
+    pub max_ulps: u32,
+}
+
+const DEFAULT_TOLERANCE: Tolerance = Tolerance {
+    abs_tol: 1e-08,
+    rel_tol: 0.0,
+    max_ulps: 0,
+};
+
+thread_local! {
+    static CURRENT: Cell<Tolerance> = const { Cell::new(DEFAULT_TOLERANCE) };
+}
+
+impl Tolerance {
+    /// The defaults for the current thread, as set by `deuterium.tolerance(...)`.
+    pub fn current() -> Tolerance {
+        CURRENT.with(|c| c.get())
+    }
+
+    /// Overrides the current thread's defaults with any tolerances given explicitly.
+    pub fn resolve(
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<Tolerance> {
+        let current = Tolerance::current();
+        let tol = Tolerance {
+            abs_tol: abs_tol.unwrap_or(current.abs_tol),
+            rel_tol: rel_tol.unwrap_or(current.rel_tol),
+            max_ulps: max_ulps.unwrap_or(current.max_ulps),
+        };
+        let valid = |t: f64| !t.is_nan() && t >= 0.0;
+        if !valid(tol.abs_tol) || !valid(tol.rel_tol) {
+            return Err(PyValueError::new_err(
+                "Tolerances must be non-negative numbers",
+            ));
+        }
+        Ok(tol)
+    }
+
+    pub fn eq<T>(&self, a: &T, b: &T) -> bool
+    where
+        T: RelativeEq<Epsilon = f64> + UlpsEq<Epsilon = f64>,
+    {
+        a.relative_eq(b, self.abs_tol, self.rel_tol)
+            || (self.max_ulps > 0 && a.ulps_eq(b, self.abs_tol, self.max_ulps))
+    }
+}
+
+/// A context manager which sets the default tolerances used by `approx_equals`
+/// for the current thread. Tolerances which aren't given are inherited from
+/// the defaults active when the context is entered.
+#[pyclass(name = "tolerance")]
+pub struct ToleranceContext {
+    abs_tol: Option<f64>,
+    rel_tol: Option<f64>,
+    max_ulps: Option<u32>,
+    previous: Vec<Tolerance>,
+}
+
+#[pymethods]
+impl ToleranceContext {
+    #[new]
+    #[pyo3(signature = (abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn new(abs_tol: Option<f64>, rel_tol: Option<f64>, max_ulps: Option<u32>) -> PyResult<Self> {
+        Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        Ok(ToleranceContext {
+            abs_tol,
+            rel_tol,
+            max_ulps,
+            previous: Vec::new(),
+        })
+    }
+
+    fn __enter__(mut slf: PyRefMut<Self>) -> PyResult<PyRefMut<Self>> {
+        let tol = Tolerance::resolve(slf.abs_tol, slf.rel_tol, slf.max_ulps)?;
+        slf.previous.push(Tolerance::current());
+        CURRENT.with(|c| c.set(tol));
+        Ok(slf)
+    }
+
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        if let Some(previous) = self.previous.pop() {
+            CURRENT.with(|c| c.set(previous));
+        }
+        false
+    }
+
+    /// Returns the current thread's default (abs_tol, rel_tol, max_ulps).
+    #[staticmethod]
+    fn current() -> (f64, f64, u32) {
+        let tol = Tolerance::current();
+        (tol.abs_tol, tol.rel_tol, tol.max_ulps)
+    }
+
+    fn __repr__(&self) -> String {
+        fn opt<T: ToString>(v: Option<T>) -> String {
+            v.map_or("None".to_string(), |v| v.to_string())
+        }
+        format!(
+            "tolerance(abs_tol={}, rel_tol={}, max_ulps={})",
+            opt(self.abs_tol),
+            opt(self.rel_tol),
+            opt(self.max_ulps)
+        )
+    }
+}
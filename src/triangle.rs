@@ -0,0 +1,141 @@
+use crate::intersect;
+use crate::shape::{self, Transform};
+use crate::tolerance::Tolerance;
+use crate::vec3::Vector3;
+use nalgebra as na;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
+
+/// A triangle with vertices `a`, `b` and `c`. Its front face is the side from
+/// which the vertices appear counter-clockwise.
+#[pyclass(module = "deuterium")]
+#[derive(Clone)]
+pub struct Triangle {
+    pub a: na::Vector3<f64>,
+    pub b: na::Vector3<f64>,
+    pub c: na::Vector3<f64>,
+}
+
+impl Triangle {
+    /// The cross product of two edges, whose length is twice the area.
+    pub fn scaled_normal(&self) -> na::Vector3<f64> {
+        (self.b - self.a).cross(&(self.c - self.a))
+    }
+}
+
+#[pymethods]
+impl Triangle {
+    #[new]
+    fn new(a: &Vector3, b: &Vector3, c: &Vector3) -> Triangle {
+        Triangle {
+            a: a.0,
+            b: b.0,
+            c: c.0,
+        }
+    }
+
+    #[getter]
+    fn get_a(&self) -> Vector3 {
+        Vector3(self.a)
+    }
+
+    #[getter]
+    fn get_b(&self) -> Vector3 {
+        Vector3(self.b)
+    }
+
+    #[getter]
+    fn get_c(&self) -> Vector3 {
+        Vector3(self.c)
+    }
+
+    /// Returns the unit normal of the front face, raising a ValueError if the
+    /// triangle is degenerate.
+    fn normal(&self) -> PyResult<Vector3> {
+        Ok(Vector3(*shape::unit(
+            self.scaled_normal(),
+            "Triangle normal",
+        )?))
+    }
+
+    fn area(&self) -> f64 {
+        self.scaled_normal().magnitude() * 0.5
+    }
+
+    fn centroid(&self) -> Vector3 {
+        Vector3((self.a + self.b + self.c) / 3.0)
+    }
+
+    /// Returns the point with the given barycentric coordinates, which are
+    /// the weights of `b` and `c` (the weight of `a` being `1 - u - v`).
+    fn at(&self, u: f64, v: f64) -> Vector3 {
+        Vector3(self.a * (1.0 - u - v) + self.b * u + self.c * v)
+    }
+
+    fn vertices(&self) -> (Vector3, Vector3, Vector3) {
+        (Vector3(self.a), Vector3(self.b), Vector3(self.c))
+    }
+
+    /// Returns the closest point on or inside the triangle to `point`.
+    fn closest_point(&self, point: &Vector3) -> Vector3 {
+        Vector3(intersect::closest_point_on_triangle(&point.0, self))
+    }
+
+    /// Returns the same triangle with its front face on the other side.
+    fn flipped(&self) -> Triangle {
+        Triangle {
+            a: self.a,
+            b: self.c,
+            c: self.b,
+        }
+    }
+
+    fn transformed(&self, arg: &PyAny) -> PyResult<Triangle> {
+        let t = Transform::extract(arg, "Triangle")?;
+        Ok(Triangle {
+            a: t.point(&self.a),
+            b: t.point(&self.b),
+            c: t.point(&self.c),
+        })
+    }
+
+    fn transform(&mut self, arg: &PyAny) -> PyResult<()> {
+        *self = self.transformed(arg)?;
+        Ok(())
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Triangle, op: CompareOp) -> Py<PyAny> {
+        let eq = self.a == other.a && self.b == other.b && self.c == other.c;
+        match op {
+            CompareOp::Eq => eq.into_py(py),
+            CompareOp::Ne => (!eq).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &Triangle,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        let tol = Tolerance::resolve(abs_tol, rel_tol, max_ulps)?;
+        Ok(tol.eq(&self.a, &other.a) && tol.eq(&self.b, &other.b) && tol.eq(&self.c, &other.c))
+    }
+
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (Vector3, Vector3, Vector3)) {
+        (py.get_type::<Triangle>().into(), self.vertices())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Triangle(a={}, b={}, c={})",
+            shape::repr(&self.a),
+            shape::repr(&self.b),
+            shape::repr(&self.c)
+        )
+    }
+}
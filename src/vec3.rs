@@ -1,16 +1,19 @@
+use crate::frozen::FrozenVector3;
 use crate::iso::Isometry3;
 use crate::mat4::Matrix4;
 use crate::quat::UnitQuaternion;
-use approx::AbsDiffEq;
+use crate::seq::{self, ComponentIter};
+use crate::tolerance::Tolerance;
 use nalgebra as na;
-use pyo3::exceptions::{PyIndexError, PyTypeError};
+use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 use pyo3::pyclass::CompareOp;
+use pyo3::types::PyType;
 // use pyo3::types::PySequence;
 
 use crate::mat4;
 
-#[pyclass]
+#[pyclass(module = "deuterium")]
 #[derive(Clone)]
 pub struct Vector3(pub na::Vector3<f64>);
 
@@ -41,6 +44,7 @@ impl Vector3 {
 }
 
 #[pymethods]
+#[allow(clippy::needless_return, clippy::unused_unit)]
 impl Vector3 {
     #[new]
     fn new(x: Option<f64>, y: Option<f64>, z: Option<f64>) -> Self {
@@ -91,35 +95,38 @@ impl Vector3 {
         self.0.z = arg;
     }
 
-    fn __getitem__(&self, idx: isize) -> Result<f64, PyErr> {
-        let i: usize = if idx < 0 && idx > -(self.0.len() as isize + 1) {
-            (self.0.len() as isize + idx) as usize
-        } else {
-            match TryInto::<usize>::try_into(idx) {
-                Err(_) => Err(PyIndexError::new_err(idx))?,
-                Ok(ui) => ui,
-            }
-        };
-        match self.0.get(i) {
-            Some(v) => Ok(v.clone()),
-            None => Err(PyIndexError::new_err(idx)),
-        }
+    /// Returns a component for an integer index, or a tuple of components for a slice.
+    fn __getitem__(&self, py: Python, idx: &PyAny) -> PyResult<PyObject> {
+        seq::get_item(py, self.0.as_slice(), idx)
     }
 
-    fn __setitem__(&mut self, idx: isize, value: f64) -> Result<(), PyErr> {
-        let i: usize = if idx < 0 && idx > -(self.0.len() as isize + 1) {
-            self.0.len() - idx as usize
-        } else {
-            let ui: usize = idx.try_into()?;
-            ui
-        };
+    /// Sets a component for an integer index, or several components from a
+    /// sequence of the same length for a slice.
+    fn __setitem__(&mut self, idx: &PyAny, value: &PyAny) -> PyResult<()> {
+        seq::set_item(self.0.as_mut_slice(), idx, value)
+    }
 
-        self.0[i] = value;
-        Ok(())
+    fn __iter__(&self) -> ComponentIter {
+        ComponentIter::new(self.0.as_slice())
+    }
+
+    fn __reversed__(&self) -> ComponentIter {
+        ComponentIter::new_reversed(self.0.as_slice())
+    }
+
+    fn __contains__(&self, value: &PyAny) -> bool {
+        seq::contains(self.0.as_slice(), value)
+    }
+
+    /// A Vector3 is falsy if it is the zero vector.
+    fn __bool__(&self) -> bool {
+        self.0.iter().any(|c| *c != 0.0)
     }
 
     #[classattr]
-    const __contains__: Option<PyObject> = None;
+    fn __match_args__() -> (&'static str, &'static str, &'static str) {
+        ("x", "y", "z")
+    }
 
     fn __richcmp__(&self, py: Python, other: &Vector3, op: CompareOp) -> Py<PyAny> {
         match op {
@@ -129,8 +136,15 @@ impl Vector3 {
         }
     }
 
-    fn approx_equals(&self, other: &Vector3) -> bool {
-        self.0.abs_diff_eq(&other.0, 1e-08)
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &Vector3,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &other.0))
     }
 
     fn __add__(&self, other: &Vector3) -> Vector3 {
@@ -292,6 +306,11 @@ impl Vector3 {
         self.0 = -self.0;
     }
 
+    /// Returns an immutable, hashable copy of this vector.
+    fn freeze(&self) -> FrozenVector3 {
+        FrozenVector3(self.0)
+    }
+
     fn tuple(&self) -> (f64, f64, f64) {
         return (self.0[0], self.0[1], self.0[2]);
     }
@@ -300,6 +319,10 @@ impl Vector3 {
         return [self.0[0], self.0[1], self.0[2]];
     }
 
+    fn __reduce__(&self, py: Python) -> (Py<PyType>, (f64, f64, f64)) {
+        (py.get_type::<Vector3>().into(), self.tuple())
+    }
+
     fn __repr__(&self) -> String {
         format!("Vector3({}, {}, {})", self.0[0], self.0[1], self.0[2])
     }
@@ -0,0 +1,201 @@
+use crate::seq::{self, ComponentIter};
+use crate::tolerance::Tolerance;
+use nalgebra as na;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+
+#[pyclass]
+#[derive(Clone)]
+pub struct Vector4(pub na::Vector4<f64>);
+
+#[pymethods]
+impl Vector4 {
+    #[new]
+    fn new(x: Option<f64>, y: Option<f64>, z: Option<f64>, w: Option<f64>) -> Self {
+        Vector4(na::Vector4::new(
+            x.unwrap_or(0.0),
+            y.unwrap_or(0.0),
+            z.unwrap_or(0.0),
+            w.unwrap_or(0.0),
+        ))
+    }
+
+    #[getter]
+    fn get_x(&self) -> f64 {
+        self.0.x
+    }
+
+    #[setter]
+    fn set_x(&mut self, arg: f64) {
+        self.0.x = arg;
+    }
+
+    #[getter]
+    fn get_y(&self) -> f64 {
+        self.0.y
+    }
+
+    #[setter]
+    fn set_y(&mut self, arg: f64) {
+        self.0.y = arg;
+    }
+
+    #[getter]
+    fn get_z(&self) -> f64 {
+        self.0.z
+    }
+
+    #[setter]
+    fn set_z(&mut self, arg: f64) {
+        self.0.z = arg;
+    }
+
+    #[getter]
+    fn get_w(&self) -> f64 {
+        self.0.w
+    }
+
+    #[setter]
+    fn set_w(&mut self, arg: f64) {
+        self.0.w = arg;
+    }
+
+    fn __getitem__(&self, py: Python, idx: &PyAny) -> PyResult<PyObject> {
+        seq::get_item(py, self.0.as_slice(), idx)
+    }
+
+    fn __setitem__(&mut self, idx: &PyAny, value: &PyAny) -> PyResult<()> {
+        seq::set_item(self.0.as_mut_slice(), idx, value)
+    }
+
+    fn __iter__(&self) -> ComponentIter {
+        ComponentIter::new(self.0.as_slice())
+    }
+
+    fn __reversed__(&self) -> ComponentIter {
+        ComponentIter::new_reversed(self.0.as_slice())
+    }
+
+    fn __contains__(&self, value: &PyAny) -> bool {
+        seq::contains(self.0.as_slice(), value)
+    }
+
+    /// A Vector4 is falsy if it is the zero vector.
+    fn __bool__(&self) -> bool {
+        self.0.iter().any(|c| *c != 0.0)
+    }
+
+    #[classattr]
+    fn __match_args__() -> (&'static str, &'static str, &'static str, &'static str) {
+        ("x", "y", "z", "w")
+    }
+
+    #[staticmethod]
+    fn __len__() -> usize {
+        4
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Vector4, op: CompareOp) -> Py<PyAny> {
+        match op {
+            CompareOp::Eq => (self.0 == other.0).into_py(py),
+            CompareOp::Ne => (self.0 != other.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    #[pyo3(signature = (other, *, abs_tol=None, rel_tol=None, max_ulps=None))]
+    fn approx_equals(
+        &self,
+        other: &Vector4,
+        abs_tol: Option<f64>,
+        rel_tol: Option<f64>,
+        max_ulps: Option<u32>,
+    ) -> PyResult<bool> {
+        Ok(Tolerance::resolve(abs_tol, rel_tol, max_ulps)?.eq(&self.0, &other.0))
+    }
+
+    fn __add__(&self, other: &Vector4) -> Vector4 {
+        Vector4(self.0 + other.0)
+    }
+
+    fn __sub__(&self, other: &Vector4) -> Vector4 {
+        Vector4(self.0 - other.0)
+    }
+
+    fn __iadd__(&mut self, other: &Vector4) {
+        self.0 += other.0;
+    }
+
+    fn __isub__(&mut self, other: &Vector4) {
+        self.0 -= other.0;
+    }
+
+    fn __mul__(&self, arg: &PyAny) -> PyResult<Vector4> {
+        let scalarr: PyResult<f64> = arg.extract();
+        if let Ok(scalar) = scalarr {
+            return Ok(Vector4(self.0 * scalar));
+        }
+        let vecr: PyResult<PyRef<Vector4>> = arg.extract();
+        if let Ok(vec) = vecr {
+            return Ok(Vector4(self.0.component_mul(&vec.0)));
+        }
+        Err(PyTypeError::new_err(format!(
+            "Cannot multiply a Vector4 by {}",
+            arg.get_type().name().unwrap_or("?")
+        )))
+    }
+
+    fn __rmul__(&self, scalar: f64) -> Vector4 {
+        Vector4(self.0 * scalar)
+    }
+
+    fn __imul__(&mut self, arg: &PyAny) -> PyResult<()> {
+        let r = self.__mul__(arg)?;
+        self.0 = r.0;
+        Ok(())
+    }
+
+    fn __truediv__(&self, arg: f64) -> Vector4 {
+        Vector4(self.0 / arg)
+    }
+
+    fn __itruediv__(&mut self, arg: f64) {
+        self.0 /= arg;
+    }
+
+    fn __neg__(&self) -> Vector4 {
+        Vector4(-self.0)
+    }
+
+    fn length(&self) -> f64 {
+        self.0.magnitude()
+    }
+
+    fn length_squared(&self) -> f64 {
+        self.0.magnitude_squared()
+    }
+
+    fn normalized(&self) -> Vector4 {
+        Vector4(self.0.normalize())
+    }
+
+    fn dot(&self, other: PyRef<Vector4>) -> f64 {
+        self.0.dot(&other.0)
+    }
+
+    fn tuple(&self) -> (f64, f64, f64, f64) {
+        (self.0[0], self.0[1], self.0[2], self.0[3])
+    }
+
+    fn list(&self) -> [f64; 4] {
+        [self.0[0], self.0[1], self.0[2], self.0[3]]
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Vector4({}, {}, {}, {})",
+            self.0[0], self.0[1], self.0[2], self.0[3]
+        )
+    }
+}
@@ -0,0 +1,84 @@
+use crate::cloud::{parse_number, Cloud, Input};
+use nalgebra as na;
+use std::io::{self, Write};
+
+/// Reads a text file with one point per line as 3, 6 or 9 numbers separated
+/// by whitespace or commas: the point, then optionally its normal and then
+/// its color from 0 to 255. Every line must have the same number of values.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn read(data: &[u8]) -> Result<Cloud, String> {
+    let mut input = Input::new(data);
+    let mut cloud = Cloud::default();
+    let mut columns = None;
+    while let Some(line) = input.line() {
+        let line = line?;
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let values = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+            .map(parse_number)
+            .collect::<Result<Vec<f64>, String>>()
+            .map_err(|e| input.error(&e))?;
+        if values.is_empty() {
+            continue;
+        }
+        match columns {
+            None => {
+                if ![3, 6, 9].contains(&values.len()) {
+                    return Err(input.error(&format!(
+                        "expected 3, 6 or 9 values (a point, normal and color) but got {}",
+                        values.len()
+                    )));
+                }
+                columns = Some(values.len());
+                if values.len() >= 6 {
+                    cloud.normals = Some(Vec::new());
+                }
+                if values.len() == 9 {
+                    cloud.colors = Some(Vec::new());
+                }
+            }
+            Some(columns) if columns != values.len() => {
+                return Err(input.error(&format!(
+                    "expected {} values like the lines before but got {}",
+                    columns,
+                    values.len()
+                )));
+            }
+            Some(_) => {}
+        }
+        cloud
+            .points
+            .push(na::Vector3::from_column_slice(&values[..3]));
+        if let Some(normals) = &mut cloud.normals {
+            normals.push(na::Vector3::from_column_slice(&values[3..6]));
+        }
+        if let Some(colors) = &mut cloud.colors {
+            if values[6..].iter().any(|c| !(0.0..=255.0).contains(c)) {
+                return Err(input.error("colors must be from 0 to 255"));
+            }
+            colors.push([0, 1, 2].map(|c| values[6 + c].round() as u8));
+        }
+    }
+    Ok(cloud)
+}
+
+/// Writes the points, and their normals and colors if the cloud has them,
+/// one per line. Colors can only be written along with normals.
+pub fn write(out: &mut impl Write, cloud: &Cloud) -> io::Result<()> {
+    for (i, point) in cloud.points.iter().enumerate() {
+        write!(out, "{} {} {}", point.x, point.y, point.z)?;
+        if let Some(normals) = &cloud.normals {
+            let n = normals[i];
+            write!(out, " {} {} {}", n.x, n.y, n.z)?;
+            if let Some(colors) = &cloud.colors {
+                let [r, g, b] = colors[i];
+                write!(out, " {} {} {}", r, g, b)?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+import array
+import random
+import pytest
+from math import radians, sqrt
+from deuterium import (
+    Aabb,
+    Isometry3,
+    Sphere,
+    UnitQuaternion,
+    Vector3,
+    oriented_bounding_box,
+)
+
+
+def buffer(points):
+    """An N x 3 float64 buffer, as a numpy array would provide."""
+    flat = array.array("d", [c for p in points for c in p])
+    return memoryview(flat).cast("B").cast("d", shape=[len(points), 3])
+
+
+def random_points(n, seed=1):
+    rng = random.Random(seed)
+    return [Vector3(rng.uniform(-5, 5), rng.uniform(-2, 2), rng.uniform(-1, 3)) for _ in range(n)]
+
+
+def test_point_formats():
+    points = [Vector3(1, 2, 3), Vector3(-1, 5, 0), Vector3(2, -4, 1)]
+    expected = Aabb(Vector3(-1, -4, 0), Vector3(2, 5, 3))
+    assert Aabb.from_points(points) == expected
+    assert Aabb.from_points([p.tuple() for p in points]) == expected
+    assert Aabb.from_points([p.freeze() for p in points]) == expected
+    assert Aabb.from_points(p.list() for p in points) == expected
+    assert Aabb.from_points(buffer([p.tuple() for p in points])) == expected
+
+    with pytest.raises(ValueError, match="at least one"):
+        Aabb.from_points([])
+    with pytest.raises(ValueError, match="point 1 has 2"):
+        Aabb.from_points([(1, 2, 3), (1, 2)])
+    with pytest.raises(ValueError, match="N x 3"):
+        Aabb.from_points(memoryview(array.array("d", [1, 2, 3, 4])))
+    with pytest.raises(TypeError):
+        Aabb.from_points([1, 2, 3])
+    with pytest.raises(ValueError, match="finite"):
+        Aabb.from_points([(float("nan"), 0, 0), (1, 0, 0)])
+    with pytest.raises(ValueError, match="finite"):
+        Aabb.from_points([(0, 0, 0), (1, float("-inf"), 0)])
+
+
+def test_minimal_sphere():
+    points = random_points(500)
+    sphere = Sphere.from_points(points)
+    for p in points:
+        assert p.distance_to(sphere.center) <= sphere.radius * (1 + 1e-9)
+    # A minimal sphere has between 2 and 4 points on its surface
+    on_surface = [p for p in points if abs(p.distance_to(sphere.center) - sphere.radius) < 1e-9]
+    assert 2 <= len(on_surface) <= 4
+    assert Sphere.from_points(buffer([p.tuple() for p in points])).approx_equals(sphere)
+
+    # Interior points don't change the result
+    corners = [Vector3(x, y, z) for x in (-1, 1) for y in (-1, 1) for z in (-1, 1)]
+    cube = Sphere.from_points(corners + [Vector3(0.5, 0, 0.2)])
+    assert cube.approx_equals(Sphere(Vector3(), sqrt(3)))
+
+
+def test_minimal_sphere_degenerate():
+    assert Sphere.from_points([Vector3(1, 2, 3)]) == Sphere(Vector3(1, 2, 3), 0)
+    assert Sphere.from_points([Vector3(1, 2, 3)] * 5) == Sphere(Vector3(1, 2, 3), 0)
+
+    collinear = [Vector3(t, 2 * t, 0) for t in (0, 3, 1, 2, 0.5)]
+    sphere = Sphere.from_points(collinear)
+    assert sphere.approx_equals(Sphere(Vector3(1.5, 3, 0), 1.5 * sqrt(5)))
+
+    circle = [Vector3(2 * (i % 3 - 1), 0, 0) for i in range(6)] + [Vector3(0, 2, 0), Vector3(0, -2, 0)]
+    assert Sphere.from_points(circle).approx_equals(Sphere(Vector3(), 2))
+
+    # Coplanar points, including four on a common circle
+    square = [Vector3(1, 1, 0), Vector3(-1, 1, 0), Vector3(-1, -1, 0), Vector3(1, -1, 0), Vector3(0, 0, 0)]
+    assert Sphere.from_points(square).approx_equals(Sphere(Vector3(), sqrt(2)))
+
+
+def test_oriented_bounding_box():
+    iso = Isometry3.from_translation(Vector3(3, -2, 1))
+    iso.rotation = UnitQuaternion.from_axis_angle(Vector3(1, 2, 3).normalized(), radians(40))
+    extents = Vector3(4, 2, 0.5)
+    corners = [
+        Vector3(x * extents.x, y * extents.y, z * extents.z)
+        for x in (-1, 1)
+        for y in (-1, 1)
+        for z in (-1, 1)
+    ]
+    points = [c.transformed(iso) for c in corners]
+    pose, half_extents = oriented_bounding_box(points)
+    assert half_extents.approx_equals(extents, abs_tol=1e-9)
+    assert pose.translation.approx_equals(iso.translation, abs_tol=1e-9)
+    # The box's axes match up to sign
+    for axis in (Vector3(1, 0, 0), Vector3(0, 1, 0), Vector3(0, 0, 1)):
+        found, expected = axis.transformed(pose.rotation), axis.transformed(iso.rotation)
+        assert abs(found.dot(expected)) == pytest.approx(1.0)
+
+    result = oriented_bounding_box(buffer([p.tuple() for p in points]))
+    assert result.half_extents.approx_equals(half_extents)
+
+    # Every point is inside the box
+    inverse = result.pose.inverse()
+    for p in points:
+        local = p.transformed(inverse)
+        assert all(abs(local[i]) <= half_extents[i] + 1e-9 for i in range(3))
+
+
+def test_oriented_bounding_box_degenerate():
+    pose, half_extents = oriented_bounding_box([Vector3(1, 2, 3)])
+    assert half_extents == Vector3()
+    assert pose.translation == Vector3(1, 2, 3)
+
+    collinear = [Vector3(t, t, 0) for t in range(5)]
+    pose, half_extents = oriented_bounding_box(collinear)
+    assert half_extents.approx_equals(Vector3(2 * sqrt(2), 0, 0))
+    assert pose.translation.approx_equals(Vector3(2, 2, 0))
+
+    coplanar = [Vector3(x, y, 5) for x in (0, 4) for y in (0, 1)]
+    pose, half_extents = oriented_bounding_box(coplanar)
+    assert half_extents.approx_equals(Vector3(2, 0.5, 0))
+    assert pose.translation.approx_equals(Vector3(2, 0.5, 5))
+
+    with pytest.raises(ValueError):
+        oriented_bounding_box([])
+
+
+def test_non_finite_points():
+    for bad in (float("nan"), float("inf")):
+        points = random_points(10) + [Vector3(1, bad, 0)]
+        with pytest.raises(ValueError, match="Points must be finite"):
+            Sphere.from_points(points)
+        with pytest.raises(ValueError, match="Points must be finite"):
+            oriented_bounding_box(points)
+        with pytest.raises(ValueError, match="Points must be finite"):
+            Sphere.from_points(buffer([(bad, 0, 0)]))
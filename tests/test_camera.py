@@ -0,0 +1,138 @@
+import array
+import math
+import pickle
+import pytest
+from math import radians
+from deuterium import Isometry3, PinholeCamera, PointCloud, UnitQuaternion, Vector3
+
+
+def camera():
+    return PinholeCamera(500, 400, 320, 240, 640, 480)
+
+
+def pose():
+    iso = Isometry3.from_translation(Vector3(0.5, -1, 4))
+    iso.rotation = UnitQuaternion.from_axis_angle(Vector3(1, 2, 3).normalized(), radians(20))
+    return iso
+
+
+def test_camera():
+    cam = camera()
+    assert (cam.fx, cam.fy, cam.cx, cam.cy, cam.width, cam.height) == (500, 400, 320, 240, 640, 480)
+    assert repr(cam) == "PinholeCamera(fx=500, fy=400, cx=320, cy=240, width=640, height=480)"
+    assert pickle.loads(pickle.dumps(cam)) == cam
+    assert cam != PinholeCamera(500, 400, 320, 240, 640, 481)
+
+    with pytest.raises(ValueError, match="Focal lengths"):
+        PinholeCamera(0, 400, 320, 240, 640, 480)
+    with pytest.raises(ValueError, match="Focal lengths"):
+        PinholeCamera(500, math.inf, 320, 240, 640, 480)
+    with pytest.raises(ValueError, match="principal point"):
+        PinholeCamera(500, 400, math.nan, 240, 640, 480)
+    with pytest.raises(ValueError, match="width and height"):
+        PinholeCamera(500, 400, 320, 240, 0, 480)
+
+
+def test_project():
+    cam = camera()
+    assert cam.project(Vector3(0, 0, 2)) == (320, 240)
+    assert cam.project(Vector3(1, -1, 2)) == (570, 40)
+    with pytest.raises(ValueError, match="not in front of the camera"):
+        cam.project(Vector3(1, 1, 0))
+    with pytest.raises(ValueError, match="not in front of the camera"):
+        cam.project(Vector3(1, 1, -1))
+
+    # unproject inverts project, with the depth along the optical axis
+    point = cam.unproject(570, 40, 2)
+    assert point.approx_equals(Vector3(1, -1, 2))
+    assert cam.project(cam.unproject(12.5, 400.25, 7)) == pytest.approx((12.5, 400.25))
+
+    # With a world to camera pose
+    world = Vector3(0.3, 0.2, -1)
+    u, v = cam.project(world, pose())
+    assert (u, v) == pytest.approx(cam.project(world.transformed(pose())))
+    depth = world.transformed(pose()).z
+    assert cam.unproject(u, v, depth, pose()).approx_equals(world, abs_tol=1e-12)
+
+
+def test_ray():
+    cam = camera()
+    ray = cam.ray(570, 40)
+    assert ray.origin == Vector3(0, 0, 0)
+    assert ray.direction.approx_equals(Vector3(1, -1, 2).normalized())
+
+    # In the world the ray starts at the camera's center
+    world = Vector3(0.3, 0.2, -1)
+    ray = cam.ray(*cam.project(world, pose()), pose())
+    assert ray.origin.approx_equals(Vector3(0, 0, 0).transformed(pose().inverse()), abs_tol=1e-12)
+    assert ray.closest_point(world).approx_equals(world, abs_tol=1e-9)
+
+
+def test_visibility():
+    cam = camera()
+    assert cam.contains_pixel(-0.5, -0.5)
+    assert cam.contains_pixel(639.5, 479.5)
+    assert not cam.contains_pixel(639.6, 100)
+    assert not cam.contains_pixel(100, -0.6)
+
+    assert cam.is_visible(Vector3(0, 0, 1))
+    assert not cam.is_visible(Vector3(0, 0, -1))
+    assert not cam.is_visible(Vector3(0, 0, 0))
+    assert not cam.is_visible(Vector3(10, 0, 1))
+    assert not cam.is_visible(Vector3(0, 0, 1), near=2)
+    assert not cam.is_visible(Vector3(0, 0, 10), far=5)
+    assert cam.is_visible(Vector3(0, 0, 5), near=5, far=5.5)
+    assert cam.is_visible(Vector3(0.3, 0.2, -1), pose())
+    with pytest.raises(ValueError, match="near must be"):
+        cam.is_visible(Vector3(0, 0, 1), near=-1)
+    with pytest.raises(ValueError, match="far greater than near"):
+        cam.is_visible(Vector3(0, 0, 1), near=2, far=1)
+
+
+def test_frustum_planes():
+    cam = camera()
+    planes = cam.frustum_planes()
+    assert len(planes) == 4
+    assert len(cam.frustum_planes(near=0.1)) == 5
+    planes = cam.frustum_planes(0.5, 10, pose())
+    assert len(planes) == 6
+
+    # The planes agree with is_visible, and pass through the image corners
+    points = [Vector3(x * 0.5, y * 0.5, z) for x in range(-8, 9) for y in range(-8, 9) for z in (-1, 0.3, 1, 4, 12)]
+    for p in points:
+        world = p.transformed(pose().inverse())
+        inside = all(plane.signed_distance(world) >= -1e-12 for plane in planes)
+        assert inside == cam.is_visible(world, pose(), near=0.5, far=10)
+    corner = cam.unproject(-0.5, -0.5, 3, pose())
+    assert sorted(abs(plane.signed_distance(corner)) for plane in planes)[:2] == pytest.approx([0, 0], abs=1e-12)
+
+
+def test_batches():
+    cam = camera()
+    points = [Vector3(0, 0, 2), Vector3(1, -1, 2), Vector3(0, 0, -1), Vector3(100, 0, 1)]
+    pixels = cam.project_batch(points)
+    assert pixels[:2] == [(320, 240), (570, 40)]
+    assert all(math.isnan(c) for c in pixels[2])
+    assert pixels[3] == (50320, 240)
+    assert cam.is_visible_batch(points) == [True, True, False, False]
+    assert cam.is_visible_batch(points, far=1.5) == [False, False, False, False]
+    from_cloud = cam.project_batch(PointCloud(points))
+    from_lists = cam.project_batch([[p.x, p.y, p.z] for p in points])
+    assert from_cloud[:2] + from_cloud[3:] == from_lists[:2] + from_lists[3:] == pixels[:2] + pixels[3:]
+
+    world = [p.transformed(pose().inverse()) for p in points[:2]]
+    for uv, expected in zip(cam.project_batch(world, pose()), pixels[:2]):
+        assert uv == pytest.approx(expected)
+
+    flat = array.array("d", [320, 240, 570, 40])
+    buffer = memoryview(flat).cast("B").cast("d", shape=[2, 2])
+    for pixels in (buffer, [(320, 240), [570, 40]]):
+        unprojected = cam.unproject_batch(pixels, [2, 2])
+        assert unprojected == [Vector3(0, 0, 2), Vector3(1, -1, 2)]
+        for p, expected in zip(cam.unproject_batch(pixels, [2, 2], pose()), world):
+            assert p.approx_equals(expected, abs_tol=1e-12)
+
+    with pytest.raises(ValueError, match="Expected 2 depths but got 1"):
+        cam.unproject_batch([(1, 2), (3, 4)], [1])
+    with pytest.raises(ValueError, match="pixel 1 has 3"):
+        cam.unproject_batch([(1, 2), (3, 4, 5)], [1, 1])
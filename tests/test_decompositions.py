@@ -0,0 +1,90 @@
+import pytest
+from math import radians
+from deuterium import Matrix4, UnitQuaternion, Vector3, Vector4
+
+
+GENERAL = Matrix4.from_rows([[4, 1, -2, 3], [2, 7, 1, 0], [0, -3, 5, 1], [1, 2, 0, 6]])
+SPD = Matrix4.from_rows([[4, 1, 0, 1], [1, 5, 2, 0], [0, 2, 6, 1], [1, 0, 1, 3]])
+
+
+def diagonal(v):
+    return Matrix4.from_rows([[v[r] if r == c else 0 for c in range(4)] for r in range(4)])
+
+
+def test_svd():
+    u, s, v_t = GENERAL.svd()
+    assert isinstance(s, Vector4)
+    assert list(s) == sorted(s, reverse=True)
+    assert (u * diagonal(s) * v_t).approx_equals(GENERAL, abs_tol=1e-9)
+    assert (u.transposed() * u).approx_equals(Matrix4.identity())
+
+    result = GENERAL.svd()
+    assert result.singular_values == s
+    assert result.u == u and result.v_t == v_t
+
+    bad = Matrix4.identity()
+    bad[0, 0] = float("nan")
+    with pytest.raises(ValueError):
+        bad.svd()
+
+
+def test_symmetric_eigen():
+    values, vectors = SPD.symmetric_eigen()
+    assert list(values) == sorted(values, reverse=True)
+    assert (vectors * diagonal(values) * vectors.transposed()).approx_equals(SPD, abs_tol=1e-9)
+    for i in range(4):
+        column = Vector4(*vectors[:, i])
+        assert (SPD * column).approx_equals(column * values[i], abs_tol=1e-9)
+
+    with pytest.raises(ValueError, match="symmetric"):
+        GENERAL.symmetric_eigen()
+
+
+def test_qr():
+    q, r = GENERAL.qr()
+    assert (q * r).approx_equals(GENERAL, abs_tol=1e-9)
+    assert (q.transposed() * q).approx_equals(Matrix4.identity())
+    for row in range(4):
+        for col in range(row):
+            assert r[row, col] == 0
+
+
+def test_lu():
+    p, l, u = GENERAL.lu()
+    assert (p * GENERAL).approx_equals(l * u, abs_tol=1e-9)
+    for row in range(4):
+        assert l[row, row] == 1
+        for col in range(row + 1, 4):
+            assert l[row, col] == 0
+        for col in range(row):
+            assert u[row, col] == 0
+    assert sorted(sum(p.tuple(), ())) == [0] * 12 + [1] * 4
+
+
+def test_cholesky():
+    l = SPD.cholesky()
+    assert (l * l.transposed()).approx_equals(SPD, abs_tol=1e-9)
+    assert l[0, 1] == 0
+
+    with pytest.raises(ValueError, match="symmetric"):
+        GENERAL.cholesky()
+
+    with pytest.raises(ValueError, match="positive-definite"):
+        (-SPD).cholesky()
+
+
+def test_polar():
+    q = UnitQuaternion.from_axis_angle(Vector3(1, 2, 3), radians(40))
+    stretch = Matrix4.from_rows([[2, 0.1, 0, 0], [0.1, 1, 0, 0], [0, 0, 3, 0], [0, 0, 0, 1]])
+    m = Matrix4.from_rotation(q) * stretch
+    m.translation = Vector3(5, 6, 7)
+
+    rotation, s = m.polar()
+    assert rotation.approx_equals(q, as_rotation=True)
+    assert s.approx_equals(stretch)
+
+    # Re-orthonormalize a rotation matrix which has drifted slightly
+    drifted = Matrix4.from_rotation(q)
+    drifted[0, 1] += 1e-4
+    rotation, _ = drifted.polar()
+    assert rotation.approx_equals(q, abs_tol=1e-4, as_rotation=True)
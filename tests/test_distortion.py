@@ -0,0 +1,151 @@
+import array
+import math
+import pickle
+import pytest
+from deuterium import BrownConrady, DivisionModel, KannalaBrandt, PinholeCamera
+
+# A typical wide angle calibration, in OpenCV's distCoeffs order
+DIST_COEFFS = [-0.28, 0.07, 0.0012, -0.0004, -0.008]
+
+
+def opencv_distort(coeffs, x, y):
+    """OpenCV's projectPoints distortion, written out directly."""
+    k1, k2, p1, p2, k3, k4, k5, k6, s1, s2, s3, s4 = list(coeffs) + [0] * (12 - len(coeffs))
+    r2 = x * x + y * y
+    radial = (1 + k1 * r2 + k2 * r2**2 + k3 * r2**3) / (1 + k4 * r2 + k5 * r2**2 + k6 * r2**3)
+    return (
+        x * radial + 2 * p1 * x * y + p2 * (r2 + 2 * x * x) + s1 * r2 + s2 * r2**2,
+        y * radial + p1 * (r2 + 2 * y * y) + 2 * p2 * x * y + s3 * r2 + s4 * r2**2,
+    )
+
+
+GRID = [(x / 10, y / 10) for x in range(-6, 7, 3) for y in range(-4, 5, 2)]
+
+
+def test_brown_conrady():
+    model = BrownConrady(*DIST_COEFFS)
+    assert model.coefficients == DIST_COEFFS
+    assert repr(model) == "BrownConrady(k1=-0.28, k2=0.07, p1=0.0012, p2=-0.0004, k3=-0.008)"
+    assert BrownConrady().coefficients == [0, 0, 0, 0]
+    assert BrownConrady(k6=1).coefficients == [0, 0, 0, 0, 0, 0, 0, 1]
+    assert len(BrownConrady(s4=1).coefficients) == 12
+    assert pickle.loads(pickle.dumps(model)) == model
+
+    rational = [0.1, -0.2, 0.001, 0.002, 0.05, 0.3, -0.1, 0.02, 0.001, -0.002, 0.003, 0.0005]
+    for coeffs in (DIST_COEFFS, rational):
+        model = BrownConrady(*coeffs)
+        for x, y in GRID:
+            assert model.distort(x, y) == pytest.approx(opencv_distort(coeffs, x, y), abs=1e-15)
+            result = model.undistort(*model.distort(x, y))
+            assert result.converged
+            assert 0 < result.iterations <= 20 or (x, y) == (0, 0)
+            assert (result.x, result.y) == pytest.approx((x, y), abs=1e-11)
+
+    assert BrownConrady().undistort(0.3, 0.2) == (0.3, 0.2, True, 0)
+
+
+def test_from_coefficients():
+    assert BrownConrady.from_coefficients(DIST_COEFFS) == BrownConrady(*DIST_COEFFS)
+    assert BrownConrady.from_coefficients(DIST_COEFFS[:4]).coefficients == DIST_COEFFS[:4]
+    assert BrownConrady.from_coefficients([1] * 12).coefficients == [1] * 12
+    assert BrownConrady.from_coefficients([1] * 12 + [0, 0]).coefficients == [1] * 12
+    with pytest.raises(ValueError, match="Tilted sensor"):
+        BrownConrady.from_coefficients([1] * 12 + [0.1, 0])
+    with pytest.raises(ValueError, match="Expected 4, 5, 8, 12 or 14 distortion coefficients but got 6"):
+        BrownConrady.from_coefficients([0] * 6)
+    with pytest.raises(ValueError, match="must be finite"):
+        BrownConrady(math.nan)
+    with pytest.raises(ValueError, match="must be finite"):
+        KannalaBrandt(k4=math.inf)
+
+
+def test_convergence_reporting():
+    model = BrownConrady(*DIST_COEFFS)
+    x, y = model.distort(0.5, -0.4)
+    result = model.undistort(x, y, max_iterations=1)
+    assert not result.converged
+    assert result.iterations == 1
+    assert model.undistort(x, y, tolerance=1e-3).iterations < model.undistort(x, y).iterations
+
+    # Strong barrel distortion folds back on itself far from the center, so
+    # points beyond the fold have no undistorted point
+    x, y, converged, iterations = model.undistort(2.0, 2.0)
+    assert not converged
+    with pytest.raises(ValueError, match="tolerance"):
+        model.undistort(0.1, 0.1, tolerance=0)
+
+
+def test_kannala_brandt():
+    coeffs = [0.02, -0.005, 0.001, -0.0002]
+    model = KannalaBrandt(*coeffs)
+    assert model.coefficients == coeffs
+    assert repr(model) == "KannalaBrandt(k1=0.02, k2=-0.005, k3=0.001, k4=-0.0002)"
+    assert pickle.loads(pickle.dumps(model)) == model
+
+    for x, y in GRID + [(3.0, -2.0), (20.0, 5.0)]:
+        r = math.hypot(x, y)
+        theta = math.atan(r)
+        theta_d = theta * (1 + sum(k * theta ** (2 * i + 2) for i, k in enumerate(coeffs)))
+        expected = (x * theta_d / r, y * theta_d / r) if r else (0, 0)
+        assert model.distort(x, y) == pytest.approx(expected, abs=1e-15)
+        result = model.undistort(*model.distort(x, y))
+        assert result.converged
+        assert (result.x, result.y) == pytest.approx((x, y), rel=1e-9, abs=1e-12)
+
+    # Angles of 90 degrees or more can't be undistorted
+    assert not KannalaBrandt().undistort(2.0, 0).converged
+    assert KannalaBrandt().undistort(1.0, 0).x == pytest.approx(math.tan(1))
+
+
+def test_division_model():
+    model = DivisionModel(-0.2)
+    assert model.k1 == -0.2
+    assert repr(model) == "DivisionModel(k1=-0.2)"
+    assert pickle.loads(pickle.dumps(model)) == model
+
+    x, y = 0.5, -0.3
+    result = model.undistort(x, y)
+    r2 = x * x + y * y
+    assert (result.x, result.y) == pytest.approx((x / (1 - 0.2 * r2), y / (1 - 0.2 * r2)))
+    assert result.converged and result.iterations == 0
+    assert model.distort(result.x, result.y) == pytest.approx((x, y))
+    for px, py in GRID:
+        result = DivisionModel(0.3).undistort(*DivisionModel(0.3).distort(px, py))
+        assert (result.x, result.y) == pytest.approx((px, py), abs=1e-14)
+
+    # Pincushion distortion can't reach points too far out
+    assert all(math.isnan(c) for c in DivisionModel(1).distort(1, 0))
+    assert DivisionModel().distort(0.25, 0.5) == (0.25, 0.5)
+
+
+def test_batches():
+    points = [(0.1, 0.2), (-0.3, 0.4), (0.5, -0.1)]
+    flat = array.array("d", [c for p in points for c in p])
+    buffer = memoryview(flat).cast("B").cast("d", shape=[3, 2])
+    for model in (BrownConrady(*DIST_COEFFS), KannalaBrandt(0.01, 0.002), DivisionModel(-0.1)):
+        distorted = model.distort_batch(points)
+        assert distorted == [model.distort(*p) for p in points]
+        assert model.distort_batch(buffer) == distorted
+        result = model.undistort_batch(distorted)
+        assert result.converged == [True] * 3
+        for p, expected in zip(result.points, points):
+            assert p == pytest.approx(expected, abs=1e-11)
+        assert model.undistort_batch([]) == ([], [])
+
+    result = BrownConrady(*DIST_COEFFS).undistort_batch([(0.1, 0.1), (2.0, 2.0)])
+    assert result.converged == [True, False]
+    with pytest.raises(ValueError, match="point 0 has 3"):
+        DivisionModel().distort_batch([(1, 2, 3)])
+
+
+def test_with_camera():
+    camera = PinholeCamera(500, 400, 320, 240, 640, 480)
+    model = BrownConrady(*DIST_COEFFS)
+    assert camera.to_normalized(570, 40) == (0.5, -0.5)
+    assert camera.to_pixel(0.5, -0.5) == (570, 40)
+
+    # Undistort a distorted pixel back to where the ideal camera projects it
+    u, v = camera.to_pixel(*model.distort(*camera.to_normalized(100, 50)))
+    x, y, converged, _ = model.undistort(*camera.to_normalized(u, v))
+    assert converged
+    assert camera.to_pixel(x, y) == pytest.approx((100, 50))
@@ -0,0 +1,175 @@
+import array
+import math
+import random
+import pytest
+from math import radians
+from deuterium import (
+    Isometry3,
+    Matrix3,
+    PinholeCamera,
+    UnitQuaternion,
+    Vector3,
+    essential_from_fundamental,
+    essential_from_pose,
+    fundamental_from_essential,
+    fundamental_matrix,
+    pose_from_essential,
+    poses_from_essential,
+    sampson_error,
+    sampson_errors,
+    triangulate,
+    triangulate_batch,
+)
+
+
+def camera1():
+    return PinholeCamera(500, 480, 320, 240, 640, 480)
+
+
+def camera2():
+    return PinholeCamera(620, 600, 300, 250, 640, 480)
+
+
+# World to camera poses of two cameras looking at the origin from about 5
+# units away, and the relative pose from the first camera to the second
+POSE1 = Isometry3(Vector3(0.2, 0, 5), UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), radians(10)))
+POSE2 = Isometry3(Vector3(-0.8, 0.1, 5.2), UnitQuaternion.from_axis_angle(Vector3(0.2, -1, 0.1), radians(15)))
+RELATIVE = POSE2 * POSE1.inverse()
+
+
+def scene(n=30, seed=1):
+    rng = random.Random(seed)
+    return [Vector3(rng.uniform(-1, 1), rng.uniform(-1, 1), rng.uniform(-1, 1)) for _ in range(n)]
+
+
+def unit_relative():
+    """The relative pose with its translation scaled to unit length."""
+    unit = Isometry3.from_translation(RELATIVE.translation.normalized())
+    unit.rotation = RELATIVE.rotation
+    return unit
+
+
+def normalized(camera, pixels):
+    return [camera.to_normalized(u, v) for u, v in pixels]
+
+
+def epipolar(m, x1, x2):
+    return (Vector3(x2[0], x2[1], 1)).dot(m * Vector3(x1[0], x1[1], 1))
+
+
+def test_essential_matrix():
+    points = scene()
+    pixels1 = camera1().project_batch(points, POSE1)
+    pixels2 = camera2().project_batch(points, POSE2)
+    e = essential_from_pose(RELATIVE)
+    assert e.rank() == 2
+    for x1, x2 in zip(normalized(camera1(), pixels1), normalized(camera2(), pixels2)):
+        assert epipolar(e, x1, x2) == pytest.approx(0, abs=1e-12)
+
+    # One of the four decompositions is the relative pose, up to scale
+    unit = unit_relative()
+    candidates = poses_from_essential(e)
+    assert len(candidates) == 4
+    assert sum(c.approx_equals(unit, abs_tol=1e-9) for c in candidates) == 1
+
+    # The cheirality check picks it out
+    for scaled in (e, e * -3):
+        result = pose_from_essential(scaled, pixels1, pixels2, camera1(), camera2())
+        assert result.pose.approx_equals(unit, abs_tol=1e-9)
+        assert result.in_front == [True] * len(points)
+    result = pose_from_essential(e, normalized(camera1(), pixels1), normalized(camera2(), pixels2))
+    assert result.pose.approx_equals(unit, abs_tol=1e-9)
+
+    with pytest.raises(ValueError, match="two non-zero singular values"):
+        poses_from_essential(Matrix3([[1, 0, 0], [0, 0, 0], [0, 0, 0]]))
+    with pytest.raises(ValueError, match="non-finite"):
+        poses_from_essential(Matrix3([[math.nan, 0, 0], [0, 1, 0], [0, 0, 0]]))
+    with pytest.raises(ValueError, match="2 points in the first image but 1"):
+        pose_from_essential(e, pixels1[:2], pixels2[:1])
+
+
+def test_fundamental_matrix():
+    points = scene()
+    pixels1 = camera1().project_batch(points, POSE1)
+    pixels2 = camera2().project_batch(points, POSE2)
+    f = fundamental_matrix(pixels1, pixels2)
+    assert f.norm() == pytest.approx(1)
+    assert f.rank(1e-12) == 2
+    for x1, x2 in zip(pixels1, pixels2):
+        assert epipolar(f, x1, x2) == pytest.approx(0, abs=1e-9)
+
+    # It agrees with the matrix from the known pose up to scale
+    expected = fundamental_from_essential(essential_from_pose(RELATIVE), camera1(), camera2())
+    expected = expected / expected.norm()
+    assert f.approx_equals(expected, abs_tol=1e-9) or f.approx_equals(-expected, abs_tol=1e-9)
+
+    # Going back to an essential matrix recovers the relative pose
+    e = essential_from_fundamental(f, camera1(), camera2())
+    pose = pose_from_essential(e, pixels1, pixels2, camera1(), camera2()).pose
+    assert pose.approx_equals(unit_relative(), abs_tol=1e-7)
+
+    # Buffers work too
+    flat = array.array("d", [c for uv in pixels1 for c in uv])
+    buffer = memoryview(flat).cast("B").cast("d", shape=[len(pixels1), 2])
+    assert fundamental_matrix(buffer, pixels2).approx_equals(f, abs_tol=1e-12)
+
+    with pytest.raises(ValueError, match="at least 8 correspondences but got 7"):
+        fundamental_matrix(pixels1[:7], pixels2[:7])
+    with pytest.raises(ValueError, match="unique fundamental matrix"):
+        fundamental_matrix(pixels1, pixels1)
+
+
+def test_sampson_error():
+    points = scene(10)
+    pixels1 = camera1().project_batch(points, POSE1)
+    pixels2 = camera2().project_batch(points, POSE2)
+    f = fundamental_matrix(pixels1, pixels2)
+    assert sampson_error(f, pixels1[0], pixels2[0]) == pytest.approx(0, abs=1e-18)
+
+    # Moving a pixel 2 pixels away from its epipolar line gives an error of
+    # less than 4 squared pixels, as the error is shared by both pixels
+    a, b, _ = f * Vector3(pixels1[0][0], pixels1[0][1], 1)
+    norm = math.hypot(a, b)
+    moved = (pixels2[0][0] + 2 * a / norm, pixels2[0][1] + 2 * b / norm)
+    c, d, _ = f.transposed() * Vector3(moved[0], moved[1], 1)
+    expected = 4 * (a * a + b * b) / (a * a + b * b + c * c + d * d)
+    assert sampson_error(f, pixels1[0], moved) == pytest.approx(expected, rel=1e-6)
+    errors = sampson_errors(f, pixels1, [moved] + pixels2[1:])
+    assert errors[0] == sampson_error(f, pixels1[0], moved)
+    assert max(errors[1:]) < 1e-18
+
+
+def test_triangulate():
+    points = scene(10)
+    pixels1 = camera1().project_batch(points, POSE1)
+    pixels2 = camera2().project_batch(points, POSE2)
+    for method in ("linear", "midpoint"):
+        for p, uv1, uv2 in zip(points, pixels1, pixels2):
+            point = triangulate(camera1(), POSE1, uv1, camera2(), POSE2, uv2, method=method)
+            assert point.approx_equals(p, abs_tol=1e-9)
+        batch = triangulate_batch(camera1(), POSE1, pixels1, camera2(), POSE2, pixels2, method=method)
+        assert all(b.approx_equals(p, abs_tol=1e-9) for b, p in zip(batch, points))
+
+    # With noise the rays don't meet, but the point is still close
+    noisy = (pixels2[0][0] + 0.5, pixels2[0][1] - 0.5)
+    for method in ("linear", "midpoint"):
+        point = triangulate(camera1(), POSE1, pixels1[0], camera2(), POSE2, noisy, method=method)
+        assert point.approx_equals(points[0], abs_tol=0.02)
+
+    # Parallel rays, from cameras looking the same way, never meet
+    shifted = Isometry3.from_translation(Vector3(1, 0, 5))
+    beside = Isometry3.from_translation(Vector3(0, 0, 5))
+    with pytest.raises(ValueError, match="parallel"):
+        triangulate(camera1(), beside, (320, 240), camera1(), shifted, (320, 240))
+    nan = triangulate_batch(camera1(), beside, [(320, 240)], camera1(), shifted, [(320, 240)], method="midpoint")[0]
+    assert math.isnan(nan.x)
+    with pytest.raises(ValueError, match="method must be 'linear' or 'midpoint', not 'dlt'"):
+        triangulate(camera1(), POSE1, pixels1[0], camera2(), POSE2, pixels2[0], method="dlt")
+
+
+def test_camera_matrix():
+    k = camera1().matrix()
+    assert k.list() == [[500, 0, 320], [0, 480, 240], [0, 0, 1]]
+    p = Vector3(0.3, -0.2, 2)
+    h = k * p
+    assert (h.x / h.z, h.y / h.z) == pytest.approx(camera1().project(p))
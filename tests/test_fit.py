@@ -0,0 +1,153 @@
+import random
+import pytest
+from math import radians, sqrt
+from deuterium import (
+    Isometry3,
+    Plane,
+    UnitQuaternion,
+    Vector3,
+    centroid,
+    fit_line,
+    fit_plane,
+    fit_plane_ransac,
+    principal_axes,
+)
+
+
+def noisy_plane_points(n, noise, offset=Vector3(), seed=1):
+    rng = random.Random(seed)
+    return [
+        Vector3(rng.uniform(-10, 10), rng.uniform(-10, 10), 2 + rng.gauss(0, noise)) + offset
+        for _ in range(n)
+    ]
+
+
+def test_fit_plane():
+    points = [Vector3(x, y, 2) for x in range(3) for y in range(3)]
+    plane, rms = fit_plane(points)
+    assert abs(plane.normal.z) == pytest.approx(1)
+    assert plane.signed_distance(Vector3(0, 0, 2)) == pytest.approx(0, abs=1e-12)
+    assert rms == pytest.approx(0, abs=1e-12)
+
+    result = fit_plane(noisy_plane_points(200, 0.01))
+    assert abs(result.plane.normal.z) == pytest.approx(1, abs=1e-4)
+    assert result.rms == pytest.approx(0.01, rel=0.2)
+
+    # Two points above and two below the plane z = 0, by 1 each
+    plane, rms = fit_plane([Vector3(0, 0, 1), Vector3(4, 0, -1), Vector3(0, 4, -1), Vector3(4, 4, 1)])
+    assert plane.normal.approx_equals(Vector3(0, 0, 1)) or plane.normal.approx_equals(Vector3(0, 0, -1))
+    assert rms == pytest.approx(1)
+
+    with pytest.raises(ValueError, match="collinear"):
+        fit_plane([Vector3(t, t, t) for t in range(5)])
+    with pytest.raises(ValueError):
+        fit_plane([Vector3(1, 2, 3)] * 3)
+    with pytest.raises(ValueError):
+        fit_plane([Vector3(), Vector3(1, 0, 0)])
+
+
+def test_fit_plane_far_from_origin():
+    offset = Vector3(1e7, -3e7, 5e6)
+    points = [Vector3(x, y, 0.001 * (x + y) % 0.002) + offset for x in range(10) for y in range(10)]
+    plane, rms = fit_plane(points)
+    assert abs(plane.normal.z) == pytest.approx(1, abs=1e-6)
+    assert rms < 0.002
+    for p in points:
+        assert abs(plane.signed_distance(p)) < 0.002
+
+
+def test_fit_line():
+    direction = Vector3(1, 2, 2) * (1 / 3)
+    points = [Vector3(1, 1, 1) + direction * t for t in (-3, 0, 1, 5, 8)]
+    point, found, rms = fit_line(points)
+    assert abs(found.dot(direction)) == pytest.approx(1)
+    assert found.length() == pytest.approx(1)
+    assert point.approx_equals(centroid(points))
+    assert rms == pytest.approx(0, abs=1e-12)
+
+    # Points 1 either side of the x axis
+    result = fit_line([Vector3(x, y, 0) for x in range(10) for y in (-1, 1)])
+    assert abs(result.direction.x) == pytest.approx(1)
+    assert result.rms == pytest.approx(1)
+
+    with pytest.raises(ValueError, match="distinct"):
+        fit_line([Vector3(1, 2, 3)] * 4)
+    # Coordinates which aren't exactly representable leave the mean a
+    # rounding error away from the points
+    with pytest.raises(ValueError, match="distinct"):
+        fit_line([(0.1, 0.2, 0.3)] * 7)
+    with pytest.raises(ValueError, match="distinct"):
+        fit_line([(1e8 + 0.1, -1e8 + 0.2, 0.3)] * 7)
+
+
+def test_centroid():
+    points = [Vector3(0, 0, 0), Vector3(2, 0, 0), Vector3(0, 4, 0)]
+    assert centroid(points).approx_equals(Vector3(2 / 3, 4 / 3, 0))
+    assert centroid(points, [1, 1, 0]) == Vector3(1, 0, 0)
+    assert centroid(points, weights=[0, 0, 2]) == Vector3(0, 4, 0)
+    assert centroid([(1, 2, 3)]) == Vector3(1, 2, 3)
+
+    with pytest.raises(ValueError, match="3 weights but got 2"):
+        centroid(points, [1, 2])
+    with pytest.raises(ValueError, match="zero"):
+        centroid(points, [1, -1, 0])
+    with pytest.raises(ValueError):
+        centroid([])
+
+
+def test_principal_axes():
+    rotation = UnitQuaternion.from_axis_angle(Vector3(1, 1, 0).normalized(), radians(30))
+    iso = Isometry3.from_translation(Vector3(5, 6, 7))
+    iso.rotation = rotation
+    extents = (3, 2, 1)
+    points = [
+        Vector3(*(s * e for s, e in zip(signs, extents))).transformed(iso)
+        for signs in [(x, y, z) for x in (-1, 1) for y in (-1, 1) for z in (-1, 1)]
+    ]
+    found, variances = principal_axes(points)
+    assert variances.approx_equals(Vector3(9, 4, 1))
+    for axis in (Vector3(1, 0, 0), Vector3(0, 1, 0), Vector3(0, 0, 1)):
+        assert abs(axis.transformed(found).dot(axis.transformed(rotation))) == pytest.approx(1)
+
+    rotation, variances = principal_axes([Vector3(1, 2, 3)])
+    assert variances == Vector3()
+
+
+def test_non_finite_points():
+    nan = float("nan")
+    points = [Vector3(x, y, 0) for x in range(3) for y in range(3)] + [Vector3(nan, 0, 0)]
+    for fit in (fit_plane, fit_line, principal_axes, lambda p: fit_plane_ransac(p, 0.1)):
+        with pytest.raises(ValueError, match="finite"):
+            fit(points)
+        with pytest.raises(ValueError, match="finite"):
+            fit([(1, 2, 3), (float("inf"), 0, 0), (4, 5, 6)])
+
+
+def test_fit_plane_ransac():
+    inliers = noisy_plane_points(100, 0.001, seed=2)
+    rng = random.Random(3)
+    outliers = [Vector3(rng.uniform(-10, 10), rng.uniform(-10, 10), rng.uniform(3, 10)) for _ in range(60)]
+    points = inliers + outliers
+
+    result = fit_plane_ransac(points, 0.01, seed=5)
+    assert sorted(result.inliers) == list(range(100))
+    assert abs(result.plane.normal.z) == pytest.approx(1, abs=1e-3)
+    assert abs(result.plane.signed_distance(Vector3(0, 0, 2))) < 1e-3
+    assert result.rms < 0.01
+
+    # The least-squares fit is pulled off by the outliers
+    assert fit_plane(points).rms > 1
+
+    # Seeded runs are deterministic
+    again = fit_plane_ransac(points, 0.01, seed=5)
+    assert again.plane == result.plane and again.inliers == result.inliers
+    assert fit_plane_ransac(points, 0.01, iterations=20, seed=7).inliers == fit_plane_ransac(
+        points, 0.01, iterations=20, seed=7
+    ).inliers
+
+    with pytest.raises(ValueError, match="threshold"):
+        fit_plane_ransac(points, 0)
+    with pytest.raises(ValueError):
+        fit_plane_ransac([Vector3(t, 0, 0) for t in range(10)], 0.1)
+    with pytest.raises(ValueError):
+        fit_plane_ransac(points[:2], 0.1)
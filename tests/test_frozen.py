@@ -0,0 +1,125 @@
+import pytest
+from math import radians
+from deuterium import (
+    FrozenIsometry3,
+    FrozenUnitQuaternion,
+    FrozenVector3,
+    Isometry3,
+    UnitQuaternion,
+    Vector3,
+)
+
+
+def test_freeze_and_thaw():
+    v = Vector3(1, 2, 3)
+    f = v.freeze()
+    assert isinstance(f, FrozenVector3)
+    assert f == FrozenVector3(1, 2, 3)
+    assert f == v and v == f
+    assert f.thaw() == v
+    assert f.x == 1 and f.y == 2 and f.z == 3
+    assert f[-1] == 3
+    assert f.tuple() == (1, 2, 3)
+
+    thawed = f.thaw()
+    thawed.x = 10
+    assert f.x == 1
+
+    with pytest.raises(AttributeError):
+        f.x = 5
+
+    with pytest.raises(IndexError):
+        f[3]
+
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(45))
+    assert q.freeze() == q
+    assert q.freeze().thaw() == q
+
+    iso = Isometry3.from_translation(Vector3(1, 2, 3))
+    iso.rotation = q
+    fi = iso.freeze()
+    assert fi == iso
+    assert fi.translation == FrozenVector3(1, 2, 3)
+    assert fi.rotation == q.freeze()
+    assert fi.thaw() == iso
+
+
+def test_constructors():
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(45))
+    iso = Isometry3(Vector3(1, 2, 3), q)
+    assert FrozenIsometry3(Vector3(1, 2, 3), q) == iso
+    assert FrozenIsometry3(FrozenVector3(1, 2, 3), q.freeze()) == iso
+    assert FrozenIsometry3(rotation=q) == Isometry3(rotation=q)
+    assert FrozenIsometry3() == Isometry3.identity()
+    assert FrozenUnitQuaternion() == UnitQuaternion()
+    assert FrozenVector3() == Vector3()
+
+    with pytest.raises(TypeError, match="Expected a Vector3 or FrozenVector3, not tuple"):
+        FrozenIsometry3((1, 2, 3))
+    with pytest.raises(TypeError, match="Expected a UnitQuaternion or FrozenUnitQuaternion"):
+        FrozenIsometry3(Vector3(), Vector3())
+
+
+def test_sequence_protocol():
+    protocol = ("__getitem__", "__iter__", "__reversed__", "__contains__", "__len__", "__match_args__")
+    for mutable, frozen in ((Vector3(1, 2, 3), Vector3(1, 2, 3).freeze()), (UnitQuaternion(), UnitQuaternion().freeze())):
+        for name in protocol:
+            assert hasattr(frozen, name) == hasattr(mutable, name), name
+        assert list(reversed(frozen)) == list(reversed(mutable))
+        assert type(frozen).__match_args__ == type(mutable).__match_args__
+        assert [getattr(frozen, a) for a in frozen.__match_args__] == list(frozen)
+        assert frozen[-1] in frozen
+        assert "a" not in frozen and 5 not in frozen
+
+    match Vector3(1, 0, 3).freeze():
+        case FrozenVector3(x, 0, z):
+            assert (x, z) == (1, 3)
+        case _:
+            assert False
+
+
+def test_hashing():
+    assert hash(FrozenVector3(1, 2, 3)) == hash(Vector3(1, 2, 3).freeze())
+    assert hash(FrozenVector3(0, 0, 0)) == hash(FrozenVector3(-0.0, 0, -0.0))
+    assert len({FrozenVector3(1, 2, 3), FrozenVector3(1, 2, 3), FrozenVector3(3, 2, 1)}) == 2
+
+    with pytest.raises(TypeError):
+        hash(Vector3())
+
+    cache = {Isometry3.identity().freeze(): "origin"}
+    assert cache[FrozenIsometry3()] == "origin"
+
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), radians(30)).freeze()
+    assert len({q, q.thaw().freeze(), FrozenUnitQuaternion()}) == 2
+
+
+def test_quantized_hash():
+    a = FrozenVector3(1.0, 2.0, 3.0)
+    b = FrozenVector3(1.0001, 2.0, 2.9999)
+    assert hash(a) != hash(b)
+    assert a.quantized_hash(0.01) == b.quantized_hash(0.01)
+    assert a.quantized_hash(0.00001) != b.quantized_hash(0.00001)
+
+    with pytest.raises(ValueError):
+        a.quantized_hash(0)
+
+    points = [Vector3(0, 0, 0), Vector3(1e-6, 0, 0), Vector3(5, 5, 5)]
+    unique = {p.freeze().quantized_hash(0.001): p for p in points}
+    assert len(unique) == 2
+
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), radians(30)).freeze()
+    negated = UnitQuaternion.from_axis_angle(Vector3(0, -1, 0), radians(330.0001)).freeze()
+    assert q.quantized_hash(0.001) == negated.quantized_hash(0.001)
+
+    i1 = Isometry3.from_translation(Vector3(1, 0, 0)).freeze()
+    i2 = Isometry3.from_translation(Vector3(1.0001, 0, 0)).freeze()
+    assert i1.quantized_hash(0.01) == i2.quantized_hash(0.01)
+    assert i1.quantized_hash(0.01, 0.001) == i2.quantized_hash(0.01, 0.001)
+    assert i1.quantized_hash(0.00001) != i2.quantized_hash(0.00001)
+
+
+def test_approx_equals():
+    assert FrozenVector3(0, 0, 1e-10).approx_equals(FrozenVector3())
+    assert FrozenVector3(0, 0, 1e-5).approx_equals(FrozenVector3(), abs_tol=1e-4)
+    assert FrozenUnitQuaternion().approx_equals(UnitQuaternion().freeze())
+    assert FrozenIsometry3().approx_equals(Isometry3.identity().freeze())
@@ -0,0 +1,97 @@
+import math
+import random
+import pytest
+from math import radians
+from deuterium import Isometry3, UnitQuaternion, Vector3, calibrate_hand_eye
+
+
+# The camera's pose on the hand, mapping camera coordinates to hand coordinates
+HAND_EYE = Isometry3(Vector3(0.05, -0.02, 0.1), UnitQuaternion.from_axis_angle(Vector3(0.3, 1, -0.2), radians(35)))
+
+
+def random_pose(rng):
+    axis = Vector3(rng.uniform(-1, 1), rng.uniform(-1, 1), rng.uniform(-1, 1))
+    translation = Vector3(rng.uniform(-0.5, 0.5), rng.uniform(-0.5, 0.5), rng.uniform(0.3, 1))
+    return Isometry3(translation, UnitQuaternion.from_axis_angle(axis, radians(rng.uniform(10, 60))))
+
+
+def motions(n=8, seed=1, noise=0.0):
+    """Robot and camera motions between successive stations of a hand
+    holding the camera, which looks at a fixed target."""
+    rng = random.Random(seed)
+    base_to_target = Isometry3(Vector3(0.8, 0.1, 0), UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(20)))
+    hands = [random_pose(rng) for _ in range(n + 1)]
+    # Where the target is seen from the camera at each station
+    camera_to_target = [(hand * HAND_EYE).inverse() * base_to_target for hand in hands]
+    robot, camera = [], []
+    for i in range(n):
+        robot.append(hands[i].inverse() * hands[i + 1])
+        b = camera_to_target[i] * camera_to_target[i + 1].inverse()
+        if noise:
+            axis = Vector3(rng.gauss(0, 1), rng.gauss(0, 1), rng.gauss(0, 1))
+            jitter = Isometry3(rotation=UnitQuaternion.from_axis_angle(axis, radians(noise)))
+            jitter.translation = Vector3(rng.gauss(0, 1), rng.gauss(0, 1), rng.gauss(0, 1)) * (noise / 100)
+            b = jitter * b
+        camera.append(b)
+    return robot, camera
+
+
+def test_exact_motions():
+    robot, camera = motions()
+    for a, b in zip(robot, camera):
+        assert (a * HAND_EYE).approx_equals(HAND_EYE * b, abs_tol=1e-12)
+    for method in ("tsai", "park"):
+        result = calibrate_hand_eye(robot, camera, method=method)
+        assert result.transform.approx_equals(HAND_EYE, abs_tol=1e-9)
+        assert result.rotation_rmse < 1e-9
+        assert result.translation_rmse < 1e-9
+        assert result.max_rotation_error < 1e-9
+        assert result.max_translation_error < 1e-9
+
+    # Two motions about different axes are enough
+    assert calibrate_hand_eye(robot[:2], camera[:2]).transform.approx_equals(HAND_EYE, abs_tol=1e-9)
+
+
+def test_noisy_motions():
+    robot, camera = motions(30, noise=0.2)
+    for method in ("tsai", "park"):
+        result = calibrate_hand_eye(robot, camera, method=method)
+        assert abs((result.transform.rotation.inverse() * HAND_EYE.rotation).angle()) < radians(0.2)
+        assert (result.transform.translation - HAND_EYE.translation).length() < 0.01
+        assert 0 < result.rotation_rmse <= result.max_rotation_error < radians(1)
+        assert 0 < result.translation_rmse <= result.max_translation_error
+
+
+def test_degenerate_motions():
+    # Rotations all about the same axis leave the rotation about it unknown
+    rng = random.Random(2)
+    hands = [
+        Isometry3(
+            Vector3(rng.uniform(-1, 1), rng.uniform(-1, 1), 0),
+            UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(rng.uniform(-90, 90))),
+        )
+        for _ in range(6)
+    ]
+    robot = [hands[i].inverse() * hands[i + 1] for i in range(5)]
+    camera = [HAND_EYE.inverse() * a * HAND_EYE for a in robot]
+    with pytest.raises(ValueError, match="not diverse enough"):
+        calibrate_hand_eye(robot, camera)
+
+    # As do pure translations
+    robot = [Isometry3.from_translation(Vector3(i, 1, 0)) for i in range(4)]
+    camera = [HAND_EYE.inverse() * a * HAND_EYE for a in robot]
+    with pytest.raises(ValueError, match="not diverse enough"):
+        calibrate_hand_eye(robot, camera, method="park")
+
+
+def test_errors():
+    robot, camera = motions(3)
+    with pytest.raises(ValueError, match="3 robot motions but 2 camera motions"):
+        calibrate_hand_eye(robot, camera[:2])
+    with pytest.raises(ValueError, match="at least 2 motions"):
+        calibrate_hand_eye(robot[:1], camera[:1])
+    with pytest.raises(ValueError, match="method must be 'tsai' or 'park', not 'daniilidis'"):
+        calibrate_hand_eye(robot, camera, method="daniilidis")
+    broken = Isometry3.from_translation(Vector3(math.inf, 0, 0))
+    with pytest.raises(ValueError, match="finite"):
+        calibrate_hand_eye(robot, [broken] + camera[1:])
@@ -0,0 +1,98 @@
+import random
+import pytest
+from math import pi, sqrt
+from deuterium import ConvexHull, Vector3
+
+
+CUBE = [Vector3(x, y, z) for x in (0, 2) for y in (0, 2) for z in (0, 2)]
+
+
+def assert_valid(hull, points):
+    """Checks the hull is closed, faces outwards and contains every point."""
+    edges = set()
+    for face in hull.faces:
+        for a, b in zip(face, face[1:] + face[:1]):
+            assert (a, b) not in edges
+            edges.add((a, b))
+    assert all((b, a) in edges for a, b in edges)
+    # Euler's formula for a closed triangulated surface
+    assert len(hull.vertices) - len(edges) // 2 + len(hull.faces) == 2
+
+    vertices = hull.vertices
+    for face, normal in zip(hull.faces, hull.normals):
+        a, b, c = (vertices[i] for i in face)
+        assert (b - a).cross(c - a).normalized().approx_equals(normal)
+        for p in points:
+            assert normal.dot(p - a) <= 1e-9
+    for p in points:
+        assert hull.contains(p)
+    for i, vertex in zip(hull.vertex_indices, vertices):
+        assert Vector3(*points[i]) == vertex
+
+
+def test_cube():
+    points = CUBE + [Vector3(1, 1, 1), Vector3(0.5, 1.5, 0.2), Vector3(1, 1, 0), Vector3(0, 1, 1)]
+    hull = ConvexHull(points)
+    assert_valid(hull, points)
+    assert sorted(hull.vertex_indices) == list(range(8))
+    assert len(hull.faces) == 12
+    assert hull.volume() == pytest.approx(8)
+    assert hull.surface_area() == pytest.approx(24)
+    assert hull.centroid().approx_equals(Vector3(1, 1, 1))
+    assert repr(hull) == "ConvexHull(vertices=8, faces=12)"
+
+    assert hull.contains(Vector3(1, 1, 1))
+    assert hull.contains(Vector3(2, 2, 2))
+    assert not hull.contains(Vector3(2.01, 1, 1))
+    assert not hull.contains(Vector3(-1, -1, -1))
+
+
+def test_tetrahedron():
+    points = [Vector3(0, 0, 0), Vector3(3, 0, 0), Vector3(0, 3, 0), Vector3(0, 0, 3)]
+    hull = ConvexHull(points)
+    assert_valid(hull, points)
+    assert hull.volume() == pytest.approx(4.5)
+    assert hull.centroid().approx_equals(Vector3(0.75, 0.75, 0.75))
+    assert hull.surface_area() == pytest.approx(13.5 + 4.5 * sqrt(3))
+
+
+def test_sphere_points():
+    rng = random.Random(4)
+    points = []
+    for _ in range(400):
+        v = Vector3(rng.gauss(0, 1), rng.gauss(0, 1), rng.gauss(0, 1)).normalized()
+        points.append(v * (3 if rng.random() < 0.5 else rng.random() * 3) + Vector3(100, -50, 20))
+    hull = ConvexHull(points)
+    assert_valid(hull, points)
+    assert hull.volume() < 4 / 3 * pi * 27
+    assert hull.volume() > 0.9 * 4 / 3 * pi * 27
+    assert hull.centroid().approx_equals(Vector3(100, -50, 20), abs_tol=0.1)
+
+
+def test_point_formats():
+    assert ConvexHull([p.tuple() for p in CUBE]).volume() == pytest.approx(8)
+
+
+def test_degenerate():
+    # Duplicates and points on faces, edges and vertices are ignored
+    points = CUBE * 3 + [Vector3(1, 1, 0), Vector3(1, 0, 0), Vector3(2, 2, 1)]
+    hull = ConvexHull(points)
+    assert_valid(hull, points)
+    assert len(hull.vertices) == 8
+
+    # A grid has many coplanar points on each face
+    grid = [Vector3(x, y, z) for x in range(4) for y in range(4) for z in range(4)]
+    hull = ConvexHull(grid)
+    assert_valid(hull, grid)
+    assert hull.volume() == pytest.approx(27)
+
+    with pytest.raises(ValueError, match="coplanar"):
+        ConvexHull([Vector3(x, y, 1) for x in range(3) for y in range(3)])
+    with pytest.raises(ValueError, match="collinear"):
+        ConvexHull([Vector3(t, 2 * t, 3 * t) for t in range(5)])
+    with pytest.raises(ValueError, match="same"):
+        ConvexHull([Vector3(1, 2, 3)] * 5)
+    with pytest.raises(ValueError, match="at least 4"):
+        ConvexHull(CUBE[:3])
+    with pytest.raises(ValueError, match="finite"):
+        ConvexHull(CUBE + [Vector3(float("nan"), 0, 0)])
@@ -0,0 +1,121 @@
+import math
+import pytest
+from math import pi
+from deuterium import Isometry3, Joint, KinematicChain, Vector3
+
+
+# A UR5-like arm's standard DH parameters (a, alpha, d, theta)
+UR5 = [
+    (0, pi / 2, 0.089159, 0),
+    (-0.425, 0, 0, 0),
+    (-0.39225, 0, 0, 0),
+    (0, pi / 2, 0.10915, 0),
+    (0, -pi / 2, 0.09465, 0),
+    (0, 0, 0.0823, 0),
+]
+Q = [0.3, -1.1, 0.9, -0.4, 1.2, 0.5]
+NEAR_Q = [0.1, -0.9, 0.7, -0.2, 1.0, 0.3]
+
+
+def test_full_pose():
+    chain = KinematicChain.from_dh(UR5)
+    target = chain.forward(Q)
+    for method in ("dls", "lm"):
+        result = chain.inverse(target, NEAR_Q, method=method, tolerance=1e-9, angular_tolerance=1e-9)
+        assert result.converged
+        assert 0 < result.iterations <= 100
+        assert result.position_error <= 1e-9 and result.orientation_error <= 1e-9
+        assert result.pose.approx_equals(target, abs_tol=1e-8)
+        assert chain.forward(result.q).approx_equals(result.pose, abs_tol=1e-12)
+
+
+def test_position_only():
+    chain = KinematicChain.from_dh(UR5)
+    target = chain.forward(Q).translation
+    for method in ("dls", "lm"):
+        result = chain.inverse(target, method=method)
+        assert result.converged
+        assert result.orientation_error is None
+        assert result.position_error <= 1e-6
+        assert result.pose.translation.approx_equals(target, abs_tol=1e-6)
+
+    # A planar two link arm reaches any point within its annulus
+    planar = KinematicChain.from_dh([(1, 0, 0, 0), (0.5, 0, 0, 0)])
+    result = planar.inverse(Vector3(0.3, 1.1, 0), [0.1, 0.5])
+    assert result.converged
+    assert planar.forward(result.q).translation.approx_equals(Vector3(0.3, 1.1, 0), abs_tol=1e-6)
+
+
+def test_tiny_damping():
+    # A straight arm is singular, so the first steps are rejected and the
+    # damping has to grow from a tiny start
+    planar = KinematicChain.from_dh([(1, 0, 0, 0), (1, 0, 0, 0)])
+    for method in ("dls", "lm"):
+        result = planar.inverse(Vector3(1, 1, 0), [0, 0], method=method, damping=1e-150)
+        assert result.iterations <= 100
+        if method == "lm":
+            assert result.converged
+
+
+def test_already_there():
+    chain = KinematicChain.from_dh(UR5)
+    result = chain.inverse(chain.forward(Q), Q)
+    assert result.converged and result.iterations == 0
+    assert result.q == Q
+
+
+def test_joint_limits():
+    limits = [(-0.5, 0.5), (-0.2, 0.2)]
+    chain = KinematicChain.from_dh([(1, 0, 0, 0), (0.5, 0, 0, 0)], limits=limits)
+    assert [j.limits for j in chain.joints] == limits
+
+    # Reachable within the limits
+    target = chain.forward([0.4, -0.1]).translation
+    for method in ("dls", "lm"):
+        result = chain.inverse(target, method=method)
+        assert result.converged
+        assert all(lower <= v <= upper for v, (lower, upper) in zip(result.q, limits))
+
+    # Reachable without the limits but not with them
+    target = Vector3(0, 1.5, 0)
+    for method in ("dls", "lm"):
+        result = chain.inverse(target, [3, 3], method=method)
+        assert not result.converged
+        assert result.q == [0.5, 0.2]
+        assert result.position_error > 0.1
+
+    # Out of reach altogether
+    result = chain.inverse(Vector3(3, 0, 0), max_iterations=20)
+    assert not result.converged and result.iterations <= 20
+    assert result.position_error == pytest.approx(1.5, abs=1e-6)
+
+
+def test_limits_on_joints():
+    joint = Joint("prismatic", limits=(0, 0.3))
+    assert joint.limits == (0, 0.3)
+    assert Joint("revolute").limits is None
+    assert Joint.urdf("revolute", limits=(-1, 1)).limits == (-1, 1)
+    assert Joint.dh(0, 0, 0, 0, limits=(-1, 1)) != Joint.dh(0, 0, 0, 0)
+    with pytest.raises(ValueError, match="lower <= upper"):
+        Joint("revolute", limits=(1, -1))
+    with pytest.raises(ValueError, match="2 rows of parameters but 1 joint limits"):
+        KinematicChain.from_dh(UR5[:2], limits=[None])
+
+
+def test_errors():
+    chain = KinematicChain.from_dh(UR5)
+    target = chain.forward(Q)
+    with pytest.raises(TypeError, match="Isometry3 or a Vector3"):
+        chain.inverse(Q)
+    with pytest.raises(ValueError, match="method must be 'dls' or 'lm', not 'ccd'"):
+        chain.inverse(target, method="ccd")
+    with pytest.raises(ValueError, match="damping must be positive"):
+        chain.inverse(target, damping=0)
+    with pytest.raises(ValueError, match="damping must be positive"):
+        chain.inverse(target, damping=1e-200)
+    with pytest.raises(ValueError, match="non-negative"):
+        chain.inverse(target, tolerance=-1)
+    with pytest.raises(ValueError, match="Expected 6 joint values but got 2"):
+        chain.inverse(target, [0, 0])
+    with pytest.raises(ValueError, match="finite"):
+        chain.inverse(Vector3(math.nan, 0, 0))
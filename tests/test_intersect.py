@@ -0,0 +1,145 @@
+import pytest
+from math import sqrt
+from deuterium import Aabb, LineSegment, Plane, Ray, Sphere, Triangle, Vector3
+
+
+def test_ray_plane():
+    plane = Plane(Vector3(0, 0, 1), 2)
+    hit = Ray(Vector3(1, 1, 0), Vector3(0, 0, 1)).intersect(plane)
+    assert hit.distance == 2
+    assert hit.point == Vector3(1, 1, 2)
+    # The normal faces back against the ray
+    assert hit.normal == Vector3(0, 0, -1)
+    assert hit.barycentric is None
+    distance, point, normal, barycentric = hit
+    assert distance == 2
+
+    from_above = Ray(Vector3(0, 0, 5), Vector3(0, 0, -1)).intersect(plane)
+    assert from_above.normal == Vector3(0, 0, 1)
+    assert from_above.distance == 3
+
+    assert Ray(Vector3(0, 0, 0), Vector3(0, 0, -1)).intersect(plane) is None
+    assert Ray(Vector3(0, 0, 0), Vector3(1, 0, 0)).intersect(plane) is None
+    assert Ray(Vector3(1, 1, 0), Vector3(0, 0, 1)).intersect(plane, max_distance=1.5) is None
+    assert Ray(Vector3(1, 1, 0), Vector3(0, 0, 1)).intersect(plane, max_distance=2) is not None
+
+
+def test_ray_triangle():
+    tri = Triangle(Vector3(0, 0, 0), Vector3(4, 0, 0), Vector3(0, 4, 0))
+    hit = Ray(Vector3(1, 2, 5), Vector3(0, 0, -1)).intersect(tri)
+    assert hit.distance == pytest.approx(5)
+    assert hit.point.approx_equals(Vector3(1, 2, 0))
+    assert hit.normal.approx_equals(Vector3(0, 0, 1))
+    u, v = hit.barycentric
+    assert (u, v) == (pytest.approx(0.25), pytest.approx(0.5))
+    assert tri.at(u, v).approx_equals(hit.point)
+
+    # Both faces are hit, with the normal facing the ray
+    back = Ray(Vector3(1, 2, -5), Vector3(0, 0, 1)).intersect(tri)
+    assert back.normal.approx_equals(Vector3(0, 0, -1))
+
+    assert Ray(Vector3(3, 3, 5), Vector3(0, 0, -1)).intersect(tri) is None
+    assert Ray(Vector3(1, 1, 5), Vector3(0, 0, 1)).intersect(tri) is None
+    assert Ray(Vector3(1, 1, 0), Vector3(1, 0, 0)).intersect(tri) is None
+    degenerate = Triangle(Vector3(), Vector3(1, 1, 1), Vector3(2, 2, 2))
+    assert Ray(Vector3(1, 0, 0), Vector3(-1, 1, 0)).intersect(degenerate) is None
+
+
+def test_ray_sphere():
+    sphere = Sphere(Vector3(0, 0, 10), 2)
+    hit = Ray(Vector3(), Vector3(0, 0, 1)).intersect(sphere)
+    assert hit.distance == pytest.approx(8)
+    assert hit.point.approx_equals(Vector3(0, 0, 8))
+    assert hit.normal.approx_equals(Vector3(0, 0, -1))
+
+    # Starting inside hits the far side
+    inside = Ray(Vector3(0, 0, 10), Vector3(1, 0, 0)).intersect(sphere)
+    assert inside.distance == pytest.approx(2)
+    assert inside.point.approx_equals(Vector3(2, 0, 10))
+    assert inside.normal.approx_equals(Vector3(-1, 0, 0))
+
+    grazing = Ray(Vector3(2, 0, 0), Vector3(0, 0, 1)).intersect(sphere)
+    assert grazing.point.approx_equals(Vector3(2, 0, 10))
+    assert Ray(Vector3(2.1, 0, 0), Vector3(0, 0, 1)).intersect(sphere) is None
+    assert Ray(Vector3(), Vector3(0, 0, -1)).intersect(sphere) is None
+
+
+def test_ray_aabb():
+    box = Aabb(Vector3(1, -1, -1), Vector3(3, 1, 1))
+    hit = Ray(Vector3(), Vector3(1, 0, 0)).intersect(box)
+    assert hit.distance == 1
+    assert hit.point == Vector3(1, 0, 0)
+    assert hit.normal == Vector3(-1, 0, 0)
+
+    diagonal = Ray(Vector3(0, -2, 0), Vector3(1, 1, 0)).intersect(box)
+    assert diagonal.distance == pytest.approx(sqrt(2))
+    assert diagonal.point.approx_equals(Vector3(1, -1, 0))
+
+    inside = Ray(Vector3(2, 0, 0), Vector3(0, 1, 0)).intersect(box)
+    assert inside.distance == 1
+    assert inside.normal == Vector3(0, -1, 0)
+
+    assert Ray(Vector3(), Vector3(-1, 0, 0)).intersect(box) is None
+    assert Ray(Vector3(0, 2, 0), Vector3(1, 0, 0)).intersect(box) is None
+    assert Ray(Vector3(0, -5, 0), Vector3(1, 1, 0)).intersect(box) is None
+
+
+def test_ray_intersect_type():
+    with pytest.raises(TypeError, match="Ray"):
+        Ray(Vector3(), Vector3(1, 0, 0)).intersect(Vector3())
+
+
+def test_segment_closest_points():
+    a = LineSegment(Vector3(0, 0, 0), Vector3(4, 0, 0))
+    b = LineSegment(Vector3(2, -1, 3), Vector3(2, 1, 3))
+    point, other_point, distance = a.closest_points(b)
+    assert point == Vector3(2, 0, 0)
+    assert other_point == Vector3(2, 0, 3)
+    assert distance == 3
+
+    # Closest points are clamped to the segments' ends
+    c = LineSegment(Vector3(6, 2, 0), Vector3(8, 5, 0))
+    result = a.closest_points(c)
+    assert result.point == Vector3(4, 0, 0)
+    assert result.other_point == Vector3(6, 2, 0)
+    assert result.distance == pytest.approx(sqrt(8))
+
+    parallel = LineSegment(Vector3(1, 1, 0), Vector3(3, 1, 0))
+    result = a.closest_points(parallel)
+    assert result.distance == pytest.approx(1)
+
+    point_segment = LineSegment(Vector3(1, 1, 1), Vector3(1, 1, 1))
+    assert a.closest_points(point_segment).point == Vector3(1, 0, 0)
+
+    assert a.closest_point(Vector3(-3, 1, 0)) == Vector3(0, 0, 0)
+    assert a.closest_point(Vector3(3, 1, 0)) == Vector3(3, 0, 0)
+
+
+def test_ray_closest_points():
+    a = Ray(Vector3(0, 0, 0), Vector3(1, 0, 0))
+    b = Ray(Vector3(5, -3, 2), Vector3(0, 1, 0))
+    result = a.closest_points(b)
+    assert result.point.approx_equals(Vector3(5, 0, 0))
+    assert result.other_point.approx_equals(Vector3(5, 0, 2))
+    assert result.distance == pytest.approx(2)
+
+    # Closest points can't be behind either origin
+    c = Ray(Vector3(-5, 3, 0), Vector3(0, 1, 0))
+    result = a.closest_points(c)
+    assert result.point == Vector3(0, 0, 0)
+    assert result.other_point == Vector3(-5, 3, 0)
+
+    assert a.closest_point(Vector3(-2, 1, 0)) == Vector3(0, 0, 0)
+    assert a.closest_point(Vector3(2, 1, 0)) == Vector3(2, 0, 0)
+
+
+def test_triangle_closest_point():
+    tri = Triangle(Vector3(0, 0, 0), Vector3(4, 0, 0), Vector3(0, 4, 0))
+    # Face, edge and vertex regions
+    assert tri.closest_point(Vector3(1, 1, 5)) == Vector3(1, 1, 0)
+    assert tri.closest_point(Vector3(2, -3, 1)) == Vector3(2, 0, 0)
+    assert tri.closest_point(Vector3(-1, 2, 0)) == Vector3(0, 2, 0)
+    assert tri.closest_point(Vector3(3, 3, 0)).approx_equals(Vector3(2, 2, 0))
+    assert tri.closest_point(Vector3(-1, -1, -1)) == Vector3(0, 0, 0)
+    assert tri.closest_point(Vector3(6, -1, 0)) == Vector3(4, 0, 0)
+    assert tri.closest_point(Vector3(-1, 6, 0)) == Vector3(0, 4, 0)
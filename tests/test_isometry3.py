@@ -1,9 +1,21 @@
 import pytest
-from deuterium import Isometry3, Matrix4, Vector3
+from math import radians
+from deuterium import Isometry3, Matrix4, UnitQuaternion, Vector3
 
 
 def test_identity():
     assert Isometry3.identity()
+    assert Isometry3() == Isometry3.identity()
+
+
+def test_constructor():
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(90))
+    iso = Isometry3(Vector3(1, 2, 3), q)
+    assert iso.translation == Vector3(1, 2, 3)
+    assert iso.rotation == q
+    assert (iso * Vector3(1, 0, 0)).approx_equals(Vector3(1, 3, 3))
+    assert Isometry3(Vector3(1, 2, 3)) == Isometry3.from_translation(Vector3(1, 2, 3))
+    assert Isometry3(rotation=q).translation == Vector3()
 
 
 # def test_repr():
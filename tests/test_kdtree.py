@@ -0,0 +1,102 @@
+import array
+import random
+import threading
+import pytest
+from deuterium import KdTree, Vector3
+
+
+def random_points(n, seed=1):
+    rng = random.Random(seed)
+    return [Vector3(rng.uniform(-10, 10), rng.uniform(-10, 10), rng.uniform(-10, 10)) for _ in range(n)]
+
+
+POINTS = random_points(500)
+QUERIES = random_points(50, seed=2)
+TREE = KdTree(POINTS)
+
+
+def brute_force(query):
+    return sorted((p.distance_to(query), i) for i, p in enumerate(POINTS))
+
+
+def test_nearest():
+    assert len(TREE) == 500
+    for q in QUERIES:
+        index, distance = TREE.nearest(q)
+        expected_distance, expected_index = brute_force(q)[0]
+        assert index == expected_index
+        assert distance == pytest.approx(expected_distance)
+    result = TREE.nearest(POINTS[17])
+    assert result.index == 17 and result.distance == 0
+    assert TREE.point(17) == POINTS[17]
+    assert TREE.point(-1) == POINTS[-1]
+
+
+def test_k_nearest():
+    for q in QUERIES:
+        indices, distances = TREE.k_nearest(q, 7)
+        expected = brute_force(q)[:7]
+        assert indices == [i for _, i in expected]
+        assert distances == pytest.approx([d for d, _ in expected])
+    assert TREE.k_nearest(QUERIES[0], 0) == ([], [])
+    everything = TREE.k_nearest(QUERIES[0], 1000)
+    assert len(everything.indices) == 500
+    assert everything.distances == sorted(everything.distances)
+
+
+def test_within_radius():
+    for q in QUERIES:
+        indices, distances = TREE.within_radius(q, 4.0)
+        expected = [(d, i) for d, i in brute_force(q) if d <= 4.0]
+        assert indices == [i for _, i in expected]
+        assert distances == pytest.approx([d for d, _ in expected])
+    assert TREE.within_radius(Vector3(100, 100, 100), 1.0) == ([], [])
+    with pytest.raises(ValueError, match="radius"):
+        TREE.within_radius(Vector3(), -1)
+
+
+def test_batch_queries():
+    flat = array.array("d", [c for p in QUERIES for c in p])
+    buffer = memoryview(flat).cast("B").cast("d", shape=[len(QUERIES), 3])
+    for queries in (QUERIES, [q.tuple() for q in QUERIES], buffer):
+        indices, distances = TREE.nearest_batch(queries)
+        assert indices == [TREE.nearest(q).index for q in QUERIES]
+        assert distances == [TREE.nearest(q).distance for q in QUERIES]
+
+    indices, distances = TREE.k_nearest_batch(QUERIES, 3)
+    assert indices == [TREE.k_nearest(q, 3).indices for q in QUERIES]
+    assert distances == [TREE.k_nearest(q, 3).distances for q in QUERIES]
+
+    result = TREE.within_radius_batch(QUERIES, 3.0)
+    assert result.indices == [TREE.within_radius(q, 3.0).indices for q in QUERIES]
+    assert TREE.nearest_batch([]) == ([], [])
+
+
+def test_batch_queries_in_threads():
+    results = [None] * 4
+
+    def run(i):
+        results[i] = TREE.k_nearest_batch(QUERIES, 5).indices
+
+    threads = [threading.Thread(target=run, args=(i,)) for i in range(4)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+    assert all(r == results[0] for r in results)
+
+
+def test_degenerate():
+    duplicates = KdTree([Vector3(1, 1, 1)] * 10 + [Vector3(2, 2, 2)])
+    assert duplicates.nearest(Vector3(1, 1, 1)) == (0, 0)
+    assert duplicates.within_radius(Vector3(1, 1, 1), 0).indices == list(range(10))
+    assert duplicates.k_nearest(Vector3(3, 3, 3), 2).indices == [10, 0]
+
+    empty = KdTree([])
+    assert len(empty) == 0
+    assert empty.k_nearest(Vector3(), 3) == ([], [])
+    with pytest.raises(ValueError, match="empty"):
+        empty.nearest(Vector3())
+    with pytest.raises(ValueError, match="finite"):
+        KdTree([Vector3(float("nan"), 0, 0)])
+    assert repr(duplicates) == "KdTree(11 points)"
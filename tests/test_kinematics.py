@@ -0,0 +1,124 @@
+import math
+import pytest
+from math import cos, pi, sin
+from deuterium import Isometry3, Joint, KinematicChain, UnitQuaternion, Vector3
+
+
+# A UR5-like arm's standard DH parameters (a, alpha, d, theta)
+UR5 = [
+    (0, pi / 2, 0.089159, 0),
+    (-0.425, 0, 0, 0),
+    (-0.39225, 0, 0, 0),
+    (0, pi / 2, 0.10915, 0),
+    (0, -pi / 2, 0.09465, 0),
+    (0, 0, 0.0823, 0),
+]
+Q = [0.3, -1.1, 0.9, -0.4, 1.2, 0.5]
+
+
+def rotated_z(angle):
+    iso = Isometry3.identity()
+    iso.rotation = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), angle)
+    return iso
+
+
+def planar_position(q1, q2):
+    return Vector3(cos(q1) + 0.5 * cos(q1 + q2), sin(q1) + 0.5 * sin(q1 + q2), 0)
+
+
+def test_dh_planar_arm():
+    chain = KinematicChain.from_dh([(1, 0, 0, 0), (0.5, 0, 0, 0)])
+    assert chain.dof == len(chain) == 2
+    assert repr(chain) == "KinematicChain(2 joints, 2 dof)"
+    for q in ([0, 0], [0.4, -1.2], [pi / 2, pi / 2]):
+        pose = chain.forward(q)
+        assert pose.translation.approx_equals(planar_position(*q), abs_tol=1e-12)
+        assert pose.rotation.approx_equals(rotated_z(q[0] + q[1]).rotation, abs_tol=1e-12)
+        links = chain.link_poses(q)
+        assert len(links) == 2
+        assert links[0].translation.approx_equals(Vector3(cos(q[0]), sin(q[0]), 0), abs_tol=1e-12)
+        assert links[1].approx_equals(pose)
+
+
+def test_modified_dh_matches_standard():
+    # Craig's convention attaches each a and alpha to the next joint, so the
+    # last link's needs a tool transform
+    standard = KinematicChain.from_dh(UR5)
+    modified_rows = [(0, 0, UR5[0][2], 0)] + [(UR5[i - 1][0], UR5[i - 1][1], UR5[i][2], 0) for i in range(1, 6)]
+    tool = Isometry3.from_translation(Vector3(UR5[5][0], 0, 0))
+    tool.rotation = UnitQuaternion.from_axis_angle(Vector3(1, 0, 0), UR5[5][1])
+    modified = KinematicChain.from_dh(modified_rows, modified=True, tool=tool)
+    assert modified.forward(Q).approx_equals(standard.forward(Q), abs_tol=1e-12)
+
+
+def test_urdf_joints():
+    joints = [
+        Joint.urdf("revolute", axis=(0, 0, 1)),
+        Joint.urdf("continuous", xyz=(1, 0, 0), axis=(0, 0, 1)),
+        Joint.urdf("fixed", xyz=(0.5, 0, 0)),
+    ]
+    chain = KinematicChain(joints)
+    assert chain.dof == 2 and len(chain) == 3
+    assert [j.kind for j in chain.joints] == ["revolute", "revolute", "fixed"]
+    q = [0.4, -1.2]
+    assert chain.forward(q).translation.approx_equals(planar_position(*q), abs_tol=1e-12)
+    assert len(chain.link_poses(q)) == 3
+
+    # rpy are fixed-axis roll, pitch and yaw
+    joint = Joint.urdf("fixed", rpy=(0.1, 0.2, 0.3))
+    expected = (
+        UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), 0.3)
+        * UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), 0.2)
+        * UnitQuaternion.from_axis_angle(Vector3(1, 0, 0), 0.1)
+    )
+    assert joint.transform().rotation.approx_equals(expected, abs_tol=1e-12)
+    assert Joint.urdf("revolute").axis == Vector3(1, 0, 0)
+
+
+def test_prismatic_and_base():
+    joint = Joint("prismatic", Vector3(0, 0, 2), offset=0.5)
+    assert joint.axis == Vector3(0, 0, 1)
+    assert joint.transform(1).translation == Vector3(0, 0, 1.5)
+    assert Joint.dh(0.2, 0, 0.1, 0, "prismatic").transform(0.3).translation.approx_equals(Vector3(0.2, 0, 0.4))
+
+    base = Isometry3.from_translation(Vector3(0, 0, 1))
+    chain = KinematicChain([Joint("revolute"), joint], base=base, tool=Isometry3.from_translation(Vector3(1, 0, 0)))
+    pose = chain.forward([pi / 2, 0.25])
+    assert pose.translation.approx_equals(Vector3(0, 1, 1.75), abs_tol=1e-12)
+    assert chain.base == base
+
+
+def test_jacobian():
+    h = 1e-6
+    for chain in (
+        KinematicChain.from_dh(UR5),
+        KinematicChain.from_dh(UR5[:3] + [(0.1, 0.3, 0.2, 0)], ["revolute", "prismatic", "revolute", "prismatic"]),
+        KinematicChain([Joint.urdf("revolute", axis=(0, 1, 0)), Joint.urdf("fixed", xyz=(0, 0, 1)), Joint.urdf("prismatic", rpy=(0.3, 0, 0), axis=(0, 0, 1))]),
+    ):
+        q = Q[: chain.dof]
+        jacobian = chain.jacobian(q)
+        assert len(jacobian) == 6 and all(len(row) == chain.dof for row in jacobian)
+        pose = chain.forward(q)
+        for i in range(chain.dof):
+            moved = chain.forward([v + h if j == i else v for j, v in enumerate(q)])
+            linear = (moved.translation - pose.translation) / h
+            delta = moved.rotation * pose.rotation.inverse()
+            angular = delta.axis() * (delta.angle() / h) if delta.axis() else Vector3(0, 0, 0)
+            column = [row[i] for row in jacobian]
+            assert column[:3] == pytest.approx(list(linear), abs=1e-5)
+            assert column[3:] == pytest.approx(list(angular), abs=1e-5)
+
+
+def test_errors():
+    chain = KinematicChain.from_dh(UR5)
+    with pytest.raises(ValueError, match="Expected 6 joint values but got 5"):
+        chain.forward(Q[:5])
+    with pytest.raises(ValueError, match="finite"):
+        chain.jacobian(Q[:5] + [math.nan])
+    with pytest.raises(ValueError, match="'revolute', 'prismatic' or 'fixed', not 'spherical'"):
+        Joint("spherical")
+    with pytest.raises(ValueError, match="non-zero"):
+        Joint("revolute", Vector3(0, 0, 0))
+    with pytest.raises(ValueError, match="2 rows of parameters but 1 joint kinds"):
+        KinematicChain.from_dh(UR5[:2], ["revolute"])
+    assert KinematicChain([]).forward([]) == Isometry3.identity()
@@ -0,0 +1,67 @@
+import pickle
+import pytest
+from math import radians
+from deuterium import Matrix3, UnitQuaternion, Vector3
+
+
+ROWS = [[1, 2, 3], [4, 5, 6], [7, 8, 10]]
+
+
+def test_constructor():
+    assert Matrix3() == Matrix3.identity()
+    m = Matrix3(ROWS)
+    assert m.list() == ROWS
+    assert Matrix3(m.tuple()) == m
+    assert Matrix3.from_columns(ROWS) == m.transposed()
+    assert pickle.loads(pickle.dumps(m)) == m
+    assert len(m) == 3
+    assert list(m) == [(1, 2, 3), (4, 5, 6), (7, 8, 10)]
+
+    with pytest.raises(ValueError, match="3 rows but got 2"):
+        Matrix3(ROWS[:2])
+    with pytest.raises(ValueError, match="row 1 has 4"):
+        Matrix3([[1, 2, 3], [1, 2, 3, 4], [1, 2, 3]])
+
+
+def test_indexing():
+    m = Matrix3(ROWS)
+    assert m[1, 2] == 6
+    assert m[-1] == (7, 8, 10)
+    assert m[:, 0] == (1, 4, 7)
+    assert m[:2, 1:] == ((2, 3), (5, 6))
+    assert m.row(0) == (1, 2, 3)
+    assert m.column(-1) == (3, 6, 10)
+    with pytest.raises(IndexError):
+        m[3, 0]
+
+
+def test_arithmetic():
+    m = Matrix3(ROWS)
+    assert m * Vector3(1, 0, -1) == Vector3(-2, -2, -3)
+    assert m * Matrix3.identity() == m
+    assert 2 * m == m * 2 == m + m
+    assert (m - m).norm() == 0
+    assert -m / 2 == m * -0.5
+    assert m.trace() == 16
+    assert m.determinant() == pytest.approx(-3)
+    assert (m * m.inverse()).approx_equals(Matrix3.identity(), abs_tol=1e-12)
+    with pytest.raises(ValueError, match="singular"):
+        Matrix3([[1, 2, 3], [2, 4, 6], [0, 0, 1]]).inverse()
+
+
+def test_rotations_and_cross_products():
+    q = UnitQuaternion.from_axis_angle(Vector3(1, 2, 3).normalized(), radians(40))
+    v = Vector3(0.3, -1, 2)
+    assert (Matrix3.from_rotation(q) * v).approx_equals(q * v)
+    assert Matrix3.cross_matrix(Vector3(1, 2, 3)) * v == Vector3(1, 2, 3).cross(v)
+
+
+def test_decompositions():
+    m = Matrix3(ROWS)
+    assert m.rank() == 3
+    assert Matrix3.cross_matrix(Vector3(1, 2, 3)).rank() == 2
+    u, s, v_t = m.svd()
+    assert s.x >= s.y >= s.z > 0
+    diagonal = Matrix3([[s.x, 0, 0], [0, s.y, 0], [0, 0, s.z]])
+    assert (u * diagonal * v_t).approx_equals(m, abs_tol=1e-12)
+    assert repr(Matrix3.identity()) == "Matrix3<1.0, 0.0, 0.0,\n        0.0, 1.0, 0.0,\n        0.0, 0.0, 1.0>"
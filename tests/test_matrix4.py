@@ -1,9 +1,73 @@
 import pytest
-from deuterium import Matrix4, Vector3
+from math import radians
+from deuterium import Isometry3, Matrix4, UnitQuaternion, Vector3
+
+
+ROWS = [[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 15, 16]]
 
 
 def test_constructor():
     assert Matrix4.identity()
+    assert Matrix4() == Matrix4.identity()
+    m = Matrix4(ROWS)
+    assert m.list() == ROWS
+    assert Matrix4(m.list()) == m
+    assert Matrix4(m.tuple()) == m
+
+
+def test_from_rows_and_columns():
+    assert Matrix4.from_rows(ROWS).list() == ROWS
+    columns = Matrix4.from_columns(ROWS)
+    assert columns == Matrix4.from_rows(ROWS).transposed()
+    assert columns[:, 0] == (1, 2, 3, 4)
+
+    with pytest.raises(ValueError, match="4 rows but got 3"):
+        Matrix4(ROWS[:3])
+
+    with pytest.raises(ValueError, match="row 1 has 3"):
+        Matrix4.from_rows([[1, 2, 3, 4], [1, 2, 3], [1, 2, 3, 4], [1, 2, 3, 4]])
+
+    with pytest.raises(ValueError, match="column 0 has 5"):
+        Matrix4.from_columns([[1, 2, 3, 4, 5]] + ROWS[1:])
+
+    with pytest.raises(TypeError):
+        Matrix4([[1, 2, 3, "a"]] + ROWS[1:])
+
+    with pytest.raises(TypeError):
+        Matrix4(5)
+
+
+def test_from_flat():
+    flat = list(range(16))
+    assert Matrix4.from_flat(flat)[1] == (4, 5, 6, 7)
+    assert Matrix4.from_flat(flat, order="row")[1] == (4, 5, 6, 7)
+    assert Matrix4.from_flat(flat, order="column")[:, 1] == (4, 5, 6, 7)
+
+    with pytest.raises(ValueError):
+        Matrix4.from_flat(flat[:15])
+
+    with pytest.raises(ValueError, match="order"):
+        Matrix4.from_flat(flat, order="diagonal")
+
+
+def test_from_transforms():
+    q = UnitQuaternion.from_axis_angle(Vector3(1, 2, 3), radians(70))
+    iso = Isometry3.from_translation(Vector3(4, 5, 6))
+    iso.rotation = q
+    v = Vector3(-1, 0.5, 2)
+
+    assert (Matrix4.from_isometry(iso) * v).approx_equals(iso * v)
+    assert Matrix4.from_isometry(iso).translation == Vector3(4, 5, 6)
+    assert (Matrix4.from_rotation(q) * v).approx_equals(q * v)
+    assert Matrix4.from_axis_angle(Vector3(1, 2, 3), radians(70)).approx_equals(
+        Matrix4.from_rotation(q)
+    )
+    with pytest.raises(ValueError, match="Rotation axis must have a non-zero length"):
+        Matrix4.from_axis_angle(Vector3(0, 0, 0), 1)
+    with pytest.raises(ValueError, match="Rotation axis must be finite"):
+        Matrix4.from_axis_angle(Vector3(float("nan"), 0, 1), 1)
+    assert Matrix4.from_scale(Vector3(2, 3, 4)) * Vector3(1, 1, 1) == Vector3(2, 3, 4)
+    assert Matrix4.from_scale(Vector3(2, 3, 4))[3] == (0, 0, 0, 1)
 
 
 # def test_repr():
@@ -38,11 +102,57 @@ def test_indexing():
         m[4, 1]
 
     with pytest.raises(IndexError):
-        m[1, -1]
+        m[1, -5]
+
+    with pytest.raises(IndexError):
+        m[-5, 1]
+
+    with pytest.raises(IndexError):
+        m[1, 2, 3]
+
+    with pytest.raises(TypeError):
+        m["a"]
+
+
+def numbered():
+    m = Matrix4.identity()
+    for a in range(4):
+        for b in range(4):
+            m[a, b] = a * 10 + b
+    return m
+
+
+def test_negative_indexing():
+    m = numbered()
+    assert m[-1] == (30, 31, 32, 33)
+    assert m[1, -1] == 13
+    assert m[-1, 1] == 31
+    assert m[-4, -4] == 0
+
+
+def test_rows_and_columns():
+    m = numbered()
+    assert m[:, 2] == (2, 12, 22, 32)
+    assert m[:, -1] == (3, 13, 23, 33)
+    assert m[1, 1:3] == (11, 12)
+    assert m[1:3, 0] == (10, 20)
+    assert m[::2, ::2] == ((0, 2), (20, 22))
+    assert m[2:] == ((20, 21, 22, 23), (30, 31, 32, 33))
+    assert m.row(2) == m[2] == (20, 21, 22, 23)
+    assert m.row(-1) == m[3]
+    assert m.column(1) == m[:, 1] == (1, 11, 21, 31)
 
+    with pytest.raises(IndexError):
+        m.row(4)
 
     with pytest.raises(IndexError):
-        m[-1, 1]
+        m.column(-5)
+
+
+def test_iteration():
+    m = numbered()
+    assert list(m) == list(m.tuple())
+    assert [row[0] for row in m] == [0, 10, 20, 30]
 
 
 def test_index_mutation():
@@ -55,6 +165,50 @@ def test_index_mutation():
         for b in range(4):
             assert m[a, b] == a * 10 + b
 
+    m[-1, -1] = 99
+    assert m[3, 3] == 99
+
+    with pytest.raises(IndexError):
+        m[4, 0] = 1
+
+    with pytest.raises(IndexError):
+        m[0, -5] = 1
+
+    with pytest.raises(TypeError):
+        m[0, 0] = "a"
+
+
+def test_row_and_column_mutation():
+    m = Matrix4.identity()
+    m[1] = (1, 2, 3, 4)
+    assert m[1] == (1, 2, 3, 4)
+    m[:, 2] = [5, 6, 7, 8]
+    assert m[:, 2] == (5, 6, 7, 8)
+    m[0, 1:3] = (-1, -2)
+    assert m[0] == (1, -1, -2, 0)
+    m[2:, 2:] = ((0, 0), (0, 0))
+    assert m[2:, 2:] == ((0, 0), (0, 0))
+
+    m.set_row(-1, (9, 9, 9, 9))
+    assert m[3] == (9, 9, 9, 9)
+    m.set_column(0, [0, 0, 0, 0])
+    assert m[:, 0] == (0, 0, 0, 0)
+
+    before = m.tuple()
+    with pytest.raises(ValueError):
+        m[1] = (1, 2, 3)
+
+    with pytest.raises(ValueError):
+        m.set_column(1, (1, 2, 3, 4, 5))
+
+    with pytest.raises(ValueError):
+        m[:2, :2] = ((1, 2), (3,))
+
+    with pytest.raises(IndexError):
+        m.set_row(4, (1, 2, 3, 4))
+
+    assert m.tuple() == before
+
 
 def test_translation():
     m = Matrix4.identity()
@@ -88,3 +242,63 @@ def test_transpose():
 #     assert -v == Vector3(-10, -20, -30)
 #     v.negate()
 #     assert v == Vector3(-10, -20, -30)
+
+
+def test_arithmetic():
+    a = Matrix4(ROWS)
+    i = Matrix4.identity()
+    assert (a + i)[0] == (2, 2, 3, 4)
+    assert (a - i)[0] == (0, 2, 3, 4)
+    assert (-a)[1] == (-5, -6, -7, -8)
+    assert (a * 2)[1] == (10, 12, 14, 16)
+    assert 2 * a == a * 2
+    assert (a / 2)[0] == (0.5, 1, 1.5, 2)
+    assert a * i == a
+
+    m = Matrix4(ROWS)
+    m += i
+    assert m == a + i
+    m -= i
+    assert m == a
+    m *= 3
+    assert m == a * 3
+    m /= 3
+    assert m == a
+    m *= i
+    assert m == a
+    m *= m
+    assert m == a * a
+
+    with pytest.raises(TypeError):
+        m *= "a"
+
+    with pytest.raises(TypeError):
+        a + 1
+
+
+def test_element_wise():
+    a = Matrix4(ROWS)
+    assert a.component_mul(a)[0] == (1, 4, 9, 16)
+    assert abs(-a) == a
+    assert (-a).abs() == a
+    assert a.max() == 16
+    assert (-a).min() == -16
+
+
+def test_reductions():
+    a = Matrix4(ROWS)
+    assert a.trace() == 1 + 6 + 11 + 16
+    assert a.norm_squared() == sum(v * v for v in range(1, 17))
+    assert a.norm() == pytest.approx(a.norm_squared() ** 0.5)
+    assert Matrix4.identity().norm() == 2
+
+
+def test_is_identity():
+    m = Matrix4.identity()
+    assert m.is_identity()
+    m[0, 3] = 1e-10
+    assert m.is_identity()
+    m[0, 3] = 1e-5
+    assert not m.is_identity()
+    assert m.is_identity(1e-4)
+    assert not Matrix4(ROWS).is_identity()
@@ -0,0 +1,118 @@
+import array
+import random
+import pytest
+from math import radians
+from deuterium import (
+    Isometry3,
+    PinholeCamera,
+    UnitQuaternion,
+    Vector3,
+    solve_pnp,
+    solve_pnp_ransac,
+)
+
+
+def camera():
+    return PinholeCamera(500, 480, 320, 240, 640, 480)
+
+
+POSE = Isometry3(Vector3(0.3, -0.2, 5), UnitQuaternion.from_axis_angle(Vector3(1, -2, 0.5), radians(25)))
+
+
+def scene(n=20, planar=False, seed=1):
+    rng = random.Random(seed)
+    return [
+        Vector3(rng.uniform(-1, 1), rng.uniform(-1, 1), 0 if planar else rng.uniform(-1, 1))
+        for _ in range(n)
+    ]
+
+
+def test_solve_pnp():
+    cam = camera()
+    for planar in (False, True):
+        for n in (4, 6, 20):
+            points = scene(n, planar)
+            pixels = cam.project_batch(points, POSE)
+            result = solve_pnp(points, pixels, cam)
+            assert result.pose.approx_equals(POSE, abs_tol=1e-8)
+            assert result.inliers == [True] * n
+            assert result.reprojection_error < 1e-8
+
+    # Buffers and plain sequences work too
+    points = scene()
+    pixels = cam.project_batch(points, POSE)
+    flat = array.array("d", [c for uv in pixels for c in uv])
+    buffer = memoryview(flat).cast("B").cast("d", shape=[len(pixels), 2])
+    result = solve_pnp([[p.x, p.y, p.z] for p in points], buffer, cam)
+    assert result.pose.approx_equals(POSE, abs_tol=1e-8)
+
+    # The pose agrees with the camera's own projection
+    for p, uv in zip(points, pixels):
+        assert cam.project(p, result.pose) == pytest.approx(uv)
+
+
+def test_noise():
+    cam = camera()
+    rng = random.Random(2)
+    points = scene(50)
+    pixels = [(u + rng.gauss(0, 0.5), v + rng.gauss(0, 0.5)) for u, v in cam.project_batch(points, POSE)]
+    result = solve_pnp(points, pixels, cam)
+    assert 0.5 < result.reprojection_error < 0.9
+    assert result.pose.translation.approx_equals(POSE.translation, abs_tol=0.05)
+    assert abs((result.pose.rotation.inverse() * POSE.rotation).angle()) < radians(0.5)
+
+    # Refinement can only lower the error of the initial estimate
+    unrefined = solve_pnp(points, pixels, cam, max_iterations=0)
+    assert result.reprojection_error <= unrefined.reprojection_error
+
+
+def test_ransac():
+    cam = camera()
+    rng = random.Random(3)
+    points = scene(60)
+    pixels = cam.project_batch(points, POSE)
+    outliers = set(rng.sample(range(60), 20))
+    for i in outliers:
+        pixels[i] = (rng.uniform(0, 640), rng.uniform(0, 480))
+
+    result = solve_pnp_ransac(points, pixels, cam, 1.0)
+    assert result.pose.approx_equals(POSE, abs_tol=1e-8)
+    assert result.inliers == [i not in outliers for i in range(60)]
+    assert result.reprojection_error < 1e-8
+    assert solve_pnp_ransac(points, pixels, cam, 1.0) == result
+    assert solve_pnp_ransac(points, pixels, cam, 1.0, seed=7).inliers == result.inliers
+
+    # Without RANSAC the outliers pull the pose away
+    assert not solve_pnp(points, pixels, cam).pose.approx_equals(POSE, abs_tol=1e-3)
+
+    # Planar scenes work the same way
+    points = scene(40, planar=True)
+    pixels = cam.project_batch(points, POSE)
+    pixels[0] = (0, 0)
+    result = solve_pnp_ransac(points, pixels, cam, 1.0)
+    assert result.inliers == [False] + [True] * 39
+    assert result.pose.approx_equals(POSE, abs_tol=1e-8)
+
+
+def test_errors():
+    cam = camera()
+    points = scene(6)
+    pixels = cam.project_batch(points, POSE)
+    with pytest.raises(ValueError, match="Expected 6 pixels but got 5"):
+        solve_pnp(points, pixels[:5], cam)
+    with pytest.raises(ValueError, match="at least 4 points"):
+        solve_pnp(points[:3], pixels[:3], cam)
+    with pytest.raises(ValueError, match="collinear"):
+        solve_pnp([Vector3(i, 2 * i, 0) for i in range(6)], pixels, cam)
+    with pytest.raises(ValueError, match="finite"):
+        solve_pnp(points, [(float("nan"), 0)] + pixels[1:], cam)
+    with pytest.raises(ValueError, match="pixel 2 has 3"):
+        solve_pnp(points, pixels[:2] + [(1, 2, 3)] + pixels[3:], cam)
+    with pytest.raises(ValueError, match="threshold must be a positive number"):
+        solve_pnp_ransac(points, pixels, cam, 0)
+
+    # Pixels unrelated to the points leave no pose with enough inliers
+    rng = random.Random(5)
+    noise = [(rng.uniform(0, 640), rng.uniform(0, 480)) for _ in range(20)]
+    with pytest.raises(ValueError, match="Could not estimate a pose"):
+        solve_pnp_ransac(scene(20), noise, cam, 0.001)
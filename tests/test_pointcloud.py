@@ -0,0 +1,253 @@
+import os
+import pathlib
+import pickle
+import struct
+import tempfile
+import pytest
+from deuterium import KdTree, PointCloud, PointCloudFormatError, Vector3, centroid
+
+
+POINTS = [Vector3(0, 0, 0), Vector3(1.5, -2, 3), Vector3(1e-9, 1e9, -0.1)]
+NORMALS = [Vector3(0, 0, 1), Vector3(1, 0, 0), Vector3(0, -1, 0)]
+COLORS = [(255, 0, 0), (0, 128, 255), (1, 2, 3)]
+
+
+def write_file(directory, name, data):
+    path = os.path.join(directory, name)
+    with open(path, "w" if isinstance(data, str) else "wb") as f:
+        f.write(data)
+    return path
+
+
+def read_text(text, name):
+    with tempfile.TemporaryDirectory() as directory:
+        return PointCloud.read(write_file(directory, name, text))
+
+
+def read_error(text, name):
+    with pytest.raises(PointCloudFormatError) as error:
+        read_text(text, name)
+    return str(error.value)
+
+
+def test_point_cloud():
+    cloud = PointCloud(POINTS, NORMALS, COLORS)
+    assert len(cloud) == 3
+    assert cloud.points == POINTS
+    assert cloud.normals == NORMALS
+    assert cloud.colors == COLORS
+    assert repr(cloud) == "PointCloud(3 points, normals, colors)"
+    assert repr(PointCloud(POINTS)) == "PointCloud(3 points)"
+    assert PointCloud(POINTS).normals is None
+    assert PointCloud(POINTS).colors is None
+    assert pickle.loads(pickle.dumps(cloud)).colors == COLORS
+
+    with pytest.raises(ValueError, match="Expected 3 normals but got 2"):
+        PointCloud(POINTS, NORMALS[:2])
+    with pytest.raises(ValueError, match="Expected 3 colors but got 1"):
+        PointCloud(POINTS, colors=COLORS[:1])
+    with pytest.raises(ValueError, match="color 1 has 2"):
+        PointCloud(POINTS, colors=[(1, 2, 3), (1, 2), (1, 2, 3)])
+    with pytest.raises(OverflowError):
+        PointCloud(POINTS, colors=[(1, 2, 3), (1, 2, 256), (1, 2, 3)])
+
+
+def test_buffer():
+    cloud = PointCloud(POINTS)
+    view = memoryview(cloud)
+    assert view.shape == (3, 3)
+    assert view.format == "d"
+    assert view.readonly
+    assert view.tolist() == [[p.x, p.y, p.z] for p in POINTS]
+    with pytest.raises(TypeError):
+        view[0, 0] = 1.0
+
+    # Point clouds can be used wherever points are accepted
+    assert centroid(cloud) == centroid(POINTS)
+    assert KdTree(cloud).nearest(Vector3(1, -2, 3)).index == 1
+    assert PointCloud(cloud).points == POINTS
+    assert memoryview(PointCloud([])).shape == (0, 3)
+
+
+def test_round_trip():
+    clouds = [PointCloud(POINTS), PointCloud(POINTS, NORMALS), PointCloud(POINTS, NORMALS, COLORS), PointCloud([])]
+    cases = [
+        ("ply", [None, "ascii", "binary", "binary_little_endian", "binary_big_endian"]),
+        ("pcd", [None, "ascii", "binary"]),
+        ("xyz", [None, "ascii"]),
+    ]
+    with tempfile.TemporaryDirectory() as directory:
+        for extension, encodings in cases:
+            for encoding in encodings:
+                for cloud in clouds + [PointCloud(POINTS, colors=COLORS)] * (extension != "xyz"):
+                    path = os.path.join(directory, "cloud." + extension)
+                    cloud.write(path, encoding=encoding)
+                    result = PointCloud.read(path)
+                    assert result.points == cloud.points, (extension, encoding)
+                    assert result.normals == cloud.normals, (extension, encoding)
+                    assert result.colors == cloud.colors, (extension, encoding)
+
+        # The format can be given explicitly, and paths can be PathLike
+        path = os.path.join(directory, "cloud.txt")
+        PointCloud(POINTS).write(path, format="ply", encoding="ascii")
+        with open(path) as f:
+            assert f.read().startswith("ply\nformat ascii 1.0\nelement vertex 3\n")
+        assert PointCloud.read(pathlib.Path(path), format="ply").points == POINTS
+
+
+def test_write_errors():
+    with tempfile.TemporaryDirectory() as directory:
+        cloud = PointCloud(POINTS, colors=COLORS)
+        with pytest.raises(ValueError, match="colors along with normals"):
+            cloud.write(os.path.join(directory, "cloud.xyz"))
+        with pytest.raises(ValueError, match="XYZ files are always ascii"):
+            cloud.write(os.path.join(directory, "cloud.xyz"), encoding="binary")
+        with pytest.raises(ValueError, match="PCD encoding must be"):
+            cloud.write(os.path.join(directory, "cloud.pcd"), encoding="binary_big_endian")
+        with pytest.raises(ValueError, match="PLY encoding must be"):
+            cloud.write(os.path.join(directory, "cloud.ply"), encoding="text")
+        with pytest.raises(ValueError, match="from its extension"):
+            cloud.write(os.path.join(directory, "cloud.obj"))
+        with pytest.raises(ValueError, match="format must be"):
+            cloud.write(os.path.join(directory, "cloud.ply"), format="obj")
+        with pytest.raises(FileNotFoundError):
+            PointCloud.read(os.path.join(directory, "missing.ply"))
+
+
+PLY_HEADER = """ply
+format ascii 1.0
+comment made by hand
+element vertex 2
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+"""
+
+
+def test_read_ply():
+    cloud = read_text(PLY_HEADER + "1 2 3 10 20 30\n4 5 6 40 50 60\n3 0 1 2\n", "cloud.ply")
+    assert cloud.points == [Vector3(1, 2, 3), Vector3(4, 5, 6)]
+    assert cloud.colors == [(10, 20, 30), (40, 50, 60)]
+    assert cloud.normals is None
+
+    # Binary, with faces before the vertices and float colors
+    header = (
+        "ply\nformat binary_big_endian 1.0\n"
+        "element face 2\nproperty list uchar int vertex_indices\n"
+        "element vertex 1\nproperty double z\nproperty float red\nproperty float green\n"
+        "property float blue\nproperty double y\nproperty short x\n"
+        "end_header\n"
+    ).encode()
+    faces = struct.pack(">B3i", 3, 0, 0, 0) + struct.pack(">B", 0)
+    vertex = struct.pack(">dfffdh", 3, 1, 0.5, 0, 2, -1)
+    with tempfile.TemporaryDirectory() as directory:
+        cloud = PointCloud.read(write_file(directory, "cloud.ply", header + faces + vertex))
+        assert cloud.points == [Vector3(-1, 2, 3)]
+        assert cloud.colors == [(255, 128, 0)]
+
+        with pytest.raises(PointCloudFormatError, match="unexpected end of file in vertex 0"):
+            PointCloud.read(write_file(directory, "cloud.ply", header + faces + vertex[:-1]))
+
+
+def test_ply_errors():
+    assert "the first line must be 'ply'" in read_error("plx\n", "cloud.ply")
+    assert read_error("ply\nformat ascii 1.0\nelement vertex 1\nproperty flot x\n", "a.ply").endswith(
+        "a.ply: line 4: unknown property type 'flot'"
+    )
+    assert "line 2: unsupported PLY version '2.0'" in read_error("ply\nformat ascii 2.0\nend_header\n", "a.ply")
+    assert "line 2: unknown format 'binary'" in read_error("ply\nformat binary 1.0\nend_header\n", "a.ply")
+    assert "no format line" in read_error("ply\nelement vertex 0\nend_header\n", "a.ply")
+    assert "no end_header line" in read_error("ply\nformat ascii 1.0\n", "a.ply")
+    assert "line 3: invalid count 'x'" in read_error("ply\nformat ascii 1.0\nelement vertex x\n", "a.ply")
+    assert "line 3: property before any element" in read_error("ply\nformat ascii 1.0\nproperty float x\n", "a.ply")
+    assert "line 3: unexpected header line 'vertex 3'" in read_error("ply\nformat ascii 1.0\nvertex 3\n", "a.ply")
+    assert "no vertex element" in read_error("ply\nformat ascii 1.0\nend_header\n", "a.ply")
+    header = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\n"
+    assert "some of x, y, z but not 'z'" in read_error(header + "end_header\n1 2\n", "a.ply")
+    header += "property float z\nend_header\n"
+    assert "line 8: too few values for vertex 0" in read_error(header + "1 2\n", "a.ply")
+    assert "line 8: too many values for vertex 0" in read_error(header + "1 2 3 4\n", "a.ply")
+    assert "line 8: expected a number but got 'a'" in read_error(header + "1 2 a\n", "a.ply")
+    assert "unexpected end of file in vertex 0" in read_error(header, "a.ply")
+    header = header.replace("end_header", "property list uchar int indices\nend_header")
+    assert "line 9: negative list length in vertex 0" in read_error(header + "1 2 3 -1\n", "a.ply")
+    assert "line 9: invalid list length 1.5 in vertex 0" in read_error(header + "1 2 3 1.5 7\n", "a.ply")
+    assert "line 9: invalid list length NaN in vertex 0" in read_error(header + "1 2 3 nan\n", "a.ply")
+    assert read_text(header + "1 2 3 2 7 8\n", "a.ply").points == [Vector3(1, 2, 3)]
+
+
+PCD_HEADER = """# .PCD v0.7 - Point Cloud Data file format
+VERSION 0.7
+FIELDS x y z curvature rgb
+SIZE 4 4 4 4 4
+TYPE F F F F F
+COUNT 1 1 1 1 1
+WIDTH 2
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 2
+DATA ascii
+"""
+
+
+def test_read_pcd():
+    # Packed colors may be written as integers or the floats they represent
+    red = struct.unpack("<f", struct.pack("<I", 0xFF0000))[0]
+    cloud = read_text(PCD_HEADER + "1 2 3 0.5 65280\n4 5 6 0.1 %r\n" % red, "cloud.pcd")
+    assert cloud.points == [Vector3(1, 2, 3), Vector3(4, 5, 6)]
+    assert cloud.colors == [(0, 255, 0), (255, 0, 0)]
+
+    header = (
+        "VERSION .7\nFIELDS normal_x normal_y normal_z _ x y z\nSIZE 4 4 4 1 8 8 8\n"
+        "TYPE F F F U F F F\nCOUNT 1 1 1 3 1 1 1\nWIDTH 1\nHEIGHT 1\nPOINTS 1\nDATA binary\n"
+    ).encode()
+    data = struct.pack("<fff3Bddd", 0, 0, 1, 7, 8, 9, 1.5, 2.5, 3.5)
+    with tempfile.TemporaryDirectory() as directory:
+        cloud = PointCloud.read(write_file(directory, "cloud.pcd", header + data))
+        assert cloud.points == [Vector3(1.5, 2.5, 3.5)]
+        assert cloud.normals == [Vector3(0, 0, 1)]
+        with pytest.raises(PointCloudFormatError, match="unexpected end of file in point 0"):
+            PointCloud.read(write_file(directory, "cloud.pcd", header + data[:-1]))
+
+
+def test_pcd_errors():
+    def replace(old, new):
+        return read_error(PCD_HEADER.replace(old, new) + "1 2 3 0 0\n4 5 6 0 0\n", "a.pcd")
+
+    assert "line 5: unknown header entry 'TPYE'" in replace("TYPE", "TPYE")
+    assert "SIZE has 4 entries but FIELDS has 5" in replace("SIZE 4 4 4 4 4", "SIZE 4 4 4 4")
+    assert "unsupported TYPE F with SIZE 2 for field 'curvature'" in replace("SIZE 4 4 4 4 4", "SIZE 4 4 4 2 4")
+    assert "POINTS is 3 but WIDTH x HEIGHT is 2" in replace("POINTS 2", "POINTS 3")
+    assert "binary_compressed PCD data is not supported" in replace("DATA ascii", "DATA binary_compressed")
+    assert "line 3: VERSION must come before FIELDS" in replace("VERSION 0.7\nFIELDS x y z curvature rgb", "FIELDS x y z curvature rgb\nVERSION 0.7")
+    assert "no WIDTH line" in replace("WIDTH 2\n", "")
+    assert "the fields don't include x, y and z" in replace("x y z", "a b c")
+    assert "line 12: expected 5 values but got 4" in read_error(PCD_HEADER + "1 2 3 0\n", "a.pcd")
+    assert "line 12: expected a number but got 'y'" in read_error(PCD_HEADER + "1 y 3 0 0\n", "a.pcd")
+    assert "no DATA line" in read_error("VERSION 0.7\n", "a.pcd")
+    too_many = "SIZE 4 4 4 8 4\nTYPE F F F F F\nCOUNT 1 1 1 2305843009213693953 1"
+    assert "field 'curvature' has too large a COUNT" in replace("SIZE 4 4 4 4 4\nTYPE F F F F F\nCOUNT 1 1 1 1 1", too_many)
+    assert "COUNTs are too large" in replace("COUNT 1 1 1 1 1", "COUNT 1 1 1 1 18446744073709551615")
+
+
+def test_read_xyz():
+    cloud = read_text("# a comment\n1 2 3\n\n4,5,6\n  7\t8 9\n", "cloud.xyz")
+    assert cloud.points == [Vector3(1, 2, 3), Vector3(4, 5, 6), Vector3(7, 8, 9)]
+    assert cloud.normals is None and cloud.colors is None
+
+    cloud = read_text("1 2 3 0 0 1\n", "cloud.xyz")
+    assert cloud.normals == [Vector3(0, 0, 1)]
+    cloud = read_text("1 2 3 0 0 1 255 128 0\n", "cloud.xyz")
+    assert cloud.colors == [(255, 128, 0)]
+    assert len(read_text("", "cloud.xyz")) == 0
+
+    assert "line 1: expected 3, 6 or 9 values" in read_error("1 2 3 4\n", "a.xyz")
+    assert "line 2: expected 3 values like the lines before but got 6" in read_error("1 2 3\n1 2 3 4 5 6\n", "a.xyz")
+    assert "line 1: expected a number but got 'x'" in read_error("x 2 3\n", "a.xyz")
+    assert "line 1: colors must be from 0 to 255" in read_error("1 2 3 0 0 1 256 0 0\n", "a.xyz")
@@ -0,0 +1,162 @@
+import array
+import pytest
+from math import radians
+from deuterium import (
+    Isometry3,
+    UnitQuaternion,
+    Vector3,
+    align_points,
+    estimate_normals,
+    icp,
+)
+
+
+def surface(n=15):
+    # A bumpy, asymmetric patch so there's only one way to align it
+    return [
+        Vector3(x, y, 0.3 * x * x + 0.1 * x * y + 0.05 * y * y * y)
+        for x in [i / n * 4 - 2 for i in range(n)]
+        for y in [j / n * 4 - 2 for j in range(n)]
+    ]
+
+
+def test_align_points():
+    source = [Vector3(0, 0, 0), Vector3(1, 0, 0), Vector3(0, 2, 0), Vector3(0, 0, 3), Vector3(1, 1, 1)]
+    iso = Isometry3(Vector3(4, -5, 6), UnitQuaternion.from_axis_angle(Vector3(1, 2, 3), radians(70)))
+    target = [p.transformed(iso) for p in source]
+    assert align_points(source, target).approx_equals(iso, abs_tol=1e-9)
+
+    # Numeric sequences and buffers work too
+    flat = array.array("d", [c for p in target for c in (p.x, p.y, p.z)])
+    buffer = memoryview(flat).cast("B").cast("d", shape=[len(target), 3])
+    assert align_points([[p.x, p.y, p.z] for p in source], buffer).approx_equals(iso, abs_tol=1e-9)
+
+    # Mirrored points can't be aligned exactly, as the result is never a
+    # reflection, but a planar set can be by rotating it over
+    mirrored = [Vector3(-p.x, p.y, p.z) for p in source]
+    result = align_points(source, mirrored)
+    assert max((p.transformed(result) - q).length() for p, q in zip(source, mirrored)) > 0.1
+    planar = [Vector3(0, 0, 0), Vector3(1, 0, 0), Vector3(0, 2, 0), Vector3(3, 1, 0)]
+    mirrored = [Vector3(-p.x, p.y, p.z) for p in planar]
+    result = align_points(planar, mirrored)
+    assert max((p.transformed(result) - q).length() for p, q in zip(planar, mirrored)) < 1e-9
+
+
+def test_align_points_weights():
+    source = [Vector3(0, 0, 0), Vector3(1, 0, 0), Vector3(0, 1, 0), Vector3(5, 5, 5)]
+    target = [Vector3(0, 0, 0), Vector3(1, 0, 0), Vector3(0, 1, 0), Vector3(9, 9, 9)]
+    # With no weight on the outlier the other points align exactly
+    result = align_points(source, target, [1, 1, 1, 0])
+    assert result.approx_equals(Isometry3.identity(), abs_tol=1e-12)
+    assert not align_points(source, target).approx_equals(Isometry3.identity(), abs_tol=1e-3)
+
+    with pytest.raises(ValueError, match="Expected 4 weights but got 3"):
+        align_points(source, target, [1, 1, 1])
+    with pytest.raises(ValueError, match="non-negative"):
+        align_points(source, target, [1, 1, 1, -1])
+    with pytest.raises(ValueError, match="not all zero"):
+        align_points(source, target, [0, 0, 0, 0])
+    with pytest.raises(ValueError, match="Expected 4 target points but got 3"):
+        align_points(source, target[:3])
+    with pytest.raises(ValueError, match="at least one point"):
+        align_points([], [])
+    with pytest.raises(ValueError, match="finite"):
+        align_points([(float("nan"), 0, 0)] + source[1:], target)
+    with pytest.raises(ValueError, match="finite"):
+        align_points(source, target[:3] + [(0, float("inf"), 0)])
+
+
+def test_estimate_normals():
+    points = [Vector3(x, y, 2) for x in range(5) for y in range(5)]
+    normals = estimate_normals(points)
+    assert len(normals) == len(points)
+    for n in normals:
+        assert abs(n.z) == pytest.approx(1)
+    tilted = [p.transformed(Isometry3(rotation=UnitQuaternion.from_axis_angle(Vector3(1, 0, 0), radians(90)))) for p in points]
+    for n in estimate_normals(tilted, k=4):
+        assert abs(n.y) == pytest.approx(1)
+    with pytest.raises(ValueError, match="at least 3"):
+        estimate_normals(points, k=2)
+    with pytest.raises(ValueError, match="finite"):
+        estimate_normals(points + [Vector3(float("nan"), 0, 0)])
+
+
+def test_icp():
+    for method in ["point_to_point", "point_to_plane"]:
+        target = surface()
+        offset = Isometry3(Vector3(0.1, -0.05, 0.08), UnitQuaternion.from_axis_angle(Vector3(1, -1, 2), radians(5)))
+        source = [p.transformed(offset.inverse()) for p in target]
+
+        result = icp(source, target, method=method, max_iterations=200, tolerance=1e-10)
+        assert result.converged
+        assert result.iterations <= 200
+        assert result.transform.approx_equals(offset, abs_tol=1e-6)
+        assert result.fitness == 1
+        assert result.rmse == pytest.approx(0, abs=1e-6)
+        assert result.correspondences == [(i, i) for i in range(len(source))]
+
+        # Starting from the answer converges straight away
+        result = icp(source, target, offset, method=method)
+        assert result.converged
+        assert result.iterations == 1
+        assert result.rmse == pytest.approx(0, abs=1e-9)
+
+
+def test_icp_point_to_plane_converges_faster():
+    target = surface()
+    offset = Isometry3(Vector3(0.1, 0.1, 0), UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(4)))
+    source = [p.transformed(offset.inverse()) for p in target]
+    point = icp(source, target, tolerance=1e-8, max_iterations=500)
+    plane = icp(source, target, method="point_to_plane", tolerance=1e-8, max_iterations=500)
+    assert point.converged and plane.converged
+    assert plane.iterations < point.iterations
+
+    # Explicit target normals are used as given
+    normals = [Vector3(0, 0, 1)] * len(target)
+    result = icp(source, target, method="point_to_plane", target_normals=normals)
+    assert result.iterations >= 1
+    with pytest.raises(ValueError, match="Expected 225 target normals but got 1"):
+        icp(source, target, method="point_to_plane", target_normals=normals[:1])
+
+
+def test_icp_outliers():
+    target = surface()
+    offset = Isometry3(Vector3(0.05, 0, 0.05), UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), radians(3)))
+    source = [p.transformed(offset.inverse()) for p in target]
+    outliers = [Vector3(20, 20, 20), Vector3(-30, 10, 5), Vector3(15, -25, -10)]
+    result = icp(source + outliers, target, max_correspondence_distance=1, tolerance=1e-10, max_iterations=200)
+    assert result.converged
+    assert result.transform.approx_equals(offset, abs_tol=1e-6)
+    assert result.fitness == pytest.approx(len(source) / (len(source) + 3))
+    assert all(i < len(source) for i, _ in result.correspondences)
+
+    # Without a limit the outliers pull the result away
+    result = icp(source + outliers, target, tolerance=1e-10, max_iterations=200)
+    assert result.fitness == 1
+    assert not result.transform.approx_equals(offset, abs_tol=1e-3)
+
+    # Nothing within range
+    result = icp([Vector3(100, 100, 100)], target, max_correspondence_distance=1)
+    assert result.fitness == 0
+    assert result.rmse == float("inf")
+    assert result.iterations == 0
+    assert not result.converged
+    assert result.transform == Isometry3.identity()
+
+
+def test_icp_errors():
+    points = surface(4)
+    with pytest.raises(ValueError, match="method must be"):
+        icp(points, points, method="point_to_line")
+    with pytest.raises(ValueError, match="max_correspondence_distance"):
+        icp(points, points, max_correspondence_distance=0)
+    with pytest.raises(ValueError, match="at least one point"):
+        icp([], points)
+    with pytest.raises(ValueError, match="at least one point"):
+        icp(points, [])
+    with pytest.raises(ValueError, match="at least 3 target points"):
+        icp(points, points[:2], method="point_to_plane")
+    with pytest.raises(ValueError, match="finite"):
+        icp(points + [Vector3(float("nan"), 0, 0)], points)
+    with pytest.raises(ValueError, match="finite"):
+        icp(points, points + [Vector3(0, 0, float("inf"))])
@@ -0,0 +1,187 @@
+import copy
+import pickle
+import pytest
+from math import radians, sqrt
+from deuterium import (
+    Aabb,
+    Isometry3,
+    LineSegment,
+    Matrix4,
+    Plane,
+    Ray,
+    Sphere,
+    Triangle,
+    UnitQuaternion,
+    Vector3,
+)
+
+
+def transform():
+    iso = Isometry3.from_translation(Vector3(1, 2, 3))
+    iso.rotation = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(90))
+    return iso
+
+
+SHAPES = [
+    Plane(Vector3(0, 0, 2), 1.5),
+    Ray(Vector3(1, 2, 3), Vector3(0, 3, 4)),
+    LineSegment(Vector3(1, 2, 3), Vector3(4, 5, 6)),
+    Sphere(Vector3(1, 2, 3), 0.5),
+    Aabb(Vector3(-1, 0, 1), Vector3(2, 3, 4)),
+    Triangle(Vector3(0, 0, 0), Vector3(1, 0, 0), Vector3(0, 1, 0)),
+]
+
+
+def test_pickle():
+    v = Vector3(1, 2.5, -3)
+    assert pickle.loads(pickle.dumps(v)) == v
+    for shape in SHAPES:
+        restored = pickle.loads(pickle.dumps(shape))
+        assert type(restored) is type(shape)
+        assert restored == shape
+        assert repr(restored) == repr(shape)
+        assert copy.deepcopy(shape) == shape
+
+
+def test_transform_types():
+    m = Matrix4.from_translation(Vector3(1, 2, 3))
+    for shape in SHAPES:
+        assert shape.transformed(m).approx_equals(
+            shape.transformed(Isometry3.from_translation(Vector3(1, 2, 3)))
+        )
+        with pytest.raises(TypeError, match=type(shape).__name__):
+            shape.transformed(Vector3())
+        projective = Matrix4()
+        projective[3, 2] = 1.0
+        with pytest.raises(ValueError, match="projective"):
+            shape.transformed(projective)
+
+        moved = copy.deepcopy(shape)
+        moved.transform(m)
+        assert moved == shape.transformed(m)
+
+
+def test_plane():
+    p = Plane(Vector3(0, 0, 2), 1.5)
+    assert p.normal == Vector3(0, 0, 1)
+    assert p.offset == 1.5
+    assert p.signed_distance(Vector3(5, 5, 4)) == 2.5
+    assert p.signed_distance(Vector3(5, 5, 0)) == -1.5
+    assert p.project_point(Vector3(5, 6, 7)) == Vector3(5, 6, 1.5)
+    assert p.flipped().signed_distance(Vector3(5, 5, 4)) == -2.5
+    assert repr(p) == "Plane(normal=Vector3(0, 0, 1), offset=1.5)"
+
+    assert Plane.from_point_normal(Vector3(0, 0, 1.5), Vector3(0, 0, 1)) == p
+    from_points = Plane.from_points(Vector3(0, 0, 1.5), Vector3(1, 0, 1.5), Vector3(0, 1, 1.5))
+    assert from_points.approx_equals(p)
+    with pytest.raises(ValueError, match="non-zero"):
+        Plane(Vector3(), 1.0)
+    with pytest.raises(ValueError):
+        Plane.from_points(Vector3(), Vector3(1, 1, 1), Vector3(2, 2, 2))
+
+    moved = p.transformed(transform())
+    assert moved.approx_equals(Plane(Vector3(0, 0, 1), 4.5))
+
+    # Non-uniform scaling keeps the normal perpendicular to the plane
+    tilted = Plane.from_points(Vector3(1, 0, 0), Vector3(0, 1, 0), Vector3(0, 0, 1))
+    scale = Matrix4.from_scale(Vector3(2, 1, 1))
+    scaled = tilted.transformed(scale)
+    for corner in (Vector3(2, 0, 0), Vector3(0, 1, 0), Vector3(0, 0, 1)):
+        assert abs(scaled.signed_distance(corner)) < 1e-12
+
+    assert p.approx_equals(Plane(Vector3(0, 0, 1), 1.5 + 1e-10))
+    assert not p.approx_equals(Plane(Vector3(0, 0, 1), 1.6))
+
+
+def test_ray():
+    r = Ray(Vector3(1, 2, 3), Vector3(0, 3, 4))
+    assert r.origin == Vector3(1, 2, 3)
+    assert r.direction.approx_equals(Vector3(0, 0.6, 0.8))
+    assert r.at(5).approx_equals(Vector3(1, 5, 7))
+    assert Ray.from_points(Vector3(1, 2, 3), Vector3(1, 5, 7)).approx_equals(r)
+    with pytest.raises(ValueError, match="non-zero"):
+        Ray(Vector3(), Vector3())
+
+    moved = r.transformed(transform())
+    assert moved.origin.approx_equals(Vector3(-1, 3, 6))
+    assert moved.direction.approx_equals(Vector3(-0.6, 0, 0.8))
+    assert r.transformed(Matrix4.from_scale(Vector3(2, 2, 2))).direction.length() == pytest.approx(1.0)
+    assert repr(Ray(Vector3(), Vector3(1, 0, 0))) == "Ray(origin=Vector3(0, 0, 0), direction=Vector3(1, 0, 0))"
+
+
+def test_line_segment():
+    s = LineSegment(Vector3(1, 2, 3), Vector3(4, 6, 3))
+    assert s.start == Vector3(1, 2, 3) and s.end == Vector3(4, 6, 3)
+    assert s.direction() == Vector3(3, 4, 0)
+    assert s.length() == 5
+    assert s.midpoint() == Vector3(2.5, 4, 3)
+    assert s.at(0) == s.start and s.at(1) == s.end
+    assert s.reversed() == LineSegment(Vector3(4, 6, 3), Vector3(1, 2, 3))
+    assert s.transformed(transform()).approx_equals(
+        LineSegment(Vector3(-1, 3, 6), Vector3(-5, 6, 6))
+    )
+    assert repr(s) == "LineSegment(start=Vector3(1, 2, 3), end=Vector3(4, 6, 3))"
+
+
+def test_sphere():
+    s = Sphere(Vector3(1, 2, 3), 2)
+    assert s.center == Vector3(1, 2, 3) and s.radius == 2
+    assert s.contains_point(Vector3(1, 2, 5))
+    assert not s.contains_point(Vector3(1, 2, 5.1))
+    with pytest.raises(ValueError):
+        Sphere(Vector3(), -1)
+
+    assert s.transformed(transform()).approx_equals(Sphere(Vector3(-1, 3, 6), 2))
+    scaled = s.transformed(Matrix4.from_scale(Vector3(3, 3, 3)))
+    assert scaled.approx_equals(Sphere(Vector3(3, 6, 9), 6))
+    with pytest.raises(ValueError, match="non-uniform"):
+        s.transformed(Matrix4.from_scale(Vector3(1, 2, 1)))
+    assert repr(s) == "Sphere(center=Vector3(1, 2, 3), radius=2)"
+
+
+def test_aabb():
+    b = Aabb(Vector3(-1, 0, 1), Vector3(3, 2, 2))
+    assert b.min == Vector3(-1, 0, 1) and b.max == Vector3(3, 2, 2)
+    assert b.center() == Vector3(1, 1, 1.5)
+    assert b.half_extents() == Vector3(2, 1, 0.5)
+    assert b.size() == Vector3(4, 2, 1)
+    assert b.volume() == 8
+    assert b.contains_point(Vector3(3, 2, 2))
+    assert not b.contains_point(Vector3(3, 2, 2.5))
+    assert Aabb.from_center_half_extents(Vector3(1, 1, 1.5), Vector3(2, 1, 0.5)) == b
+    with pytest.raises(ValueError, match="greater"):
+        Aabb(Vector3(1, 0, 0), Vector3(0, 1, 1))
+
+    other = Aabb(Vector3(2, 1, 0), Vector3(5, 5, 1))
+    assert b.intersects(other) and other.intersects(b)
+    assert not b.intersects(Aabb(Vector3(4, 0, 0), Vector3(5, 1, 1)))
+    assert b.union(other) == Aabb(Vector3(-1, 0, 0), Vector3(5, 5, 2))
+
+    # A 90 degree rotation about z swaps the x and y extents
+    moved = b.transformed(transform())
+    assert moved.approx_equals(Aabb(Vector3(-1, 1, 4), Vector3(1, 5, 5)))
+    # A 45 degree rotation grows the box to fit the rotated corners
+    iso = Isometry3.identity()
+    iso.rotation = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(45))
+    unit = Aabb(Vector3(-1, -1, -1), Vector3(1, 1, 1))
+    assert unit.transformed(iso).approx_equals(
+        Aabb(Vector3(-sqrt(2), -sqrt(2), -1), Vector3(sqrt(2), sqrt(2), 1))
+    )
+    assert repr(unit) == "Aabb(min=Vector3(-1, -1, -1), max=Vector3(1, 1, 1))"
+
+
+def test_triangle():
+    t = Triangle(Vector3(0, 0, 0), Vector3(2, 0, 0), Vector3(0, 2, 0))
+    assert t.vertices() == (t.a, t.b, t.c)
+    assert t.normal() == Vector3(0, 0, 1)
+    assert t.flipped().normal() == Vector3(0, 0, -1)
+    assert t.area() == 2
+    assert t.centroid().approx_equals(Vector3(2 / 3, 2 / 3, 0))
+    assert t.at(0, 0) == t.a and t.at(1, 0) == t.b and t.at(0, 1) == t.c
+    with pytest.raises(ValueError):
+        Triangle(Vector3(), Vector3(1, 1, 1), Vector3(2, 2, 2)).normal()
+
+    moved = t.transformed(transform())
+    assert moved.approx_equals(Triangle(Vector3(1, 2, 3), Vector3(1, 4, 3), Vector3(-1, 2, 3)))
+    assert moved.normal().approx_equals(Vector3(0, 0, 1))
+    assert repr(t) == "Triangle(a=Vector3(0, 0, 0), b=Vector3(2, 0, 0), c=Vector3(0, 2, 0))"
@@ -0,0 +1,88 @@
+import pytest
+import warnings
+from deuterium import IllConditionedWarning, Matrix4, Vector3, Vector4
+
+
+A = Matrix4.from_rows([[4, 1, -2, 3], [2, 7, 1, 0], [0, -3, 5, 1], [1, 2, 0, 6]])
+SINGULAR = Matrix4.from_rows([[1, 2, 3, 4], [2, 4, 6, 8], [0, 1, 0, 1], [1, 0, 0, 0]])
+
+
+def test_solve():
+    b = Vector4(1, 2, 3, 4)
+    x = A.solve(b)
+    assert isinstance(x, Vector4)
+    assert (A * x).approx_equals(b, abs_tol=1e-12)
+
+    rhs = Matrix4.from_rows([[1, 0, 2, 0], [0, 1, 0, 3], [4, 0, 1, 0], [0, 5, 0, 1]])
+    xs = A.solve(rhs)
+    assert isinstance(xs, Matrix4)
+    assert (A * xs).approx_equals(rhs, abs_tol=1e-12)
+
+    with pytest.raises(ValueError, match="singular"):
+        SINGULAR.solve(b)
+    with pytest.raises(TypeError):
+        A.solve(1.0)
+
+
+def test_inverse():
+    assert (A * A.inverse()).approx_equals(Matrix4.identity(), abs_tol=1e-12)
+    m = Matrix4(list(A))
+    m.invert()
+    assert m == A.inverse()
+
+    with pytest.raises(ValueError, match="singular"):
+        SINGULAR.inverse()
+    with pytest.raises(ValueError, match="singular"):
+        Matrix4.from_scale(Vector3(1, 0, 1)).inverse()
+
+
+def test_ill_conditioned_warning():
+    assert issubclass(IllConditionedWarning, RuntimeWarning)
+    nearly = Matrix4.from_rows([[1, 1, 0, 0], [1, 1 + 1e-14, 0, 0], [0, 0, 1, 0], [0, 0, 0, 1]])
+    with pytest.warns(IllConditionedWarning):
+        nearly.solve(Vector4(1, 2, 3, 4))
+
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+        A.solve(Vector4(1, 2, 3, 4))
+
+
+def test_pseudo_inverse():
+    assert A.pseudo_inverse().approx_equals(A.inverse(), abs_tol=1e-12)
+
+    p = SINGULAR.pseudo_inverse()
+    # The Moore-Penrose conditions
+    assert (SINGULAR * p * SINGULAR).approx_equals(SINGULAR, abs_tol=1e-9)
+    assert (p * SINGULAR * p).approx_equals(p, abs_tol=1e-9)
+    assert (SINGULAR * p).transposed().approx_equals(SINGULAR * p, abs_tol=1e-9)
+    assert (p * SINGULAR).transposed().approx_equals(p * SINGULAR, abs_tol=1e-9)
+
+    # A huge eps discards every singular value
+    assert A.pseudo_inverse(1e6) == Matrix4([[0] * 4] * 4)
+    with pytest.raises(ValueError):
+        A.pseudo_inverse(-1.0)
+
+
+def test_solve_least_squares():
+    b = Vector4(1, 2, 3, 4)
+    assert A.solve_least_squares(b).approx_equals(A.solve(b), abs_tol=1e-12)
+
+    # b is outside the column space, so the residual is orthogonal to it
+    x = SINGULAR.solve_least_squares(b)
+    residual = SINGULAR * x - b
+    for c in range(4):
+        assert abs(Vector4(*SINGULAR[:, c]).dot(residual)) < 1e-9
+    assert isinstance(SINGULAR.solve_least_squares(Matrix4.identity()), Matrix4)
+
+
+def test_rank_and_condition_number():
+    assert A.rank() == 4
+    assert SINGULAR.rank() == 3
+    assert Matrix4([[0] * 4] * 4).rank() == 0
+    assert Matrix4.from_scale(Vector3(2, 2, 2)).rank() == 4
+
+    assert Matrix4.identity().condition_number() == pytest.approx(1.0)
+    assert Matrix4.from_rows(
+        [[10, 0, 0, 0], [0, 1, 0, 0], [0, 0, 2, 0], [0, 0, 0, 1]]
+    ).condition_number() == pytest.approx(10.0)
+    assert SINGULAR.condition_number() > 1e12
@@ -0,0 +1,175 @@
+import inspect
+import pytest
+from math import radians
+import deuterium
+from deuterium import Isometry3, Matrix3, Matrix4, UnitDualQuaternion, UnitQuaternion, Vector3, Vector4, tolerance
+from deuterium.testing import DEUTERIUM_TYPES, assert_close, components
+
+
+# deuterium's classes which aren't values with components to compare
+NOT_VALUES = {
+    "Aabb",
+    "BrownConrady",
+    "ConvexHull",
+    "DivisionModel",
+    "IllConditionedWarning",
+    "Joint",
+    "KannalaBrandt",
+    "KdTree",
+    "KinematicChain",
+    "LineSegment",
+    "PinholeCamera",
+    "Plane",
+    "PointCloud",
+    "PointCloudFormatError",
+    "Ray",
+    "Sphere",
+    "Triangle",
+    "tolerance",
+}
+
+
+def test_components():
+    assert components(Vector3(1, 2, 3)) == [("x", 1), ("y", 2), ("z", 3)]
+    assert [l for l, _ in components(UnitQuaternion())] == ["i", "j", "k", "w"]
+    assert len(components(Isometry3.identity())) == 7
+    assert len(components(UnitDualQuaternion())) == 8
+    assert components(Matrix4.identity())[5] == ("[1, 1]", 1)
+    assert components(Matrix3.identity())[4] == ("[1, 1]", 1)
+    assert components(Vector3(1, 2, 3).freeze()) == components(Vector3(1, 2, 3))
+
+    with pytest.raises(TypeError):
+        components(1.0)
+
+
+def test_every_type_is_covered():
+    # A new class must either be handled by the helpers or listed here
+    classes = {name for name, value in vars(deuterium).items() if inspect.isclass(value)}
+    assert classes - NOT_VALUES == {t.__name__ for t in DEUTERIUM_TYPES}
+    samples = [
+        Vector3(1, 2, 3),
+        Vector4(1, 2, 3, 4),
+        UnitQuaternion(),
+        Isometry3.identity(),
+        UnitDualQuaternion(),
+        Matrix3.identity(),
+        Matrix4.identity(),
+        Vector3(1, 2, 3).freeze(),
+        UnitQuaternion().freeze(),
+        Isometry3.identity().freeze(),
+    ]
+    assert {type(v) for v in samples} == set(DEUTERIUM_TYPES)
+    for value in samples:
+        assert all(isinstance(c, float) for _, c in components(value))
+        assert_close(value, value)
+
+
+def test_assert_close_passes():
+    assert_close(Vector3(1, 2, 3), Vector3(1, 2, 3 + 1e-10))
+    assert_close(Vector3(1, 2, 3), Vector3(1, 2, 3.001), abs_tol=0.01)
+    assert_close(Matrix4.identity(), Matrix4.identity())
+    assert_close(Isometry3.identity(), Isometry3.identity())
+    assert_close(UnitDualQuaternion(), UnitDualQuaternion())
+    assert_close([Vector3(1, 0, 0), (1.0, Vector3())], [Vector3(1, 0, 0), (1.0, Vector3())])
+
+    with tolerance(abs_tol=0.1):
+        assert_close(Vector3(1, 2, 3), Vector3(1, 2, 3.05))
+
+
+def test_assert_close_reports_components():
+    with pytest.raises(AssertionError) as info:
+        assert_close(Vector3(1, 2, 3), Vector3(1, 2.5, 4), msg="positions differ")
+    message = str(info.value)
+    assert message.startswith("positions differ")
+    assert "  y " in message and "  z " in message
+    assert "  x " not in message
+    assert "-0.5" in message
+
+    m = Matrix4.identity()
+    m[2, 3] = 5
+    with pytest.raises(AssertionError, match=r"\[2, 3\]"):
+        assert_close(m, Matrix4.identity())
+
+    with pytest.raises(AssertionError, match="translation.x"):
+        assert_close(Isometry3.from_translation(Vector3(1, 0, 0)), Isometry3.identity())
+
+    with pytest.raises(AssertionError, match=r"\[1\]\.z"):
+        assert_close([Vector3(), Vector3(0, 0, 1)], [Vector3(), Vector3()])
+
+    with pytest.raises(AssertionError, match="type mismatch"):
+        assert_close(Vector3(), UnitQuaternion())
+
+    with pytest.raises(AssertionError, match="length mismatch"):
+        assert_close([Vector3()], [Vector3(), Vector3()])
+
+
+def test_assert_close_as_rotation():
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), radians(30))
+    negated = UnitQuaternion.from_axis_angle(Vector3(0, -1, 0), radians(330))
+    with pytest.raises(AssertionError):
+        assert_close(q, negated)
+    assert_close(q, negated, as_rotation=True)
+
+
+def test_strategies():
+    hypothesis = pytest.importorskip("hypothesis")
+    from deuterium.testing import strategies
+
+    @hypothesis.given(strategies.vectors(-10, 10))
+    def check_vectors(v):
+        assert all(-10 <= c <= 10 for c in v.tuple())
+
+    @hypothesis.given(strategies.unit_quaternions())
+    def check_quaternions(q):
+        assert abs(sum(c * c for c in q.tuple()) - 1) < 1e-9
+
+    @hypothesis.given(strategies.isometries())
+    def check_isometries(iso):
+        assert_close(iso * iso.inverse(), Isometry3.identity(), abs_tol=1e-6, as_rotation=True)
+
+    @hypothesis.given(strategies.invertible_matrices())
+    def check_matrices(m):
+        assert any(c != 0 for row in m.tuple() for c in row)
+
+    check_vectors()
+    check_quaternions()
+    check_isometries()
+    check_matrices()
+
+
+def test_approx():
+    from deuterium.testing import approx
+
+    assert Vector3(1, 2, 3) == approx(Vector3(1, 2, 3 + 1e-10))
+    assert Vector3(1, 2, 3) != approx(Vector3(1, 2, 3.1))
+    assert Matrix4.identity() == approx(Matrix4.identity())
+    assert Matrix3.identity() == approx(Matrix3.identity())
+    assert Isometry3.identity() == approx(Isometry3.identity())
+    assert Vector3() != approx(UnitQuaternion())
+    assert Vector3(1, 2, 3) == approx(Vector3(1, 2, 3.1), abs_tol=0.2)
+    assert [1.0, Vector3()] == approx([1.0, Vector3()])
+    assert repr(approx(1.5)) == "approx(1.5)"
+
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), radians(30))
+    negated = UnitQuaternion.from_axis_angle(Vector3(0, -1, 0), radians(330))
+    assert q != approx(negated)
+    assert q == approx(negated, as_rotation=True)
+
+    # Tolerances are fixed when approx is called
+    with tolerance(abs_tol=0.1):
+        expected = approx(Vector3(1, 2, 3.05))
+    assert Vector3(1, 2, 3) == expected
+
+
+def test_pytest_plugin():
+    from deuterium.testing import approx
+    from deuterium.testing.pytest_plugin import pytest_assertrepr_compare
+
+    lines = pytest_assertrepr_compare(None, "==", Vector3(1, 2, 3), approx(Vector3(1, 2.5, 3)))
+    assert lines[0].startswith("Vector3(1, 2, 3) is not close to")
+    assert any(line.startswith("  y ") for line in lines)
+    assert not any(line.startswith("  x ") for line in lines)
+    assert pytest_assertrepr_compare(None, "==", approx(Vector3()), Vector3(0, 0, 1))[-1].startswith("  z ")
+    assert pytest_assertrepr_compare(None, "==", Vector3(), approx(Vector3())) is None
+    assert pytest_assertrepr_compare(None, "==", Vector3(), Vector3(1, 0, 0)) is None
+    assert pytest_assertrepr_compare(None, "!=", Vector3(), approx(Vector3(1, 0, 0))) is None
@@ -0,0 +1,90 @@
+import threading
+import pytest
+from math import radians
+from deuterium import Isometry3, Matrix4, UnitQuaternion, Vector3, tolerance
+
+
+def test_keyword_tolerances():
+    a = Vector3(0, 0, 0)
+    b = Vector3(0, 0, 1e-5)
+    assert not a.approx_equals(b)
+    assert a.approx_equals(b, abs_tol=1e-4)
+    assert not a.approx_equals(b, abs_tol=1e-6)
+
+    big = Vector3(1e6, 0, 0)
+    assert not big.approx_equals(Vector3(1e6 + 1e-3, 0, 0))
+    assert big.approx_equals(Vector3(1e6 + 1e-3, 0, 0), rel_tol=1e-8)
+
+    one = Vector3(1, 0, 0)
+    next_up = Vector3(1.0000000000000002, 0, 0)
+    assert one.approx_equals(next_up, abs_tol=0, max_ulps=1)
+    assert not one.approx_equals(next_up, abs_tol=0)
+
+    with pytest.raises(ValueError):
+        a.approx_equals(b, abs_tol=-1)
+
+
+def test_keyword_tolerances_on_all_types():
+    m = Matrix4.identity()
+    m2 = Matrix4.identity()
+    m2[0, 3] = 1e-5
+    assert not m.approx_equals(m2)
+    assert m.approx_equals(m2, abs_tol=1e-4)
+
+    i = Isometry3.identity()
+    i2 = Isometry3.from_translation(Vector3(1e-5, 0, 0))
+    assert not i.approx_equals(i2)
+    assert i.approx_equals(i2, abs_tol=1e-4)
+
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(10))
+    q2 = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(10.001))
+    assert not q.approx_equals(q2)
+    assert q.approx_equals(q2, abs_tol=1e-4)
+
+
+def test_quaternion_as_rotation():
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 1, 0), radians(30))
+    # The same rotation, but the long way around the axis
+    negated = UnitQuaternion.from_axis_angle(Vector3(0, -1, 0), radians(330))
+    assert not q.approx_equals(negated)
+    assert q.approx_equals(negated, as_rotation=True)
+    assert not q.approx_equals(UnitQuaternion.identity(), as_rotation=True)
+
+
+def test_context_manager():
+    a = Vector3(0, 0, 0)
+    b = Vector3(0, 0, 1e-5)
+    assert tolerance.current() == (1e-8, 0, 0)
+    with tolerance(abs_tol=1e-4):
+        assert tolerance.current() == (1e-4, 0, 0)
+        assert a.approx_equals(b)
+        # Explicit arguments still take precedence
+        assert not a.approx_equals(b, abs_tol=1e-6)
+        with tolerance(rel_tol=1e-3):
+            assert tolerance.current() == (1e-4, 1e-3, 0)
+        assert tolerance.current() == (1e-4, 0, 0)
+    assert tolerance.current() == (1e-8, 0, 0)
+    assert not a.approx_equals(b)
+
+
+def test_context_manager_restores_on_error():
+    with pytest.raises(RuntimeError):
+        with tolerance(abs_tol=1):
+            raise RuntimeError()
+    assert tolerance.current() == (1e-8, 0, 0)
+
+    with pytest.raises(ValueError):
+        tolerance(rel_tol=-1)
+
+
+def test_context_manager_is_per_thread():
+    seen = []
+
+    def worker():
+        seen.append(tolerance.current())
+
+    with tolerance(abs_tol=0.5):
+        t = threading.Thread(target=worker)
+        t.start()
+        t.join()
+    assert seen == [(1e-8, 0, 0)]
@@ -0,0 +1,101 @@
+import pytest
+from math import radians
+from deuterium import Isometry3, UnitDualQuaternion, UnitQuaternion, Vector3
+
+
+def make_iso(axis, angle, translation):
+    iso = Isometry3.from_translation(translation)
+    iso.rotation = UnitQuaternion.from_axis_angle(axis, angle)
+    return iso
+
+
+def test_constructor():
+    assert UnitDualQuaternion()
+    assert UnitDualQuaternion() == UnitDualQuaternion.identity()
+    assert UnitDualQuaternion().to_isometry() == Isometry3.identity()
+
+
+def test_isometry_round_trip():
+    iso = make_iso(Vector3(1, 2, 3), radians(40), Vector3(4, -5, 6))
+    dq = UnitDualQuaternion.from_isometry(iso)
+    assert dq.to_isometry().approx_equals(iso)
+    assert dq.rotation.approx_equals(iso.rotation)
+    assert dq.translation.approx_equals(iso.translation)
+
+    parts = UnitDualQuaternion.from_parts(iso.translation, iso.rotation)
+    assert parts.approx_equals(dq)
+
+
+def test_composition():
+    a = make_iso(Vector3(0, 0, 1), radians(30), Vector3(1, 0, 0))
+    b = make_iso(Vector3(1, 0, 0), radians(-75), Vector3(0, 2, 3))
+    dqa = UnitDualQuaternion.from_isometry(a)
+    dqb = UnitDualQuaternion.from_isometry(b)
+    assert (dqa * dqb).to_isometry().approx_equals(a * b)
+
+    dq = UnitDualQuaternion.from_isometry(a)
+    dq *= dqb
+    assert dq.to_isometry().approx_equals(a * b)
+
+    dq = UnitDualQuaternion.from_isometry(a)
+    dq.premultiply(dqb)
+    assert dq.to_isometry().approx_equals(b * a)
+
+    assert dqa.inverse().to_isometry().approx_equals(a.inverse())
+    dqa.invert()
+    assert dqa.to_isometry().approx_equals(a.inverse())
+
+
+def test_transform_point():
+    iso = make_iso(Vector3(1, 1, 0), radians(120), Vector3(-1, 2, 0.5))
+    dq = UnitDualQuaternion.from_isometry(iso)
+    v = Vector3(3, -2, 7)
+    assert (dq * v).approx_equals(iso * v)
+    assert dq.transform_point(v).approx_equals(iso * v)
+    assert dq.transform_vector(v).approx_equals(iso.rotation * v)
+
+
+def test_sclerp():
+    a = make_iso(Vector3(0, 0, 1), radians(10), Vector3(0, 0, 0))
+    b = make_iso(Vector3(0, 0, 1), radians(90), Vector3(0, 0, 4))
+    dqa = UnitDualQuaternion.from_isometry(a)
+    dqb = UnitDualQuaternion.from_isometry(b)
+
+    assert dqa.sclerp(dqb, 0).to_isometry().approx_equals(a)
+    assert dqa.sclerp(dqb, 1).to_isometry().approx_equals(b)
+
+    # A pure screw about z: halfway rotates by the mean angle and rises halfway
+    half = dqa.sclerp(dqb, 0.5).to_isometry()
+    expected = make_iso(Vector3(0, 0, 1), radians(50), Vector3(0, 0, 2))
+    assert half.approx_equals(expected)
+
+    flipped = UnitDualQuaternion.from_isometry(
+        make_iso(Vector3(0, 0, 1), radians(190), Vector3(0, 0, 0))
+    )
+    with pytest.raises(ValueError):
+        dqa.sclerp(flipped, 0.5)
+
+
+def test_blend():
+    a = make_iso(Vector3(0, 1, 0), radians(20), Vector3(1, 2, 3))
+    dqa = UnitDualQuaternion.from_isometry(a)
+    assert UnitDualQuaternion.blend([dqa]).to_isometry().approx_equals(a)
+    assert UnitDualQuaternion.blend([dqa, dqa], [0.25, 0.75]).to_isometry().approx_equals(a)
+
+    # Pure translations blend linearly
+    t1 = UnitDualQuaternion.from_isometry(Isometry3.from_translation(Vector3(0, 0, 0)))
+    t2 = UnitDualQuaternion.from_isometry(Isometry3.from_translation(Vector3(4, 0, 0)))
+    blended = UnitDualQuaternion.blend([t1, t2], [0.75, 0.25])
+    assert blended.translation.approx_equals(Vector3(1, 0, 0))
+
+    # Rotations about the same axis blend to the mean angle
+    r1 = UnitDualQuaternion.from_isometry(make_iso(Vector3(0, 0, 1), radians(0), Vector3()))
+    r2 = UnitDualQuaternion.from_isometry(make_iso(Vector3(0, 0, 1), radians(60), Vector3()))
+    blended = UnitDualQuaternion.blend([r1, r2])
+    assert blended.rotation.approx_equals(UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(30)))
+
+    with pytest.raises(ValueError):
+        UnitDualQuaternion.blend([])
+
+    with pytest.raises(ValueError):
+        UnitDualQuaternion.blend([t1, t2], [1.0])
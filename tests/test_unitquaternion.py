@@ -8,6 +8,31 @@ def test_constructor():
     assert UnitQuaternion() == UnitQuaternion.identity()
 
 
+def test_sequence():
+    q = UnitQuaternion.from_axis_angle(Vector3(0, 0, 1), radians(90))
+    assert len(q) == 4
+    assert q.w == q[3] == q[-1]
+    assert (q.i, q.j, q.k) == q[:3] == (q[0], q[1], q[-2])
+    assert q[::-1] == tuple(reversed(q))
+    assert list(q) == q.list()
+    i, j, k, w = q
+    assert (i, j, k, w) == q.tuple()
+    assert q.w in q
+    assert "w" not in q
+
+    with pytest.raises(IndexError):
+        q[4]
+
+    with pytest.raises(IndexError):
+        q[-5]
+
+    match UnitQuaternion():
+        case UnitQuaternion(0, 0, 0, w):
+            assert w == 1
+        case _:
+            assert False
+
+
 def test_euler_convention():
     roll, pitch, yaw = radians(30), radians(45), radians(60)
 
@@ -47,11 +47,24 @@ def test_indexing():
     with pytest.raises(IndexError):
         Vector3(1, 2, 3)[-4]
 
+    with pytest.raises(TypeError):
+        Vector3(1, 2, 3)["x"]
+
     assert Vector3(1, 2, 3).x == 1
     assert Vector3(1, 2, 3).y == 2
     assert Vector3(1, 2, 3).z == 3
 
 
+def test_slicing():
+    v = Vector3(1, 2, 3)
+    assert v[:] == (1, 2, 3)
+    assert v[1:] == (2, 3)
+    assert v[:-1] == (1, 2)
+    assert v[::-1] == (3, 2, 1)
+    assert v[::2] == (1, 3)
+    assert v[5:] == ()
+
+
 def test_index_mutation():
     v = Vector3(1, 2, 3)
     v[0] = 99
@@ -66,12 +79,57 @@ def test_index_mutation():
     assert v == Vector3(-1, -1, 12)
     v.z = -1
     assert v == Vector3(-1, -1, -1)
+    v[-1] = 3
+    assert v == Vector3(-1, -1, 3)
+    v[-2] = 2
+    assert v == Vector3(-1, 2, 3)
+    v[-3] = 1
+    assert v == Vector3(1, 2, 3)
+
+    with pytest.raises(IndexError):
+        v[3] = 0
+
+    with pytest.raises(IndexError):
+        v[-4] = 0
+
+    v[1:] = (20, 30)
+    assert v == Vector3(1, 20, 30)
+    v[::-1] = [3, 2, 1]
+    assert v == Vector3(1, 2, 3)
+
+    with pytest.raises(ValueError):
+        v[1:] = (1, 2, 3)
 
 
 
 def test_iter():
     assert len(Vector3(0, 0, 0)) == 3
     assert list(Vector3(1, 2, 3)) == [1, 2, 3]
+    assert list(reversed(Vector3(1, 2, 3))) == [3, 2, 1]
+    x, y, z = Vector3(4, 5, 6)
+    assert (x, y, z) == (4, 5, 6)
+
+    it = iter(Vector3(1, 2, 3))
+    assert next(it) == 1
+    assert list(it) == [2, 3]
+
+
+def test_contains_and_bool():
+    assert 2 in Vector3(1, 2, 3)
+    assert 4 not in Vector3(1, 2, 3)
+    assert "a" not in Vector3(1, 2, 3)
+    assert None not in Vector3(1, 2, 3)
+    assert Vector3(0, 0, 1)
+    assert not Vector3()
+    assert not Vector3(-0.0, 0, 0)
+
+
+def test_match():
+    match Vector3(1, 0, 3):
+        case Vector3(x, 0, z):
+            assert (x, z) == (1, 3)
+        case _:
+            assert False
 
 
 def test_length_and_normalization():
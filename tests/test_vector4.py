@@ -0,0 +1,44 @@
+import pytest
+from deuterium import Matrix4, Vector4
+
+
+def test_construction():
+    assert Vector4(1, 2, 3, 4) == Vector4(1.0, 2.0, 3.0, 4.0)
+    assert Vector4() == Vector4(0, 0, 0, 0)
+    assert Vector4(w=1) == Vector4(0, 0, 0, 1)
+    assert repr(Vector4(1, 2, 3, 4)) == "Vector4(1, 2, 3, 4)"
+
+
+def test_sequence():
+    v = Vector4(1, 2, 3, 4)
+    assert len(v) == 4
+    assert list(v) == [1, 2, 3, 4]
+    assert v[-1] == v.w == 4
+    assert v[1:3] == (2, 3)
+    v[0] = 10
+    assert v.x == 10
+    v.y = 20
+    assert v.tuple() == (10, 20, 3, 4)
+
+    with pytest.raises(IndexError):
+        v[4]
+
+
+def test_ops():
+    a = Vector4(1, 2, 3, 4)
+    assert a + a == a * 2 == 2 * a
+    assert a - a == Vector4()
+    assert -a == Vector4(-1, -2, -3, -4)
+    assert a / 2 == Vector4(0.5, 1, 1.5, 2)
+    assert a * a == Vector4(1, 4, 9, 16)
+    assert a.dot(a) == 30
+    assert a.length_squared() == 30
+    assert Vector4(0, 3, 0, 4).length() == 5
+    assert Vector4(0, 3, 0, 4).normalized().approx_equals(Vector4(0, 0.6, 0, 0.8))
+    assert not Vector4()
+
+
+def test_matrix_product():
+    m = Matrix4.from_rows([[1, 0, 0, 1], [0, 2, 0, 0], [0, 0, 3, 0], [0, 0, 0, 1]])
+    assert m * Vector4(1, 1, 1, 1) == Vector4(2, 2, 3, 1)
+    assert m * Vector4(1, 1, 1, 0) == Vector4(1, 2, 3, 0)